@@ -1,3 +1,4 @@
+use crate::storage::WorkspacePath;
 use crate::SharedAppState;
 use serde_json::json;
 use std::path::Path;
@@ -36,13 +37,17 @@ fn write_state_json(workspace_path: &str, file_path: Option<&str>) {
         "noteCount": count_notes(workspace_path),
     });
 
-    if let Err(e) = std::fs::write(&state_path, serde_json::to_string_pretty(&state).unwrap_or_default()) {
+    let content = serde_json::to_string_pretty(&state).unwrap_or_default();
+    if let Err(e) = crate::storage::write_file_atomic(&state_path, &content) {
         tracing::warn!("Failed to write state.json: {}", e);
     }
 }
 
 /// Update the app state with current file and workspace information
-/// Called by the frontend when the current file or workspace changes
+/// Called by the frontend when the current file or workspace changes.
+/// Workspaces are opened by `open_workspace`; a `workspace_path` passed here (an
+/// ID or root path) picks which open workspace the file belongs to, defaulting to
+/// the active one.
 #[tauri::command]
 pub async fn update_app_state(
     state: State<'_, SharedAppState>,
@@ -50,31 +55,34 @@ pub async fn update_app_state(
     file_content: Option<String>,
     workspace_path: Option<String>,
 ) -> Result<(), String> {
+    let workspace = match workspace_path {
+        Some(ref path) => WorkspacePath::check_workspace(&state, path).await?,
+        None => match WorkspacePath::workspace(&state).await {
+            Ok(workspace) => workspace,
+            Err(_) => return Ok(()),
+        },
+    };
+
     let mut app_state = state.write().await;
+    let Some(session) = app_state.workspaces.get_mut(workspace.id()) else {
+        return Ok(());
+    };
 
-    if let Some(ref path) = file_path {
-        app_state.current_file_path = Some(path.clone());
+    if let Some(path) = file_path {
+        session.current_file_path = Some(path);
     }
 
     if let Some(content) = file_content {
-        app_state.current_file_content = Some(content);
-    }
-
-    if let Some(ref path) = workspace_path {
-        app_state.workspace_path = Some(path.clone());
+        session.current_file_content = Some(content);
     }
 
-    // Write state.json to .chronicle/ if we have a workspace path
-    let ws = app_state.workspace_path.clone();
-    let fp = app_state.current_file_path.clone();
-    if let Some(ref ws_path) = ws {
-        write_state_json(ws_path, fp.as_deref());
-    }
+    // Write state.json to the workspace's .chronicle/
+    write_state_json(&session.path, session.current_file_path.as_deref());
 
     tracing::debug!(
-        "App state updated: file_set={}, workspace_set={}",
-        app_state.current_file_path.is_some(),
-        app_state.workspace_path.is_some()
+        "App state updated: workspace={}, file_set={}",
+        session.id,
+        session.current_file_path.is_some()
     );
 
     Ok(())
@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::pipeline::{
+    list_processed_outputs, processed_freshness, run_process_task, AutoProcessQueue,
+    ProcessedManifest,
+};
+use crate::storage::WorkspacePath;
+use crate::tasks::TaskTrigger;
+use crate::SharedAppState;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -13,37 +21,24 @@ pub struct ProcessedNoteInfo {
     pub action_count: usize,
     pub question_count: usize,
     pub processed_at: Option<String>,
+    /// The note changed since it was processed
+    pub stale: bool,
+    pub lines_changed: Option<usize>,
 }
 
 /// List all processed notes with their summary info
 #[tauri::command]
 pub async fn list_processed_notes(
     workspace_path: String,
+    state: State<'_, SharedAppState>,
 ) -> Result<Vec<ProcessedNoteInfo>, String> {
-    let processed_dir = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("processed");
-    if !processed_dir.exists() {
-        return Ok(vec![]);
-    }
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let workspace = workspace.opened_root();
+    let manifest = ProcessedManifest::load(workspace);
 
     let mut notes = Vec::new();
-    let entries = std::fs::read_dir(&processed_dir)
-        .map_err(|e| format!("Failed to read processed dir: {}", e))?;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("json") {
-            continue;
-        }
-
-        let filename = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("")
-            .to_string();
-        let note_name = filename.trim_end_matches(".json").to_string();
+    for (key, path) in list_processed_outputs(workspace) {
+        let note_name = key.rsplit('/').next().unwrap_or(&key).to_string();
 
         // Read and parse the processed JSON
         let content = match std::fs::read_to_string(&path) {
@@ -88,7 +83,15 @@ pub async fn list_processed_notes(
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        let note_path = format!("{}.md", note_name);
+        let (note, freshness) = processed_freshness(workspace, &key, &json, &manifest);
+        let note_path = match note {
+            Some(note) => note
+                .strip_prefix(workspace)
+                .unwrap_or(&note)
+                .display()
+                .to_string(),
+            None => format!("{}.md", key),
+        };
 
         notes.push(ProcessedNoteInfo {
             note_path,
@@ -99,6 +102,8 @@ pub async fn list_processed_notes(
             action_count,
             question_count,
             processed_at,
+            stale: freshness.stale,
+            lines_changed: freshness.lines_changed,
         });
     }
 
@@ -111,3 +116,65 @@ pub async fn list_processed_notes(
 
     Ok(notes)
 }
+
+/// Reprocess every note whose processed output is stale, one at a time.
+/// Returns the workspace-relative paths of the notes that were reprocessed successfully.
+#[tauri::command]
+pub async fn reprocess_stale_notes(
+    app_handle: AppHandle,
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<String>, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let stale: Vec<String> = list_processed_notes(workspace_path.clone(), state)
+        .await?
+        .into_iter()
+        .filter(|n| n.stale)
+        .map(|n| n.note_path)
+        .collect();
+
+    let queue = app_handle.state::<AutoProcessQueue>();
+    let mut reprocessed = Vec::new();
+
+    for (index, note) in stale.iter().enumerate() {
+        let Some(full_path) = workspace.join(note).ok().filter(|p| p.is_file()) else {
+            continue;
+        };
+
+        app_handle
+            .emit(
+                "archive:reprocess-progress",
+                ReprocessProgress {
+                    note: note.clone(),
+                    index,
+                    total: stale.len(),
+                },
+            )
+            .ok();
+
+        // Share the auto-process queue so sessions ending meanwhile wait their turn
+        let _running = queue.exclusive(workspace.id()).await;
+        let result = run_process_task(
+            &app_handle,
+            &workspace_path,
+            &full_path.display_path().display().to_string(),
+            TaskTrigger::Manual,
+        )
+        .await;
+
+        match result {
+            Ok(()) => reprocessed.push(note.clone()),
+            Err(e) => tracing::warn!("Failed to reprocess {}: {}", note, e),
+        }
+    }
+
+    Ok(reprocessed)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReprocessProgress {
+    note: String,
+    index: usize,
+    total: usize,
+}
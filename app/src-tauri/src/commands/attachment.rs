@@ -0,0 +1,97 @@
+use crate::models::AttachmentKind;
+use crate::notes::{self, ImportedAttachment};
+use crate::storage::{WorkspaceConfig, WorkspacePath};
+use crate::SharedAppState;
+use std::path::Path;
+use tauri::State;
+
+/// Reject attachments over the workspace's `files.max_attachment_size_mb`
+fn check_attachment_size(workspace_path: &Path, size: u64) -> Result<(), String> {
+    let files = WorkspaceConfig::load(workspace_path).files;
+    if size > files.max_attachment_size() {
+        return Err(format!(
+            "Attachments must be files of at most {}MB",
+            files.max_attachment_size_mb
+        ));
+    }
+    Ok(())
+}
+
+/// Copy a file (picked or dropped onto a note) into the workspace's assets folder
+/// and return the markdown that links it from the note
+#[tauri::command]
+pub async fn import_attachment(
+    note_path: String,
+    source_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<ImportedAttachment, String> {
+    let note = WorkspacePath::resolve(&state, &note_path).await?;
+
+    // The source is outside the workspace; only files that are attachments are read
+    let source = Path::new(&source_path);
+    let file_name = source
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|_| AttachmentKind::of(source).is_some())
+        .ok_or_else(|| format!("Not an attachment: {}", source_path))?;
+    let metadata =
+        std::fs::metadata(source).map_err(|e| format!("Cannot access attachment: {}", e))?;
+    if !metadata.is_file() {
+        return Err(format!("Not an attachment: {}", source_path));
+    }
+    check_attachment_size(note.root(), metadata.len())?;
+    let content = std::fs::read(source).map_err(|e| format!("Failed to read attachment: {}", e))?;
+
+    store_attachment(&state, &note, file_name, &content).await
+}
+
+/// Store pasted data (e.g. a screenshot) as an attachment of a note and return
+/// the markdown that links it
+#[tauri::command]
+pub async fn import_attachment_data(
+    note_path: String,
+    file_name: String,
+    data: Vec<u8>,
+    state: State<'_, SharedAppState>,
+) -> Result<ImportedAttachment, String> {
+    let note = WorkspacePath::resolve(&state, &note_path).await?;
+    check_attachment_size(note.root(), data.len() as u64)?;
+    store_attachment(&state, &note, &file_name, &data).await
+}
+
+async fn store_attachment(
+    state: &SharedAppState,
+    note: &WorkspacePath,
+    file_name: &str,
+    content: &[u8],
+) -> Result<ImportedAttachment, String> {
+    let attachment =
+        notes::import_attachment(note.root(), &note.relative_str(), file_name, content)
+            .map_err(|e| format!("Failed to import attachment: {}", e))?;
+    state
+        .write()
+        .await
+        .record_user_write(note.root().join(&attachment.path));
+    Ok(attachment)
+}
+
+/// Open an attachment in the app the system uses for it
+#[tauri::command]
+pub async fn open_attachment(path: String, state: State<'_, SharedAppState>) -> Result<(), String> {
+    let attachment = WorkspacePath::resolve(&state, &path).await?;
+    if AttachmentKind::of(attachment.as_path()).is_none() {
+        return Err(format!("Not an attachment: {}", path));
+    }
+    tauri_plugin_opener::open_path(attachment.as_path(), None::<&str>)
+        .map_err(|e| format!("Failed to open attachment: {}", e))
+}
+
+/// Attachments in the assets folder that no note links to, workspace-relative
+#[tauri::command]
+pub async fn list_orphaned_attachments(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<String>, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    Ok(notes::find_orphaned_attachments(workspace.root()))
+}
@@ -3,6 +3,7 @@ use serde_json::Value;
 use std::path::Path;
 use tauri::State;
 
+use crate::storage::{WorkspaceConfig, WorkspacePath, SETTING_PLACEHOLDERS};
 use crate::watcher::ChronicleWatcher;
 use crate::SharedAppState;
 
@@ -47,6 +48,21 @@ Chronicle reads this file before every processing and tagging run to maintain co
 <!-- Auto-discovered -->
 "##;
 
+/// context.md with these sections in place of the default People, Projects, ...
+/// ones, keeping the header and the auto-discovered part
+pub fn context_with_sections(sections: &str) -> String {
+    let start = CONTEXT_TEMPLATE.find("\n## ").map_or(0, |i| i + 1);
+    let end = CONTEXT_TEMPLATE
+        .find("---\n*Auto-discovered")
+        .unwrap_or(CONTEXT_TEMPLATE.len());
+    format!(
+        "{}{}{}",
+        &CONTEXT_TEMPLATE[..start],
+        sections,
+        &CONTEXT_TEMPLATE[end..]
+    )
+}
+
 /// Default prompt file contents
 const PROCESS_PROMPT: &str = r#"You are Chronicle's note processor. Your job is to transform raw meeting notes into a structured summary.
 
@@ -63,7 +79,7 @@ The note may contain semantic markers:
 - Lines starting with [x] are completed action items
 - Lines starting with @ attribute text to a person
 
-Create a structured output and write it as JSON to .chronicle/processed/{filename}.json with this schema:
+Create a structured output and write it as JSON to the output path given in the task (.chronicle/processed/ mirrors the workspace folders, e.g. projects/standup.md -> .chronicle/processed/projects/standup.json) with this schema:
 {
   "tldr": "2-3 sentence summary",
   "keyPoints": ["point1", "point2"],
@@ -89,7 +105,7 @@ Entity extraction guidelines:
 - **Topics**: Main discussion topics as lowercase slugs.
 - **References**: Any mentions of other notes, dates, or meetings (e.g., "last Tuesday's standup", "the planning doc").
 
-Also write a human-readable version to .chronicle/processed/{filename}.md with sections:
+Also write a human-readable version to the markdown output path given in the task with sections:
 ## TL;DR
 ## Key Points
 ## Action Items
@@ -98,12 +114,12 @@ Also write a human-readable version to .chronicle/processed/{filename}.md with s
 
 const TAGGER_PROMPT: &str = r##"You are Chronicle's tagger. Your job is to extract meaningful categorized tags from notes.
 
-Read .chronicle/agent-runs.json to find when the tagger last ran.
 Read .chronicle/tags.json for the current tag index (including any existing categories).
 Read .chronicle/context.md for workspace context — known people, projects, and terminology. Use this to map names to existing person tags and resolve ambiguous references.
 
-Find all .md files in the workspace that were modified after the last tagger run.
-For each modified note, extract tags using the category:name format:
+The notes that changed since your last run are listed at the end of this prompt, each with its note ID. Only read those notes.
+If a note starts with YAML front matter (between --- lines), use it first: its tags are always kept as-is, attendees become person: tags and project becomes a project: tag.
+For each listed note, extract tags using the category:name format:
 - People mentioned (@ markers or names in context) -> person:john, person:sarah
 - Projects or topics discussed -> topic:api-redesign, topic:onboarding
 - Meeting types -> meeting:standup, meeting:1on1, meeting:planning
@@ -129,25 +145,26 @@ Update .chronicle/tags.json with this schema:
     "theme": { "label": "Themes", "color": "#ce9178" }
   },
   "byNote": {
-    "path/to/note.md": ["person:john", "topic:architecture"]
+    "<note id>": ["person:john", "topic:architecture"]
   },
   "byTag": {
-    "person:john": ["path/to/note1.md"],
-    "topic:architecture": ["path/to/note1.md", "path/to/note2.md"]
+    "person:john": ["<note id>"],
+    "topic:architecture": ["<note id>", "<other note id>"]
   }
 }
 
+Refer to notes by their note ID, never by path: IDs stay the same when notes are renamed or moved.
+
 Preserve any existing categories and their colors. Add new categories as you discover them.
-Update .chronicle/agent-runs.json with: {"tagger": "ISO timestamp"}
+Replace the byNote entries of the listed notes and update byTag to match. Keep entries for all other notes unchanged.
 "##;
 
 const ACTIONS_PROMPT: &str = r#"You are Chronicle's action tracker. Your job is to find and track action items across all notes.
 
 Read .chronicle/actions.json for existing tracked actions.
-Read .chronicle/agent-runs.json for last run time.
 Read .chronicle/context.md for workspace context. Use it to identify action item owners by name and resolve references like "Sarah" to the correct person.
 
-Scan all .md files in the workspace for action items:
+The notes that changed since your last run are listed at the end of this prompt, each with its note ID. Scan only those notes for action items:
 - [] markers = open action items
 - [x] markers = completed action items
 - Look for implicit actions ("need to", "should", "will", "TODO")
@@ -155,29 +172,28 @@ Scan all .md files in the workspace for action items:
 For each action item found:
 - Extract the text
 - Identify the owner (from @ marker or context)
-- Note which file it came from and what line
-- Determine status: open, done, or stale (open + older than 7 days)
+- Note which note it came from (its note ID, never its path) and what line
+- Determine status: open, done, or stale (open + older than {{stale_after_days}} days)
+
+Replace the existing actions whose source is one of the listed notes with what you found. Keep all other actions, but re-check their status (open actions older than {{stale_after_days}} days become stale).
 
 Write updated .chronicle/actions.json:
 [
   {
     "text": "Follow up with Sarah on API timeline",
     "owner": "me",
-    "source": "2026-02-22-standup.md",
+    "source": "<note id>",
     "line": 15,
     "created": "2026-02-22",
     "status": "open|done|stale"
   }
 ]
-
-Update .chronicle/agent-runs.json with: {"actions": "ISO timestamp"}
 "#;
 
 const CONTEXT_UPDATER_PROMPT: &str = r##"You are Chronicle's context updater. Your job is to discover new people, projects, and terminology from recently processed notes and append them to .chronicle/context.md.
 
 Read .chronicle/context.md for existing context.
-Read .chronicle/agent-runs.json to find when the context updater last ran.
-Read all .chronicle/processed/*.json files modified after the last run.
+The processed notes that changed since your last run are listed at the end of this prompt. Read only those files.
 
 For each processed note, look for:
 - New people not already in context.md (from @ markers, action item owners, attendees)
@@ -185,8 +201,6 @@ For each processed note, look for:
 - New terminology or acronyms used consistently
 
 Append discoveries to the "<!-- Auto-discovered -->" section of .chronicle/context.md using the established format. Do NOT modify existing content above the auto-discovered line.
-
-Update .chronicle/agent-runs.json with: {"context-updater": "ISO timestamp"}
 "##;
 
 const DIGEST_PROMPT: &str = r##"You are Chronicle's digest generator. Your job is to create periodic summaries across multiple notes.
@@ -194,8 +208,9 @@ const DIGEST_PROMPT: &str = r##"You are Chronicle's digest generator. Your job i
 Read .chronicle/context.md for workspace context.
 Read .chronicle/agent-runs.json to find when the last digest was generated.
 
-Based on the requested range, find all processed notes in .chronicle/processed/ within that timeframe.
+Based on the requested range, find all processed notes in .chronicle/processed/ (including subfolders) within that timeframe.
 Also read .chronicle/actions.json for action item status and .chronicle/tags.json for topic mapping.
+Both refer to notes by ID; .chronicle/note-ids.json maps each note ID to its current path.
 
 Generate a digest covering:
 
@@ -211,7 +226,7 @@ Decisions extracted from processed notes, with source attribution.
 ## Action Items
 - **New**: Created during this period
 - **Completed**: Resolved during this period
-- **Overdue**: Open items older than 7 days
+- **Overdue**: Open items older than {{stale_after_days}} days
 
 ## Themes & Topics
 Recurring topics that appeared across multiple notes, with note counts.
@@ -225,6 +240,15 @@ Unresolved questions aggregated from all notes in the period.
 Write the digest as markdown to the specified output path.
 "##;
 
+/// Default prompt files in .chronicle/prompts/
+const DEFAULT_PROMPTS: &[(&str, &str)] = &[
+    ("process.md", PROCESS_PROMPT),
+    ("tagger.md", TAGGER_PROMPT),
+    ("actions.md", ACTIONS_PROMPT),
+    ("context-updater.md", CONTEXT_UPDATER_PROMPT),
+    ("digest.md", DIGEST_PROMPT),
+];
+
 /// Default template files
 const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
     (
@@ -233,15 +257,15 @@ const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
     ),
     (
         "meeting.md",
-        "# Meeting: [Topic]\n\n**Date:** {{date}}\n**Attendees:** \n\n## Notes\n\n## Action Items\n\n## Decisions\n",
+        "---\ndate: {{date_iso}}\nattendees: []\ntags: []\n---\n# Meeting: [Topic]\n\n## Notes\n\n## Action Items\n\n## Decisions\n",
     ),
     (
         "one-on-one.md",
-        "# 1:1 with [Name]\n\n**Date:** {{date}}\n\n## Updates\n\n## Discussion\n\n## Action Items\n\n## Feedback\n",
+        "---\ndate: {{date_iso}}\nattendees: []\ntags: [meeting:1on1]\n---\n# 1:1 with [Name]\n\n## Updates\n\n## Discussion\n\n## Action Items\n\n## Feedback\n",
     ),
     (
         "standup.md",
-        "# Standup — {{date}}\n\n## Yesterday\n\n## Today\n\n## Blockers\n",
+        "---\ndate: {{date_iso}}\ntags: [meeting:standup]\n---\n# Standup — {{date}}\n\n## Yesterday\n\n## Today\n\n## Blockers\n",
     ),
 ];
 
@@ -307,6 +331,37 @@ pub fn init_chronicle_dir(workspace_path: &Path) -> Result<(), String> {
         }
     }
 
+    // Create default agent pipeline (don't overwrite)
+    let pipeline_path = chronicle_dir.join(crate::pipeline::PIPELINE_FILE);
+    if !pipeline_path.exists() {
+        std::fs::write(&pipeline_path, crate::pipeline::DEFAULT_PIPELINE)
+            .map_err(|e| format!("Failed to write {}: {}", pipeline_path.display(), e))?;
+    }
+
+    // Create default schedule with every job disabled (don't overwrite)
+    let schedule_path = chronicle_dir.join(crate::scheduler::SCHEDULE_FILE);
+    if !schedule_path.exists() {
+        std::fs::write(&schedule_path, crate::scheduler::DEFAULT_SCHEDULE)
+            .map_err(|e| format!("Failed to write {}: {}", schedule_path.display(), e))?;
+    }
+
+    // Create default settings (don't overwrite)
+    let config_path = chronicle_dir.join(crate::storage::CONFIG_FILE);
+    if !config_path.exists() {
+        let content = WorkspaceConfig::default()
+            .to_toml()
+            .map_err(|e| e.to_string())?;
+        std::fs::write(&config_path, content)
+            .map_err(|e| format!("Failed to write {}: {}", config_path.display(), e))?;
+    }
+
+    // Create default naming scheme (don't overwrite)
+    let naming_path = chronicle_dir.join(crate::storage::NAMING_FILE);
+    if !naming_path.exists() {
+        std::fs::write(&naming_path, crate::storage::DEFAULT_NAMING)
+            .map_err(|e| format!("Failed to write {}: {}", naming_path.display(), e))?;
+    }
+
     // Create context.md (don't overwrite)
     let context_path = chronicle_dir.join("context.md");
     if !context_path.exists() {
@@ -315,14 +370,7 @@ pub fn init_chronicle_dir(workspace_path: &Path) -> Result<(), String> {
     }
 
     // Create default prompt files (don't overwrite)
-    let prompts = &[
-        ("process.md", PROCESS_PROMPT),
-        ("tagger.md", TAGGER_PROMPT),
-        ("actions.md", ACTIONS_PROMPT),
-        ("context-updater.md", CONTEXT_UPDATER_PROMPT),
-        ("digest.md", DIGEST_PROMPT),
-    ];
-    for (filename, content) in prompts {
+    for (filename, content) in DEFAULT_PROMPTS {
         let file_path = chronicle_dir.join("prompts").join(filename);
         if !file_path.exists() {
             std::fs::write(&file_path, content)
@@ -348,6 +396,13 @@ pub fn init_chronicle_dir(workspace_path: &Path) -> Result<(), String> {
         }
     }
 
+    // Bring built-in prompts and commands that were never edited up to date
+    match upgrade_seeded_files(&chronicle_dir) {
+        Ok(0) => {}
+        Ok(upgraded) => tracing::info!("Upgraded {} built-in prompts and commands", upgraded),
+        Err(e) => tracing::warn!("Failed to upgrade built-in prompts and commands: {}", e),
+    }
+
     tracing::info!(
         "Initialized .chronicle/ in {}",
         workspace_path.display()
@@ -356,41 +411,171 @@ pub fn init_chronicle_dir(workspace_path: &Path) -> Result<(), String> {
     Ok(())
 }
 
+/// Version of the built-in prompts and commands, recorded in .chronicle/seeds.json
+/// once a workspace's copies are upgraded to it
+const SEEDS_VERSION: u32 = 2;
+
+const SEEDS_FILE: &str = "seeds.json";
+
+/// SHA-256 of earlier versions of the built-in prompts and commands, by path inside
+/// .chronicle/. A file that still matches one was never edited, so it is replaced
+/// by the current version.
+const EARLIER_SEEDS: &[(&str, &str)] = &[
+    // Commands from before permissions were declared in front matter; without it
+    // they are read-only and can't write their digests
+    (
+        "commands/extract-decisions.md",
+        "0ac6c31447a58850749ac62b9aa1663af16c13b6eac4dd49b92a87563b0171c2",
+    ),
+    (
+        "commands/find-stale-actions.md",
+        "4601fb93d87d65465b83478cc8c562a51f29e936f793af66fa04a309fa7787df",
+    ),
+    (
+        "commands/prep-meeting.md",
+        "4b448e9b40f25cb75746d5b21b76c5b415c951824a094670fcdacbf644d4b23a",
+    ),
+    (
+        "commands/topic-summary.md",
+        "536036239265ee6d9335ec81a4f02b80b13ee31416334c9dde2440de38a8a6d8",
+    ),
+    (
+        "commands/weekly-review.md",
+        "2076e81a1d6adb96bce66655e2f6817bff0172a9a3685f7a51500e8eecd37611",
+    ),
+    // Prompts that worked out their own changed files from agent-runs.json and
+    // recorded their runs there, before the app passed them the changed files
+    (
+        "prompts/actions.md",
+        "775b852a18a7ce0b4477a4793584ad97c31de0a109c3df75d1f38caba4d175a7",
+    ),
+    (
+        "prompts/context-updater.md",
+        "d581f09398a148953f16a3d7a58fae62376b62c5201523efac1f58b3c37e6d6c",
+    ),
+    (
+        "prompts/digest.md",
+        "9701ae150f098fbf79f4cf304299b09a52a39d078ce2c95d4cd3488f10bc589d",
+    ),
+    (
+        "prompts/process.md",
+        "dd1d20639cac5f2eaf97dedca5a8cc628364abfd3eb7f090efc1172ee2bddedc",
+    ),
+    (
+        "prompts/tagger.md",
+        "44eac28e7f39ff528ca40c8e08558e232e544055a2000ba5132ce536074e5fbc",
+    ),
+];
+
+/// Current content of a built-in prompt or command, by path inside .chronicle/
+fn seeded_content(path: &str) -> Option<&'static str> {
+    let (dir, filename) = path.split_once('/')?;
+    let seeds = match dir {
+        "prompts" => DEFAULT_PROMPTS,
+        "commands" => SEED_COMMANDS,
+        _ => return None,
+    };
+    seeds
+        .iter()
+        .find(|(name, _)| *name == filename)
+        .map(|(_, content)| *content)
+}
+
+/// Replace built-in prompts and commands the user never edited with their current
+/// version. Runs once per `SEEDS_VERSION`; edited files are left alone.
+/// Returns the number of files replaced.
+pub fn upgrade_seeded_files(chronicle_dir: &Path) -> Result<usize, String> {
+    let seeds_path = chronicle_dir.join(SEEDS_FILE);
+    let recorded = std::fs::read_to_string(&seeds_path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<Value>(&c).ok())
+        .and_then(|v| v.get("version")?.as_u64())
+        .unwrap_or(0);
+    if recorded >= u64::from(SEEDS_VERSION) {
+        return Ok(0);
+    }
+
+    let mut upgraded = 0;
+    for (path, hash) in EARLIER_SEEDS {
+        let file_path = chronicle_dir.join(path);
+        let Ok(content) = std::fs::read_to_string(&file_path) else {
+            continue;
+        };
+        if crate::pipeline::content_hash(&content) != *hash {
+            continue;
+        }
+        let Some(current) = seeded_content(path) else {
+            continue;
+        };
+        crate::storage::write_file_atomic(&file_path, current).map_err(|e| e.to_string())?;
+        tracing::info!("Upgraded built-in {}", path);
+        upgraded += 1;
+    }
+
+    let seeds = serde_json::json!({ "version": SEEDS_VERSION });
+    std::fs::write(&seeds_path, format!("{:#}\n", seeds))
+        .map_err(|e| format!("Failed to write {}: {}", seeds_path.display(), e))?;
+    Ok(upgraded)
+}
+
 /// Start the filesystem watcher for .chronicle/ in the given workspace
 #[tauri::command]
 pub async fn start_chronicle_watcher(
     app_handle: tauri::AppHandle,
     watcher_state: State<'_, ChronicleWatcher>,
     workspace_path: String,
+    state: State<'_, SharedAppState>,
 ) -> Result<(), String> {
-    watcher_state.start(&workspace_path, app_handle)
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    watcher_state.start(
+        workspace.id(),
+        &workspace.opened_root().display().to_string(),
+        app_handle,
+    )
 }
 
 /// Read .chronicle/tags.json
 #[tauri::command]
-pub async fn read_tags(workspace_path: String) -> Result<Value, String> {
-    read_chronicle_file(&workspace_path, "tags.json")
+pub async fn read_tags(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Value, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    read_index_file(workspace.opened_root(), "tags.json")
 }
 
 /// Read .chronicle/actions.json
 #[tauri::command]
-pub async fn read_actions(workspace_path: String) -> Result<Value, String> {
-    read_chronicle_file(&workspace_path, "actions.json")
+pub async fn read_actions(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Value, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    read_index_file(workspace.opened_root(), "actions.json")
 }
 
 /// Read .chronicle/links.json
 #[tauri::command]
-pub async fn read_links(workspace_path: String) -> Result<Value, String> {
-    read_chronicle_file(&workspace_path, "links.json")
+pub async fn read_links(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Value, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    read_index_file(workspace.opened_root(), "links.json")
 }
 
-/// Read .chronicle/processed/{note_name}.json
+/// Read a note's processed JSON, with `stale` and `linesChanged` added.
+/// `note_path` may be absolute or relative to the workspace.
 #[tauri::command]
-pub async fn read_processed(workspace_path: String, note_name: String) -> Result<Value, String> {
-    let path = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("processed")
-        .join(format!("{}.json", note_name));
+pub async fn read_processed(
+    workspace_path: String,
+    note_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Value, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let note = workspace.join(&note_path)?;
+    let workspace = workspace.root();
+    let path = crate::pipeline::processed_json_path(workspace, &note).map_err(|e| e.to_string())?;
 
     if !path.exists() {
         return Ok(Value::Null);
@@ -399,20 +584,42 @@ pub async fn read_processed(workspace_path: String, note_name: String) -> Result
     let content = std::fs::read_to_string(&path)
         .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
 
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    let mut json: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    // Tell the UI whether the note changed since it was processed
+    let key = crate::pipeline::processed_key(workspace, &note).map_err(|e| e.to_string())?;
+    let manifest = crate::pipeline::ProcessedManifest::load(workspace);
+    let (_, freshness) = crate::pipeline::processed_freshness(workspace, &key, &json, &manifest);
+    if let Some(object) = json.as_object_mut() {
+        object.insert("stale".to_string(), Value::Bool(freshness.stale));
+        object.insert(
+            "linesChanged".to_string(),
+            freshness.lines_changed.map(Value::from).unwrap_or(Value::Null),
+        );
+    }
+
+    Ok(json)
 }
 
 /// Read .chronicle/agent-runs.json
 #[tauri::command]
-pub async fn get_agent_status(workspace_path: String) -> Result<Value, String> {
-    read_chronicle_file(&workspace_path, "agent-runs.json")
+pub async fn get_agent_status(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Value, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    read_chronicle_file(workspace.opened_root(), "agent-runs.json")
 }
 
 /// Read .chronicle/context.md
 #[tauri::command]
-pub async fn read_context(workspace_path: String) -> Result<String, String> {
-    let path = Path::new(&workspace_path).join(".chronicle").join("context.md");
+pub async fn read_context(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<String, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let path = workspace.root().join(".chronicle").join("context.md");
     if !path.exists() {
         return Ok(String::new());
     }
@@ -422,11 +629,15 @@ pub async fn read_context(workspace_path: String) -> Result<String, String> {
 
 /// Read entities from a processed note's JSON
 #[tauri::command]
-pub async fn read_entities(workspace_path: String, note_name: String) -> Result<Value, String> {
-    let path = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("processed")
-        .join(format!("{}.json", note_name));
+pub async fn read_entities(
+    workspace_path: String,
+    note_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Value, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let note = workspace.join(&note_path)?;
+    let path =
+        crate::pipeline::processed_json_path(workspace.root(), &note).map_err(|e| e.to_string())?;
 
     if !path.exists() {
         return Ok(Value::Null);
@@ -444,8 +655,12 @@ pub async fn read_entities(workspace_path: String, note_name: String) -> Result<
 
 /// List all entities across all processed notes (aggregated)
 #[tauri::command]
-pub async fn list_all_entities(workspace_path: String) -> Result<Value, String> {
-    let processed_dir = Path::new(&workspace_path).join(".chronicle").join("processed");
+pub async fn list_all_entities(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Value, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let processed_dir = workspace.root().join(".chronicle").join("processed");
     if !processed_dir.exists() {
         return Ok(serde_json::json!({ "people": [], "decisions": [], "topics": [], "references": [] }));
     }
@@ -455,16 +670,8 @@ pub async fn list_all_entities(workspace_path: String) -> Result<Value, String>
     let mut all_topics: std::collections::HashSet<String> = std::collections::HashSet::new();
     let mut all_references: Vec<Value> = Vec::new();
 
-    let entries = std::fs::read_dir(&processed_dir)
-        .map_err(|e| format!("Failed to read processed dir: {}", e))?;
-
-    for entry in entries {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("json") {
-            continue;
-        }
-
+    // Entities are attributed to their output key, e.g. projects/standup
+    for (source, path) in crate::pipeline::list_processed_outputs(workspace.root()) {
         let content = match std::fs::read_to_string(&path) {
             Ok(c) => c,
             Err(_) => continue,
@@ -475,11 +682,6 @@ pub async fn list_all_entities(workspace_path: String) -> Result<Value, String>
             Err(_) => continue,
         };
 
-        let source = path.file_stem()
-            .and_then(|n| n.to_str())
-            .unwrap_or("")
-            .to_string();
-
         if let Some(entities) = json.get("entities") {
             // People
             if let Some(people) = entities.get("people").and_then(|v| v.as_array()) {
@@ -533,11 +735,17 @@ pub async fn list_all_entities(workspace_path: String) -> Result<Value, String>
     }))
 }
 
+/// Read a note index from .chronicle/, with note IDs replaced by current paths
+fn read_index_file(workspace_path: &Path, filename: &str) -> Result<Value, String> {
+    let mut value = read_chronicle_file(workspace_path, filename)?;
+    let registry = crate::notes::NoteRegistry::load(workspace_path);
+    crate::notes::resolve_note_ids(&mut value, &registry);
+    Ok(value)
+}
+
 /// Helper to read a JSON file from .chronicle/
-fn read_chronicle_file(workspace_path: &str, filename: &str) -> Result<Value, String> {
-    let path = Path::new(workspace_path)
-        .join(".chronicle")
-        .join(filename);
+fn read_chronicle_file(workspace_path: &Path, filename: &str) -> Result<Value, String> {
+    let path = workspace_path.join(".chronicle").join(filename);
 
     if !path.exists() {
         // Return the appropriate empty default
@@ -559,15 +767,8 @@ fn read_chronicle_file(workspace_path: &str, filename: &str) -> Result<Value, St
 pub async fn list_templates(
     state: State<'_, SharedAppState>,
 ) -> Result<Vec<TemplateInfo>, String> {
-    let app_state = state.read().await;
-    let workspace_path = app_state
-        .workspace_path
-        .as_ref()
-        .ok_or_else(|| "No workspace open".to_string())?;
-
-    let templates_dir = Path::new(workspace_path)
-        .join(".chronicle")
-        .join("templates");
+    let workspace = WorkspacePath::workspace(&state).await?;
+    let templates_dir = workspace.root().join(".chronicle").join("templates");
 
     if !templates_dir.exists() {
         return Ok(vec![]);
@@ -622,9 +823,22 @@ fn replace_placeholders(content: &str) -> String {
     let date_str = now.format("%B %d, %Y").to_string();
     content
         .replace("{{date}}", &date_str)
+        .replace("{{date_iso}}", &now.format("%Y-%m-%d").to_string())
         .replace("{{title}}", "New Note")
 }
 
+/// Give a note created from a template its own ID when the template has front matter
+fn stamp_note_id(content: String) -> String {
+    let Ok((mut front_matter, _)) = crate::storage::parse_front_matter(&content) else {
+        return content;
+    };
+    if front_matter.is_empty() {
+        return content;
+    }
+    front_matter.id = Some(crate::storage::new_note_id());
+    crate::storage::set_front_matter(&content, &front_matter).unwrap_or(content)
+}
+
 /// Create a new note from a template
 #[tauri::command]
 pub async fn create_from_template(
@@ -632,40 +846,54 @@ pub async fn create_from_template(
     folder_path: Option<String>,
     state: State<'_, SharedAppState>,
 ) -> Result<(String, String), String> {
-    let app_state = state.read().await;
-    let workspace_path = app_state
-        .workspace_path
-        .as_ref()
-        .ok_or_else(|| "No workspace open".to_string())?
-        .clone();
-    drop(app_state);
-
-    let template_path = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("templates")
-        .join(&template_filename);
+    let workspace_root = WorkspacePath::workspace(&state).await?;
+    let template_path = workspace_root.join_file_name(".chronicle/templates", &template_filename)?;
+    let folder = folder_path.map(|f| workspace_root.join(f)).transpose()?;
 
     let raw_content = std::fs::read_to_string(&template_path)
         .map_err(|e| format!("Failed to read template: {}", e))?;
 
-    let content = replace_placeholders(&raw_content);
+    let content = stamp_note_id(replace_placeholders(&raw_content));
 
-    // Generate a unique path in the target folder (or workspace root)
-    let target_dir = folder_path.unwrap_or_else(|| workspace_path.clone());
-    let note_path =
-        crate::storage::generate_unique_path(Path::new(&target_dir), &content);
+    // Name the note by the workspace scheme, in the target folder or filed by it
+    let workspace = workspace_root.root();
+    let note_path = crate::storage::new_note_path(
+        workspace,
+        folder.as_deref(),
+        &content,
+        Some(&template_filename),
+    );
 
     // Write the new note
     crate::storage::write_file_atomic(&note_path, &content)
         .map_err(|e| format!("Failed to write note: {}", e))?;
 
-    let path_str = note_path.display().to_string();
+    // Remember the template for naming patterns that use it
+    let mut meta = crate::storage::NoteMeta::new(&note_path);
+    meta.file.template = Some(template_filename.clone());
+    if let Err(e) = crate::storage::save_metadata(workspace, &note_path, &meta) {
+        tracing::warn!("Failed to save metadata for {}: {}", note_path.display(), e);
+    }
+
+    if let Some(relative) = crate::storage::relative_note_path(workspace, &note_path) {
+        if let Err(e) = crate::notes::ensure_note_id(workspace, &relative) {
+            tracing::warn!("Failed to register note ID for {}: {}", relative, e);
+        }
+    }
+
+    state.write().await.record_user_write(note_path.clone());
+
+    let path_str = workspace_root.display_path_of(&note_path).display().to_string();
     Ok((path_str, content))
 }
 
 // ── Seed command files for .chronicle/commands/ ──
 
-const PREP_MEETING_CMD: &str = r#"# Prep for Meeting
+const PREP_MEETING_CMD: &str = r#"---
+tools: [Read, Glob, Grep, Write]
+writes: [.chronicle/digests/]
+---
+# Prep for Meeting
 
 Prepare a brief for my upcoming meeting with {{person}}.
 
@@ -683,32 +911,40 @@ Output as a concise prep brief I can review in 2 minutes before the meeting.
 Write the brief to .chronicle/digests/prep-{{person}}.md
 "#;
 
-const WEEKLY_REVIEW_CMD: &str = r#"# Weekly Review
+const WEEKLY_REVIEW_CMD: &str = r#"---
+tools: [Read, Glob, Grep, Write]
+writes: [.chronicle/digests/]
+---
+# Weekly Review
 
 Generate a summary of this week's notes.
 
 Read .chronicle/context.md for workspace context.
 Find all .md notes in the workspace modified in the last 7 days.
-Read their processed versions from .chronicle/processed/ if available.
+Read their processed versions from .chronicle/processed/ if available (same relative path, .json instead of .md).
 
 Produce a weekly review covering:
 1. **Meetings held** — count and list with one-line summaries
 2. **Key decisions made** — extracted from processed notes
 3. **Action items created** — new this week, grouped by owner
 4. **Action items completed** — resolved this week
-5. **Overdue items** — open actions older than 7 days
+5. **Overdue items** — open actions older than {{stale_after_days}} days
 6. **Recurring themes** — topics that appeared in 3+ meetings
 7. **Notable quotes or insights** — marked with > or ! in notes
 
 Write the review to .chronicle/digests/weekly-{{date}}.md
 "#;
 
-const EXTRACT_DECISIONS_CMD: &str = r#"# Extract Decisions
+const EXTRACT_DECISIONS_CMD: &str = r#"---
+tools: [Read, Glob, Grep, Write]
+writes: [.chronicle/digests/]
+---
+# Extract Decisions
 
 Find all decisions made across recent notes.
 
 Read .chronicle/context.md for context.
-Read all .chronicle/processed/*.json files from the last {{days}} days (default: 14).
+Read all .chronicle/processed/**/*.json files from the last {{days}} days (default: 14).
 
 For each processed note, look for:
 - Explicit decisions (key points phrased as decisions)
@@ -725,15 +961,19 @@ Output a decision log:
 Write to .chronicle/digests/decisions-{{date}}.md
 "#;
 
-const FIND_STALE_ACTIONS_CMD: &str = r#"# Find Stale Actions
+const FIND_STALE_ACTIONS_CMD: &str = r#"---
+tools: [Read, Glob, Grep, Write]
+writes: [.chronicle/digests/]
+---
+# Find Stale Actions
 
 Identify overdue and stale action items.
 
-Read .chronicle/actions.json for all tracked actions.
+Read .chronicle/actions.json for all tracked actions. Each action's source is a note ID; look up its path in .chronicle/note-ids.json.
 Read .chronicle/context.md for people context.
 
 Find all actions that are:
-1. **Overdue** — open for more than 7 days
+1. **Overdue** — open for more than {{stale_after_days}} days
 2. **Unassigned** — no clear owner
 3. **Blocked** — mentioned as blocked or waiting in subsequent notes
 
@@ -745,13 +985,17 @@ For each stale action:
 Write the report to .chronicle/digests/stale-actions-{{date}}.md
 "#;
 
-const TOPIC_SUMMARY_CMD: &str = r#"# Topic Summary
+const TOPIC_SUMMARY_CMD: &str = r#"---
+tools: [Read, Glob, Grep, Write]
+writes: [.chronicle/digests/]
+---
+# Topic Summary
 
 Summarize everything related to a specific topic.
 
 Read .chronicle/context.md for context.
-Read .chronicle/tags.json to find notes tagged with {{topic}}.
-Read the processed versions of those notes from .chronicle/processed/.
+Read .chronicle/tags.json to find notes tagged with {{topic}}. Notes are listed by ID; look up their paths in .chronicle/note-ids.json.
+Read the processed versions of those notes from .chronicle/processed/ (same relative path, .json instead of .md).
 
 Produce a topic summary:
 1. **Timeline** — when this topic was discussed, in chronological order
@@ -782,14 +1026,20 @@ pub struct CommandInfo {
     pub description: String,
     pub params: Vec<String>,
     pub content: String,
+    /// Tools the command runs with, from its front matter (read-only by default)
+    pub tools: Vec<String>,
+    /// Paths it may write
+    pub writes: Vec<String>,
 }
 
 /// List available commands from .chronicle/commands/
 #[tauri::command]
-pub async fn list_commands(workspace_path: String) -> Result<Vec<CommandInfo>, String> {
-    let commands_dir = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("commands");
+pub async fn list_commands(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<CommandInfo>, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let commands_dir = workspace.root().join(".chronicle").join("commands");
     if !commands_dir.exists() {
         return Ok(vec![]);
     }
@@ -810,8 +1060,15 @@ pub async fn list_commands(workspace_path: String) -> Result<Vec<CommandInfo>, S
             .and_then(|n| n.to_str())
             .unwrap_or("")
             .to_string();
-        let content = std::fs::read_to_string(&path)
+        let raw = std::fs::read_to_string(&path)
             .map_err(|e| format!("Failed to read command {}: {}", filename, e))?;
+        let (permissions, content) = match crate::pipeline::command_permissions(&raw) {
+            Ok((permissions, body)) => (permissions, body.to_string()),
+            Err(e) => {
+                tracing::warn!("Skipping command {}: {}", filename, e);
+                continue;
+            }
+        };
 
         let name = content
             .lines()
@@ -833,7 +1090,8 @@ pub async fn list_commands(workspace_path: String) -> Result<Vec<CommandInfo>, S
             while let Some(start) = rest.find("{{") {
                 if let Some(end) = rest[start..].find("}}") {
                     let param = rest[start + 2..start + end].trim().to_string();
-                    if !found.contains(&param) {
+                    // Settings are filled in from config.toml, not asked for
+                    if !found.contains(&param) && !SETTING_PLACEHOLDERS.contains(&param.as_str()) {
                         found.push(param);
                     }
                     rest = &rest[start + end + 2..];
@@ -850,6 +1108,8 @@ pub async fn list_commands(workspace_path: String) -> Result<Vec<CommandInfo>, S
             description,
             params,
             content,
+            tools: permissions.tools,
+            writes: permissions.writes,
         });
     }
 
@@ -861,17 +1121,27 @@ pub async fn list_commands(workspace_path: String) -> Result<Vec<CommandInfo>, S
 
 /// Read the actions.json file from .chronicle directory
 #[tauri::command]
-pub async fn read_actions_file(workspace_path: String) -> Result<String, String> {
-    let path = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("actions.json");
+pub async fn read_actions_file(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<String, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let path = workspace.root().join(".chronicle").join("actions.json");
 
     if !path.exists() {
         return Ok("[]".to_string());
     }
 
-    std::fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read actions: {}", e))
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read actions: {}", e))?;
+
+    // Sources are stored as note IDs; hand the UI their current paths
+    let Ok(mut actions) = serde_json::from_str::<Value>(&content) else {
+        return Ok(content);
+    };
+    let registry = crate::notes::NoteRegistry::load(workspace.opened_root());
+    crate::notes::resolve_note_ids(&mut actions, &registry);
+    serde_json::to_string_pretty(&actions).map_err(|e| format!("Failed to serialize: {}", e))
 }
 
 /// Update a specific action item's status in actions.json
@@ -880,10 +1150,10 @@ pub async fn update_action_status(
     workspace_path: String,
     action_index: usize,
     new_status: String,
+    state: State<'_, SharedAppState>,
 ) -> Result<(), String> {
-    let path = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("actions.json");
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let path = workspace.root().join(".chronicle").join("actions.json");
 
     let content = std::fs::read_to_string(&path)
         .map_err(|e| format!("Failed to read actions: {}", e))?;
@@ -904,8 +1174,98 @@ pub async fn update_action_status(
     let updated = serde_json::to_string_pretty(&actions)
         .map_err(|e| format!("Failed to serialize: {}", e))?;
 
-    std::fs::write(&path, updated)
+    crate::storage::write_file_atomic_with_backup(&path, &updated)
         .map_err(|e| format!("Failed to write actions: {}", e))?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::command_permissions;
+    use tempfile::tempdir;
+
+    /// prep-meeting.md as seeded before commands declared their permissions
+    const OLD_PREP_MEETING: &str = r#"# Prep for Meeting
+
+Prepare a brief for my upcoming meeting with {{person}}.
+
+Read .chronicle/context.md for background on this person.
+Search all notes for mentions of {{person}} (check both @ markers and name references).
+Read the last 3-5 notes that mention them.
+
+Summarize:
+1. **Open action items** between us (from .chronicle/actions.json where owner involves either of us)
+2. **Recent discussion topics** — what we talked about in the last few meetings
+3. **Pending decisions** — anything unresolved that involves them
+4. **Key context** — their current projects, recent concerns
+
+Output as a concise prep brief I can review in 2 minutes before the meeting.
+Write the brief to .chronicle/digests/prep-{{person}}.md
+"#;
+
+    /// context-updater.md as seeded when agents recorded their own runs
+    const OLD_CONTEXT_UPDATER: &str = r##"You are Chronicle's context updater. Your job is to discover new people, projects, and terminology from recently processed notes and append them to .chronicle/context.md.
+
+Read .chronicle/context.md for existing context.
+Read .chronicle/agent-runs.json to find when the context updater last ran.
+Read all .chronicle/processed/*.json files modified after the last run.
+
+For each processed note, look for:
+- New people not already in context.md (from @ markers, action item owners, attendees)
+- New projects not already listed
+- New terminology or acronyms used consistently
+
+Append discoveries to the "<!-- Auto-discovered -->" section of .chronicle/context.md using the established format. Do NOT modify existing content above the auto-discovered line.
+
+Update .chronicle/agent-runs.json with: {"context-updater": "ISO timestamp"}
+"##;
+
+    #[test]
+    fn test_init_upgrades_unedited_old_commands() {
+        let dir = tempdir().unwrap();
+        let commands = dir.path().join(".chronicle").join("commands");
+        std::fs::create_dir_all(&commands).unwrap();
+        std::fs::write(commands.join("prep-meeting.md"), OLD_PREP_MEETING).unwrap();
+        let edited = format!("{}\nAlso list their birthday.\n", OLD_PREP_MEETING);
+        std::fs::write(commands.join("weekly-review.md"), &edited).unwrap();
+
+        init_chronicle_dir(dir.path()).unwrap();
+
+        let upgraded = std::fs::read_to_string(commands.join("prep-meeting.md")).unwrap();
+        assert_eq!(
+            upgraded,
+            seeded_content("commands/prep-meeting.md").unwrap()
+        );
+        let (permissions, _) = command_permissions(&upgraded).unwrap();
+        assert!(permissions.allows_write(Path::new(".chronicle/digests/prep-alice.md")));
+
+        // Edited commands are left as the user wrote them
+        let kept = std::fs::read_to_string(commands.join("weekly-review.md")).unwrap();
+        assert_eq!(kept, edited);
+
+        // The upgrade runs once; a later reset to the old text is the user's choice
+        std::fs::write(commands.join("prep-meeting.md"), OLD_PREP_MEETING).unwrap();
+        init_chronicle_dir(dir.path()).unwrap();
+        let kept = std::fs::read_to_string(commands.join("prep-meeting.md")).unwrap();
+        assert_eq!(kept, OLD_PREP_MEETING);
+    }
+
+    #[test]
+    fn test_init_upgrades_prompts_that_track_their_own_runs() {
+        let dir = tempdir().unwrap();
+        let chronicle_dir = dir.path().join(".chronicle");
+        let prompts = chronicle_dir.join("prompts");
+        std::fs::create_dir_all(&prompts).unwrap();
+        std::fs::write(prompts.join("context-updater.md"), OLD_CONTEXT_UPDATER).unwrap();
+        // Upgraded once already, before the prompts were part of the upgrade
+        std::fs::write(chronicle_dir.join(SEEDS_FILE), r#"{"version": 1}"#).unwrap();
+
+        init_chronicle_dir(dir.path()).unwrap();
+
+        let upgraded = std::fs::read_to_string(prompts.join("context-updater.md")).unwrap();
+        assert_eq!(upgraded, CONTEXT_UPDATER_PROMPT);
+        assert!(!upgraded.contains("agent-runs.json"));
+    }
+}
@@ -1,10 +1,23 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
+use crate::pipeline::{
+    command_permissions, processed_json_path, processed_markdown_path, read_agent_files,
+    read_agent_runs, record_agent_files, record_agent_run, record_processed_source, scope_changes,
+    scoped_prompt, AgentPermissions, AgentSandbox, AgentScope, AgentSpec, Pipeline, ScopeFiles,
+    WorkspaceSnapshot, WriteViolation,
+};
+use crate::storage::{self, WorkspaceConfig, WorkspacePath};
+use crate::SharedAppState;
+
+/// Returned for agent runs in a workspace the user hasn't trusted
+const UNTRUSTED_WORKSPACE: &str =
+    "This workspace isn't trusted. Trust it to run agents and commands in it.";
+
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
@@ -14,6 +27,9 @@ pub struct ClaudeResult {
     pub output: String,
     pub error: Option<String>,
     pub duration_ms: u64,
+    /// Writes outside the run's allowlist, which were undone
+    #[serde(default)]
+    pub rolled_back_writes: Vec<WriteViolation>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -52,11 +68,77 @@ pub struct DigestInfo {
 }
 
 /// Core function to run `claude -p` with streaming output via Tauri events.
+///
+/// Runs only in trusted workspaces, one run at a time. Claude gets exactly the tools
+/// in `permissions`; afterwards, any file it changed outside `permissions.writes` is
+/// restored and reported in `rolled_back_writes`.
 async fn run_claude_streaming(
     app_handle: &AppHandle,
-    workspace_path: &str,
+    workspace: &WorkspacePath,
     prompt: &str,
     max_turns: Option<u32>,
+    permissions: &AgentPermissions,
+) -> Result<ClaudeResult, String> {
+    if !storage::is_workspace_trusted(workspace.root()) {
+        return Err(UNTRUSTED_WORKSPACE.to_string());
+    }
+    let sandbox = app_handle.state::<AgentSandbox>();
+    let _running = sandbox.exclusive(workspace.id()).await;
+
+    let snapshot = WorkspaceSnapshot::capture(workspace.root(), permissions);
+    let started_at = chrono::Utc::now();
+    let result = run_claude(
+        app_handle,
+        workspace.opened_root(),
+        prompt,
+        max_turns,
+        permissions,
+    )
+    .await;
+
+    let excused = user_writes_since(app_handle, workspace, started_at).await;
+    let mut violations = snapshot.violations(permissions, &excused);
+    if !violations.is_empty() {
+        snapshot.roll_back(&mut violations);
+        tracing::warn!(
+            "Rolled back {} write(s) outside the agent's allowlist",
+            violations.len()
+        );
+        app_handle.emit("claude:writes-rolled-back", &violations).ok();
+    }
+
+    result.map(|r| ClaudeResult {
+        rolled_back_writes: violations,
+        ..r
+    })
+}
+
+/// Workspace-relative paths the user changed through the app since `since`
+async fn user_writes_since(
+    app_handle: &AppHandle,
+    workspace: &WorkspacePath,
+    since: chrono::DateTime<chrono::Utc>,
+) -> HashSet<std::path::PathBuf> {
+    let Some(state) = app_handle.try_state::<SharedAppState>() else {
+        return HashSet::new();
+    };
+    let app_state = state.read().await;
+    app_state
+        .user_writes
+        .iter()
+        .filter(|(_, at)| **at >= since)
+        .filter_map(|(path, _)| path.strip_prefix(workspace.root()).ok())
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// Run `claude -p` in `workspace_path`, streaming its output
+async fn run_claude(
+    app_handle: &AppHandle,
+    workspace_path: &Path,
+    prompt: &str,
+    max_turns: Option<u32>,
+    permissions: &AgentPermissions,
 ) -> Result<ClaudeResult, String> {
     let start = std::time::Instant::now();
 
@@ -66,7 +148,10 @@ async fn run_claude_streaming(
         "--output-format".to_string(),
         "text".to_string(),
         "--allowedTools".to_string(),
-        "Read,Write,Edit,Glob,Grep".to_string(),
+        permissions.tools_arg(),
+        // Also overrides anything the workspace's .claude/settings.json allows
+        "--disallowedTools".to_string(),
+        permissions.denied_tools_arg(),
     ];
 
     if let Some(turns) = max_turns {
@@ -165,18 +250,23 @@ async fn run_claude_streaming(
             Some(stderr_output)
         },
         duration_ms: start.elapsed().as_millis() as u64,
+        rolled_back_writes: Vec::new(),
     })
 }
 
-/// Tauri command: run an arbitrary prompt via `claude -p`.
+/// Tauri command: run an arbitrary prompt via `claude -p`. It may read the
+/// workspace but not change it.
 #[tauri::command]
 pub async fn run_claude_task(
     app_handle: AppHandle,
     workspace_path: String,
     prompt: String,
     max_turns: Option<u32>,
+    state: State<'_, SharedAppState>,
 ) -> Result<ClaudeResult, String> {
-    run_claude_streaming(&app_handle, &workspace_path, &prompt, max_turns).await
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let permissions = AgentPermissions::read_only();
+    run_claude_streaming(&app_handle, &workspace, &prompt, max_turns, &permissions).await
 }
 
 /// Tauri command: process a note using the workspace's process.md prompt template.
@@ -185,7 +275,11 @@ pub async fn process_note(
     app_handle: AppHandle,
     workspace_path: String,
     note_path: String,
+    state: State<'_, SharedAppState>,
 ) -> Result<ClaudeResult, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let note = workspace.join(&note_path)?;
+
     app_handle
         .emit(
             "claude:task-started",
@@ -196,16 +290,52 @@ pub async fn process_note(
         )
         .ok();
 
-    let prompt_path = Path::new(&workspace_path).join(".chronicle/prompts/process.md");
+    let prompt_path = workspace.join(".chronicle/prompts/process.md")?;
     let prompt_template = tokio::fs::read_to_string(&prompt_path)
         .await
         .map_err(|e| format!("Failed to read process prompt: {}", e))?;
 
-    let full_prompt = format!("{}\n\nProcess this note: {}", prompt_template, note_path);
+    // Outputs mirror the note's workspace-relative path so same-named notes don't collide
+    let root = workspace.root();
+    let config = WorkspaceConfig::load(root);
+    let json_output = processed_json_path(root, &note).map_err(|e| e.to_string())?;
+    let markdown_output = processed_markdown_path(root, &note).map_err(|e| e.to_string())?;
+    // The outputs are all it may write
+    let permissions = config.agents.permissions(
+        [&json_output, &markdown_output]
+            .iter()
+            .filter_map(|p| p.strip_prefix(root).ok())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .collect(),
+    );
+    let full_prompt = format!(
+        "{}\n\nProcess this note: {}\n\nWrite the JSON output to {} and the markdown output to {}, \
+         creating folders as needed. These paths take precedence over any other output location above.",
+        config.expand_prompt(&prompt_template),
+        note_path,
+        json_output.display(),
+        markdown_output.display()
+    );
+
+    // Hash the content being processed, not whatever is on disk when Claude finishes
+    let note_content = tokio::fs::read_to_string(&note).await.ok();
 
-    match run_claude_streaming(&app_handle, &workspace_path, &full_prompt, Some(10)).await {
+    match run_claude_streaming(
+        &app_handle,
+        &workspace,
+        &full_prompt,
+        Some(config.agents.process_max_turns),
+        &permissions,
+    )
+    .await
+    {
         Ok(result) => {
             if result.success {
+                if let Some(content) = &note_content {
+                    if let Err(e) = record_processed_source(root, &note, content) {
+                        tracing::warn!("Failed to record source of {}: {}", note_path, e);
+                    }
+                }
                 app_handle
                     .emit(
                         "claude:task-completed",
@@ -249,87 +379,305 @@ pub async fn process_note(
     }
 }
 
+/// Outcome of a single agent in a background pipeline run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentRunStatus {
+    Succeeded,
+    Failed,
+    Skipped,
+    /// Nothing in the agent's scope changed since its last run
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentOutcome {
+    pub agent: String,
+    pub status: AgentRunStatus,
+    pub error: Option<String>,
+}
+
+/// Why a pipeline run didn't fully succeed: the agents that failed, and those
+/// skipped because of them. None when no agent failed.
+pub fn pipeline_failure(outcomes: &[AgentOutcome]) -> Option<String> {
+    let failed: Vec<String> = outcomes
+        .iter()
+        .filter(|o| o.status == AgentRunStatus::Failed)
+        .map(|o| match &o.error {
+            Some(e) => format!("{} failed: {}", o.agent, e),
+            None => format!("{} failed", o.agent),
+        })
+        .collect();
+    if failed.is_empty() {
+        return None;
+    }
+    let skipped: Vec<&str> = outcomes
+        .iter()
+        .filter(|o| o.status == AgentRunStatus::Skipped)
+        .map(|o| o.agent.as_str())
+        .collect();
+    let mut message = failed.join("; ");
+    if !skipped.is_empty() {
+        message.push_str(&format!(" (skipped: {})", skipped.join(", ")));
+    }
+    Some(message)
+}
+
+/// Label each note with its stable ID so agents can reference notes by ID
+fn with_note_ids(workspace: &Path, files: Vec<String>) -> Vec<String> {
+    files
+        .into_iter()
+        .map(|file| match crate::notes::ensure_note_id(workspace, &file) {
+            Ok(id) => format!("{} (note ID: {})", file, id),
+            Err(e) => {
+                tracing::warn!("Failed to assign note ID to {}: {}", file, e);
+                file
+            }
+        })
+        .collect()
+}
+
+/// Label deleted files with the note IDs they had, so agents can drop their entries
+fn deleted_with_note_ids(deleted: Vec<(String, Option<String>)>) -> Vec<String> {
+    deleted
+        .into_iter()
+        .map(|(file, id)| match id {
+            Some(id) => format!("{} (note ID: {})", file, id),
+            None => file,
+        })
+        .collect()
+}
+
+/// The files an agent saw, with the note IDs of notes, to find deletions next run
+fn seen_files(workspace: &Path, scope: AgentScope, current: Vec<String>) -> ScopeFiles {
+    let ids: HashMap<String, String> = if scope == AgentScope::Notes {
+        crate::notes::NoteRegistry::load(workspace)
+            .notes
+            .into_iter()
+            .map(|(id, path)| (path, id))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    current
+        .into_iter()
+        .map(|file| {
+            let id = ids.get(&file).cloned();
+            (file, id)
+        })
+        .collect()
+}
+
+/// Run one agent spec: read its prompt from .chronicle/prompts/ and invoke Claude
+/// with the spec's turn limit and tool allowlist.
+///
+/// Scoped agents get the explicit list of files changed and deleted since their last
+/// run appended to the prompt, and are not run at all (`Ok(None)`) when both are empty.
+/// Successful runs are recorded in agent-runs.json, and the files they saw in
+/// agent-files.json.
+async fn run_agent_spec(
+    app_handle: &AppHandle,
+    workspace: &WorkspacePath,
+    spec: &AgentSpec,
+) -> Result<Option<ClaudeResult>, String> {
+    let prompt_path = workspace.join_file_name(".chronicle/prompts", &spec.prompt_file())?;
+    let root = workspace.opened_root();
+    let prompt = tokio::fs::read_to_string(&prompt_path)
+        .await
+        .map_err(|e| format!("Failed to read {} prompt: {}", spec.name, e))?;
+    let prompt = WorkspaceConfig::load(root).expand_prompt(&prompt);
+
+    let started_at = chrono::Utc::now();
+    let mut seen = None;
+    let prompt = if spec.scope == AgentScope::All {
+        prompt
+    } else {
+        let since = read_agent_runs(root).get(&spec.name).copied();
+        let previous = read_agent_files(root, &spec.name);
+        let changes = scope_changes(root, spec.scope, since, &previous);
+        if changes.is_empty() {
+            tracing::info!("Skipping agent {}: no changes since last run", spec.name);
+            return Ok(None);
+        }
+        let files = if spec.scope == AgentScope::Notes {
+            with_note_ids(root, changes.changed)
+        } else {
+            changes.changed
+        };
+        seen = Some(changes.current);
+        scoped_prompt(
+            &prompt,
+            since,
+            &files,
+            &deleted_with_note_ids(changes.deleted),
+        )
+    };
+
+    let result = run_claude_streaming(
+        app_handle,
+        workspace,
+        &prompt,
+        Some(spec.max_turns),
+        &spec.permissions(),
+    )
+    .await?;
+
+    if result.success {
+        if let Err(e) = record_agent_run(root, &spec.name, started_at) {
+            tracing::warn!("Failed to record {} run: {}", spec.name, e);
+        }
+        if let Some(current) = seen {
+            let seen = seen_files(root, spec.scope, current);
+            if let Err(e) = record_agent_files(root, &spec.name, seen) {
+                tracing::warn!("Failed to record files seen by {}: {}", spec.name, e);
+            }
+        }
+    }
+
+    Ok(Some(result))
+}
+
 /// Tauri command: run a named agent using its prompt file from .chronicle/prompts/{name}.md.
+/// Agents declared in .chronicle/pipeline.toml use their declared prompt, turns and tools.
 #[tauri::command]
 pub async fn run_agent(
     app_handle: AppHandle,
     workspace_path: String,
     agent_name: String,
+    state: State<'_, SharedAppState>,
 ) -> Result<ClaudeResult, String> {
-    let prompt_path =
-        Path::new(&workspace_path).join(format!(".chronicle/prompts/{}.md", agent_name));
-    let prompt = tokio::fs::read_to_string(&prompt_path)
-        .await
-        .map_err(|e| format!("Failed to read {} prompt: {}", agent_name, e))?;
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let pipeline = Pipeline::load(workspace.opened_root()).map_err(|e| e.to_string())?;
+    let spec = match pipeline.agent(&agent_name) {
+        Some(spec) => spec.clone(),
+        None => {
+            let defaults = WorkspaceConfig::load(workspace.opened_root()).agents;
+            let spec = AgentSpec::named(&agent_name, &defaults);
+            spec.validate().map_err(|e| e.to_string())?;
+            spec
+        }
+    };
 
-    run_claude_streaming(&app_handle, &workspace_path, &prompt, Some(15)).await
+    let result = run_agent_spec(&app_handle, &workspace, &spec).await?;
+    Ok(result.unwrap_or_else(|| ClaudeResult {
+        success: true,
+        output: "No changes since last run".to_string(),
+        error: None,
+        duration_ms: 0,
+        rolled_back_writes: Vec::new(),
+    }))
 }
 
-/// Tauri command: run background agents sequentially (tagger, actions, then context-updater).
+/// Tauri command: run the background agent pipeline from .chronicle/pipeline.toml.
+/// Agents run in dependency order. A failing agent stops the pipeline unless it is
+/// marked `continue_on_failure`, in which case only the agents depending on it are skipped.
+/// Either way every agent's outcome is returned: failures as `Failed`, agents that
+/// didn't run as `Skipped`.
 #[tauri::command]
 pub async fn run_background_agents(
     app_handle: AppHandle,
     workspace_path: String,
-) -> Result<(), String> {
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<AgentOutcome>, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let pipeline = match Pipeline::load(workspace.opened_root()) {
+        Ok(p) => p,
+        Err(e) => {
+            app_handle
+                .emit(
+                    "claude:task-error",
+                    TaskErrorEvent {
+                        task: "pipeline".to_string(),
+                        note: None,
+                        error: e.to_string(),
+                    },
+                )
+                .ok();
+            return Err(e.to_string());
+        }
+    };
+    let order = pipeline.execution_order().map_err(|e| e.to_string())?;
+
     app_handle.emit("claude:agents-started", ()).ok();
 
-    // Run tagger agent
-    let tagger_result =
-        run_agent(app_handle.clone(), workspace_path.clone(), "tagger".to_string()).await;
-    if let Err(e) = &tagger_result {
-        app_handle
-            .emit(
-                "claude:task-error",
-                TaskErrorEvent {
-                    task: "tagger".to_string(),
-                    note: None,
-                    error: e.clone(),
-                },
-            )
-            .ok();
-        app_handle.emit("claude:agents-completed", ()).ok();
-        return Err(e.clone());
-    }
+    let mut outcomes = Vec::with_capacity(order.len());
+    let mut unsatisfied: HashSet<&str> = HashSet::new();
+    // The agent whose failure stopped the pipeline
+    let mut stopped_by: Option<String> = None;
+
+    for spec in order {
+        if let Some(failed) = &stopped_by {
+            outcomes.push(AgentOutcome {
+                agent: spec.name.clone(),
+                status: AgentRunStatus::Skipped,
+                error: Some(format!("Pipeline stopped after {} failed", failed)),
+            });
+            continue;
+        }
 
-    // Run actions agent
-    let actions_result =
-        run_agent(app_handle.clone(), workspace_path.clone(), "actions".to_string()).await;
-    if let Err(e) = &actions_result {
-        app_handle
-            .emit(
-                "claude:task-error",
-                TaskErrorEvent {
-                    task: "actions".to_string(),
-                    note: None,
-                    error: e.clone(),
-                },
-            )
-            .ok();
-    }
+        if let Some(dep) = spec.depends_on.iter().find(|d| unsatisfied.contains(d.as_str())) {
+            tracing::info!("Skipping agent {}: dependency {} did not succeed", spec.name, dep);
+            unsatisfied.insert(spec.name.as_str());
+            outcomes.push(AgentOutcome {
+                agent: spec.name.clone(),
+                status: AgentRunStatus::Skipped,
+                error: Some(format!("Dependency {} did not succeed", dep)),
+            });
+            continue;
+        }
+
+        let error = match run_agent_spec(&app_handle, &workspace, spec).await {
+            Ok(None) => {
+                outcomes.push(AgentOutcome {
+                    agent: spec.name.clone(),
+                    status: AgentRunStatus::Unchanged,
+                    error: None,
+                });
+                continue;
+            }
+            Ok(Some(r)) if r.success => None,
+            Ok(Some(r)) => Some(
+                r.error
+                    .unwrap_or_else(|| format!("Agent {} failed", spec.name)),
+            ),
+            Err(e) => Some(e),
+        };
+
+        let Some(error) = error else {
+            outcomes.push(AgentOutcome {
+                agent: spec.name.clone(),
+                status: AgentRunStatus::Succeeded,
+                error: None,
+            });
+            continue;
+        };
 
-    // Run context-updater agent
-    let context_result = run_agent(
-        app_handle.clone(),
-        workspace_path.clone(),
-        "context-updater".to_string(),
-    )
-    .await;
-    if let Err(e) = &context_result {
         app_handle
             .emit(
                 "claude:task-error",
                 TaskErrorEvent {
-                    task: "context-updater".to_string(),
+                    task: spec.name.clone(),
                     note: None,
-                    error: e.clone(),
+                    error: error.clone(),
                 },
             )
             .ok();
+
+        unsatisfied.insert(spec.name.as_str());
+        if !spec.continue_on_failure {
+            stopped_by = Some(spec.name.clone());
+        }
+        outcomes.push(AgentOutcome {
+            agent: spec.name.clone(),
+            status: AgentRunStatus::Failed,
+            error: Some(error),
+        });
     }
 
     app_handle.emit("claude:agents-completed", ()).ok();
 
-    context_result.map(|_| ())
+    Ok(outcomes)
 }
 
 /// Tauri command: check if Claude Code CLI is installed.
@@ -362,26 +710,29 @@ pub async fn generate_digest(
     range: String,
     from_date: Option<String>,
     to_date: Option<String>,
+    state: State<'_, SharedAppState>,
 ) -> Result<ClaudeResult, String> {
-    let prompt_path = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("prompts")
-        .join("digest.md");
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let prompt_path = workspace.join(".chronicle/prompts/digest.md")?;
 
+    let config = WorkspaceConfig::load(workspace.opened_root());
     let base_prompt = std::fs::read_to_string(&prompt_path)
+        .map(|p| config.expand_prompt(&p))
         .map_err(|e| format!("Failed to read digest prompt: {}", e))?;
 
     let today = chrono::Local::now().format("%Y-%m-%d").to_string();
     let (from, to) = match range.as_str() {
         "daily" => (today.clone(), today.clone()),
         "weekly" => {
-            let week_ago = (chrono::Local::now() - chrono::Duration::days(7))
+            let days = config.digests.weekly_days.into();
+            let week_ago = (chrono::Local::now() - chrono::Duration::days(days))
                 .format("%Y-%m-%d")
                 .to_string();
             (week_ago, today.clone())
         }
         "monthly" => {
-            let month_ago = (chrono::Local::now() - chrono::Duration::days(30))
+            let days = config.digests.monthly_days.into();
+            let month_ago = (chrono::Local::now() - chrono::Duration::days(days))
                 .format("%Y-%m-%d")
                 .to_string();
             (month_ago, today.clone())
@@ -393,11 +744,9 @@ pub async fn generate_digest(
         _ => return Err(format!("Unknown range: {}", range)),
     };
 
+    // Custom dates come from the frontend and end up in the file name
     let output_filename = format!("{}-{}.md", from, range);
-    let output_path = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("digests")
-        .join(&output_filename);
+    let output_path = workspace.join_file_name(".chronicle/digests", &output_filename)?;
 
     // Ensure digests directory exists
     if let Some(parent) = output_path.parent() {
@@ -412,7 +761,7 @@ pub async fn generate_digest(
         from,
         to,
         workspace_path,
-        output_path.display()
+        workspace.display_path_of(&output_path).display()
     );
 
     // Emit task-started
@@ -426,7 +775,16 @@ pub async fn generate_digest(
         )
         .ok();
 
-    let result = run_claude_streaming(&app_handle, &workspace_path, &full_prompt, Some(15)).await;
+    // The digest is all it may write
+    let permissions = config.agents.permissions(vec![output_path.relative_str()]);
+    let result = run_claude_streaming(
+        &app_handle,
+        &workspace,
+        &full_prompt,
+        Some(config.agents.max_turns),
+        &permissions,
+    )
+    .await;
 
     match &result {
         Ok(r) if r.success => {
@@ -475,10 +833,12 @@ pub async fn generate_digest(
 
 /// List available digests
 #[tauri::command]
-pub async fn list_digests(workspace_path: String) -> Result<Vec<DigestInfo>, String> {
-    let digests_dir = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("digests");
+pub async fn list_digests(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<DigestInfo>, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let digests_dir = workspace.opened_root().join(".chronicle").join("digests");
 
     if !digests_dir.exists() {
         return Ok(vec![]);
@@ -536,14 +896,17 @@ pub async fn run_custom_command(
     workspace_path: String,
     command_filename: String,
     params: HashMap<String, String>,
+    state: State<'_, SharedAppState>,
 ) -> Result<ClaudeResult, String> {
-    let command_path = Path::new(&workspace_path)
-        .join(".chronicle")
-        .join("commands")
-        .join(&command_filename);
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let command_path = workspace.join_file_name(".chronicle/commands", &command_filename)?;
 
-    let mut prompt = std::fs::read_to_string(&command_path)
+    let content = std::fs::read_to_string(&command_path)
         .map_err(|e| format!("Failed to read command: {}", e))?;
+    // Declared in the command's front matter; commands that declare nothing are read-only
+    let (permissions, body) = command_permissions(&content)?;
+    let config = WorkspaceConfig::load(workspace.opened_root());
+    let mut prompt = config.expand_prompt(body);
 
     // Substitute parameters
     for (key, value) in &params {
@@ -571,7 +934,14 @@ pub async fn run_custom_command(
         )
         .ok();
 
-    let result = run_claude_streaming(&app_handle, &workspace_path, &full_prompt, Some(15)).await;
+    let result = run_claude_streaming(
+        &app_handle,
+        &workspace,
+        &full_prompt,
+        Some(config.agents.max_turns),
+        &permissions,
+    )
+    .await;
 
     match &result {
         Ok(r) => {
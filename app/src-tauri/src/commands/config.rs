@@ -0,0 +1,53 @@
+use serde::Serialize;
+use tauri::State;
+
+use crate::storage::{WorkspaceConfig, WorkspacePath, CONFIG_FILE};
+use crate::SharedAppState;
+
+/// The settings in effect for a workspace, and why its config.toml isn't used
+/// when it is invalid
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigState {
+    pub config: WorkspaceConfig,
+    pub error: Option<String>,
+}
+
+/// Read .chronicle/config.toml. An invalid file is reported in `error`, alongside
+/// the defaults used in its place.
+#[tauri::command]
+pub async fn get_config(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<ConfigState, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    Ok(match WorkspaceConfig::read(workspace.opened_root()) {
+        Ok(config) => ConfigState {
+            config,
+            error: None,
+        },
+        Err(e) => ConfigState {
+            config: WorkspaceConfig::default(),
+            error: Some(e.to_string()),
+        },
+    })
+}
+
+/// Validate settings and write them to .chronicle/config.toml. The watcher
+/// announces the change with `chronicle:config-updated`.
+#[tauri::command]
+pub async fn update_config(
+    workspace_path: String,
+    config: WorkspaceConfig,
+    state: State<'_, SharedAppState>,
+) -> Result<(), String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    config
+        .save(workspace.opened_root())
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+    state
+        .write()
+        .await
+        .record_user_write(workspace.root().join(".chronicle").join(CONFIG_FILE));
+    Ok(())
+}
@@ -1,41 +1,26 @@
-use crate::storage;
+use crate::notes;
+use crate::storage::{self, WorkspaceConfig, WorkspacePath};
 use crate::SharedAppState;
 use serde::Serialize;
 use std::env;
-use std::path::Path;
 use tauri::State;
 
-/// Maximum file size: 50MB
-const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
-
-/// Get workspace path from shared app state, returning an error if not set
-async fn get_workspace_path(state: &SharedAppState) -> Result<String, String> {
-    let app_state = state.read().await;
-    app_state
-        .workspace_path
-        .clone()
-        .ok_or_else(|| "No workspace open".to_string())
-}
-
 #[tauri::command]
 pub async fn read_file(
     path: String,
     state: State<'_, SharedAppState>,
 ) -> Result<String, String> {
-    let workspace = get_workspace_path(&state).await?;
-    let workspace_path = Path::new(&workspace);
-    let target_path = Path::new(&path);
-
     // Validate path is within workspace
-    let validated = storage::validate_workspace_path(workspace_path, target_path)?;
+    let validated = WorkspacePath::resolve(&state, &path).await?;
 
     // Check file size before reading
+    let files = WorkspaceConfig::load(validated.root()).files;
     let metadata = std::fs::metadata(&validated)
         .map_err(|e| format!("Cannot access file: {}", e))?;
-    if metadata.len() > MAX_FILE_SIZE {
+    if metadata.len() > files.max_file_size() {
         return Err(format!(
             "File exceeds maximum size of {}MB",
-            MAX_FILE_SIZE / (1024 * 1024)
+            files.max_file_size_mb
         ));
     }
 
@@ -48,47 +33,151 @@ pub async fn write_file(
     content: String,
     state: State<'_, SharedAppState>,
 ) -> Result<(), String> {
-    let workspace = get_workspace_path(&state).await?;
-    let workspace_path = Path::new(&workspace);
-    let target_path = Path::new(&path);
-
     // Validate path is within workspace
-    let validated = storage::validate_workspace_path(workspace_path, target_path)?;
+    let validated = WorkspacePath::resolve(&state, &path).await?;
 
     // Check content size before writing
-    if content.len() as u64 > MAX_FILE_SIZE {
+    let files = WorkspaceConfig::load(validated.root()).files;
+    if content.len() as u64 > files.max_file_size() {
         return Err(format!(
             "Content exceeds maximum size of {}MB",
-            MAX_FILE_SIZE / (1024 * 1024)
+            files.max_file_size_mb
         ));
     }
 
-    storage::write_file_atomic(&validated, &content).map_err(|e| e.to_string())
+    storage::write_file_atomic(&validated, &content).map_err(|e| e.to_string())?;
+
+    let mut app_state = state.write().await;
+    if let Some(session) = app_state.workspaces.get_mut(validated.id()) {
+        session.last_edited_at = Some(chrono::Utc::now());
+    }
+    app_state.record_user_write(validated.to_path_buf());
+    Ok(())
+}
+
+/// A note split into its front matter and body
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteDocument {
+    pub path: String,
+    pub front_matter: storage::FrontMatter,
+    pub body: String,
+    /// Front matter title, falling back to the first H1 heading
+    pub title: Option<String>,
 }
 
+impl NoteDocument {
+    fn parse(path: String, content: &str) -> Result<Self, String> {
+        let (front_matter, body) =
+            storage::parse_front_matter(content).map_err(|e| e.to_string())?;
+        Ok(Self {
+            path,
+            front_matter,
+            body: body.to_string(),
+            title: storage::extract_title(content),
+        })
+    }
+}
+
+/// Read a note with its front matter parsed
 #[tauri::command]
-pub async fn file_exists(path: String) -> bool {
-    storage::file_exists(Path::new(&path))
+pub async fn read_note(
+    path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<NoteDocument, String> {
+    let validated = WorkspacePath::resolve(&state, &path).await?;
+
+    let content = storage::read_file(&validated).map_err(|e| e.to_string())?;
+    NoteDocument::parse(path, &content)
 }
 
+/// Replace a note's front matter without touching its body
 #[tauri::command]
-pub async fn suggest_rename(path: String, content: String) -> Option<String> {
-    storage::suggest_path(Path::new(&path), &content).map(|p| p.display().to_string())
+pub async fn update_front_matter(
+    path: String,
+    front_matter: storage::FrontMatter,
+    state: State<'_, SharedAppState>,
+) -> Result<NoteDocument, String> {
+    let validated = WorkspacePath::resolve(&state, &path).await?;
+
+    let content = storage::read_file(&validated).map_err(|e| e.to_string())?;
+    let updated = storage::set_front_matter(&content, &front_matter)
+        .map_err(|e| format!("Failed to update front matter: {}", e))?;
+    storage::write_file_atomic(&validated, &updated).map_err(|e| e.to_string())?;
+
+    let mut app_state = state.write().await;
+    if let Some(session) = app_state.workspaces.get_mut(validated.id()) {
+        session.last_edited_at = Some(chrono::Utc::now());
+    }
+    app_state.record_user_write(validated.to_path_buf());
+    NoteDocument::parse(path, &updated)
 }
 
+/// Whether a file exists. Paths outside the workspace never do.
 #[tauri::command]
-pub async fn rename_file(old_path: String, new_path: String) -> Result<String, String> {
-    storage::rename_file(Path::new(&old_path), Path::new(&new_path))
-        .map(|p| p.display().to_string())
-        .map_err(|e| e.to_string())
+pub async fn file_exists(path: String, state: State<'_, SharedAppState>) -> Result<bool, String> {
+    Ok(WorkspacePath::resolve(&state, &path)
+        .await
+        .is_ok_and(|p| storage::file_exists(&p)))
 }
 
-/// Generate a unique path for a new note (handles conflicts with suffixes)
+/// Suggest a new file name for a note from its title, per the workspace naming scheme
 #[tauri::command]
-pub async fn generate_note_path(workspace_path: String, content: String) -> String {
-    storage::generate_unique_path(Path::new(&workspace_path), &content)
-        .display()
-        .to_string()
+pub async fn suggest_rename(
+    path: String,
+    content: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Option<String>, String> {
+    let note = WorkspacePath::resolve(&state, &path).await?;
+    Ok(storage::suggest_path(note.root(), &note, &content)
+        .map(|p| note.display_path_of(&p).display().to_string()))
+}
+
+#[tauri::command]
+pub async fn rename_file(
+    old_path: String,
+    new_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<String, String> {
+    let from = WorkspacePath::resolve(&state, &old_path).await?;
+    let to = WorkspacePath::resolve(&state, &new_path).await?;
+    if from.id() != to.id() {
+        return Err("Notes can't be moved to another workspace".to_string());
+    }
+
+    // Carries metadata, processed outputs, indexes and links along with the note
+    let report = notes::move_note(from.root(), &note_relative(&from)?, &note_relative(&to)?)
+        .map_err(|e| format!("Failed to rename file: {}", e))?;
+    let final_path = report.path.unwrap_or_else(|| to.to_path_buf());
+
+    let mut app_state = state.write().await;
+    app_state.record_user_write(from.to_path_buf());
+    app_state.record_user_write(final_path.clone());
+    Ok(to.display_path_of(&final_path).display().to_string())
+}
+
+/// Workspace-relative path of a note; the workspace itself isn't one
+fn note_relative(path: &WorkspacePath) -> Result<String, String> {
+    match path.relative_str() {
+        relative if relative.is_empty() => Err("Path is the workspace itself".to_string()),
+        relative => Ok(relative),
+    }
+}
+
+/// Generate a unique path for a new note from the workspace naming scheme (handles
+/// conflicts with suffixes). Notes created in `folder_path` stay there; others are
+/// filed into the folders the scheme's pattern names.
+#[tauri::command]
+pub async fn generate_note_path(
+    workspace_path: String,
+    content: String,
+    folder_path: Option<String>,
+    state: State<'_, SharedAppState>,
+) -> Result<String, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let folder = folder_path.map(|f| workspace.join(f)).transpose()?;
+    let path = storage::new_note_path(workspace.root(), folder.as_deref(), &content, None);
+    Ok(workspace.display_path_of(&path).display().to_string())
 }
 
 /// Get the user's default shell from environment
@@ -108,27 +197,27 @@ pub async fn delete_file(
     path: String,
     state: State<'_, SharedAppState>,
 ) -> Result<(), String> {
-    let workspace = get_workspace_path(&state).await?;
-    let workspace_path = Path::new(&workspace);
-    let target_path = Path::new(&path);
+    let note = WorkspacePath::resolve(&state, &path).await?;
 
-    // Validate path is within workspace
-    let validated = storage::validate_workspace_path(workspace_path, target_path)?;
+    // Clean up metadata and indexes, then move the note to the OS trash
+    notes::delete_note(note.root(), &note_relative(&note)?, |p| {
+        trash::delete(p).map_err(|e| e.to_string())
+    })
+    .map_err(|e| format!("Failed to delete file: {}", e))?;
 
-    // Move to OS trash
-    trash::delete(&validated).map_err(|e| format!("Failed to delete file: {}", e))
+    state.write().await.record_user_write(note.to_path_buf());
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn create_folder(
     workspace_path: String,
     folder_path: String,
+    state: State<'_, SharedAppState>,
 ) -> Result<(), String> {
-    let ws = Path::new(&workspace_path);
-    let target = Path::new(&folder_path);
-
-    // Validate path is within workspace
-    let validated = storage::validate_workspace_path(ws, target)?;
+    // Validate path is within the open workspace
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let validated = workspace.join(&folder_path)?;
 
     std::fs::create_dir_all(&validated)
         .map_err(|e| format!("Failed to create folder: {}", e))
@@ -143,6 +232,9 @@ pub struct ParsedAIOutput {
     pub actions: Vec<ActionItem>,
     pub questions: Vec<Question>,
     pub raw_notes: Option<String>,
+    /// The note changed since it was processed
+    pub stale: bool,
+    pub lines_changed: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -177,15 +269,27 @@ pub async fn read_processed_file(
     path: String,
     state: State<'_, SharedAppState>,
 ) -> Result<ParsedAIOutput, String> {
-    let workspace = get_workspace_path(&state).await?;
-    let workspace_path = Path::new(&workspace);
-    let target_path = Path::new(&path);
-
     // Validate path is within workspace
-    let validated = storage::validate_workspace_path(workspace_path, target_path)?;
+    let validated = WorkspacePath::resolve(&state, &path).await?;
+    let workspace_path = validated.root();
 
     let content = storage::read_file(&validated).map_err(|e| e.to_string())?;
-    Ok(parse_processed_content(&content))
+    let mut output = parse_processed_content(&content);
+
+    // Staleness comes from the JSON written alongside the markdown
+    let json = std::fs::read_to_string(validated.with_extension("json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok());
+    let key = crate::pipeline::output_key(workspace_path, &validated);
+    if let (Some(json), Some(key)) = (json, key) {
+        let manifest = crate::pipeline::ProcessedManifest::load(workspace_path);
+        let (_, freshness) =
+            crate::pipeline::processed_freshness(workspace_path, &key, &json, &manifest);
+        output.stale = freshness.stale;
+        output.lines_changed = freshness.lines_changed;
+    }
+
+    Ok(output)
 }
 
 /// Parse processed markdown content into structured sections
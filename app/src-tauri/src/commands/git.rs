@@ -1,22 +1,36 @@
 use crate::git::{commit_files, get_uncommitted_files, CommitType};
-use crate::storage::get_meta_path;
-use std::path::Path;
+use crate::pipeline::AutoProcessQueue;
+use crate::storage::{get_meta_path, WorkspacePath};
+use crate::SharedAppState;
+use tauri::{AppHandle, Manager, State};
 
-/// Commit a note to git (on file close/switch)
+/// Commit a note to git (on file close/switch).
+/// Also queues the note for auto-processing, which runs only if the workspace opted in.
 #[tauri::command]
-pub fn commit_session(
+pub async fn commit_session(
+    app_handle: AppHandle,
     workspace_path: String,
     note_path: String,
     title: String,
     duration_minutes: u32,
+    state: State<'_, SharedAppState>,
 ) -> Result<String, String> {
-    let workspace = Path::new(&workspace_path);
-    let note = Path::new(&note_path);
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let note = workspace.join(&note_path)?;
+    commit_note_session(app_handle, &note, &title, duration_minutes)
+}
 
-    // Get relative paths for the note and its metadata
-    let note_relative = note.strip_prefix(workspace).unwrap_or(note);
+/// Commit a note and its metadata as a session and queue the note for auto-processing
+pub(crate) fn commit_note_session(
+    app_handle: AppHandle,
+    note: &WorkspacePath,
+    title: &str,
+    duration_minutes: u32,
+) -> Result<String, String> {
+    let workspace = note.root();
 
-    let meta_path = get_meta_path(note);
+    // Get relative paths for the note and its metadata
+    let meta_path = get_meta_path(workspace, note).map_err(|e| e.to_string())?;
     let meta_relative = meta_path.strip_prefix(workspace).unwrap_or(&meta_path);
 
     // Format duration
@@ -25,29 +39,49 @@ pub fn commit_session(
     // Commit the note and metadata files
     let commit_id = commit_files(
         workspace,
-        &[note_relative, meta_relative],
+        &[note.relative(), meta_relative],
         CommitType::Session,
-        &title,
+        title,
         &duration_str,
     )
     .map_err(|e| format!("Git commit failed: {}", e))?;
 
+    // Processing needs agents, which only run in trusted workspaces
+    let queue = app_handle
+        .try_state::<AutoProcessQueue>()
+        .filter(|_| crate::storage::is_workspace_trusted(workspace));
+    if let Some(queue) = queue {
+        queue.enqueue(
+            app_handle.clone(),
+            note.id().to_string(),
+            note.opened_root().display().to_string(),
+            note.display_path().display().to_string(),
+        );
+    }
+
     Ok(commit_id)
 }
 
 /// Create a manual snapshot commit
 #[tauri::command]
-pub fn commit_manual_snapshot(workspace_path: String, title: String) -> Result<String, String> {
-    let workspace = Path::new(&workspace_path);
+pub async fn commit_manual_snapshot(
+    workspace_path: String,
+    title: String,
+    state: State<'_, SharedAppState>,
+) -> Result<String, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
 
-    crate::git::commit_snapshot(workspace, &title)
+    crate::git::commit_snapshot(workspace.root(), &title)
         .map_err(|e| format!("Snapshot commit failed: {}", e))
 }
 
 /// Get list of uncommitted files in the workspace
 #[tauri::command]
-pub fn get_git_status(workspace_path: String) -> Result<Vec<String>, String> {
-    let workspace = Path::new(&workspace_path);
+pub async fn get_git_status(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<String>, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
 
-    get_uncommitted_files(workspace).map_err(|e| format!("Git status failed: {}", e))
+    get_uncommitted_files(workspace.root()).map_err(|e| format!("Git status failed: {}", e))
 }
@@ -1,19 +1,27 @@
 pub mod appstate;
 pub mod archive;
+pub mod attachment;
 pub mod chronicle;
 pub mod claude;
+pub mod config;
 pub mod file;
 pub mod git;
+pub mod preset;
+pub mod schedule;
 pub mod search;
 pub mod session;
 pub mod workspace;
 
 pub use appstate::*;
 pub use archive::*;
+pub use attachment::*;
 pub use chronicle::*;
 pub use claude::*;
+pub use config::*;
 pub use file::*;
 pub use git::*;
+pub use preset::*;
+pub use schedule::*;
 pub use search::*;
 pub use session::*;
 pub use workspace::*;
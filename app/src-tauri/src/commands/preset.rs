@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use crate::presets::{self, PresetInfo};
+use crate::storage;
+
+/// Presets the workspace creation wizard offers: the built-in ones, then the
+/// installed ones
+#[tauri::command]
+pub async fn list_workspace_presets() -> Result<Vec<PresetInfo>, String> {
+    Ok(presets::list_presets().iter().map(|p| p.info()).collect())
+}
+
+/// Install a preset someone shared, as a directory or a .chronicle-preset archive
+#[tauri::command]
+pub async fn install_workspace_preset(path: String) -> Result<PresetInfo, String> {
+    presets::install_preset(Path::new(&path))
+        .map(|p| p.info())
+        .map_err(|e| format!("Failed to install preset: {}", e))
+}
+
+/// Pack a preset into a .chronicle-preset archive to share it
+#[tauri::command]
+pub async fn export_workspace_preset(id: String, path: String) -> Result<(), String> {
+    let preset = presets::find_preset(&id).map_err(|e| e.to_string())?;
+    let archive = preset
+        .to_archive()
+        .map_err(|e| format!("Failed to export preset: {}", e))?;
+    storage::write_file_atomic(Path::new(&path), &archive)
+        .map_err(|e| format!("Failed to export preset: {}", e))
+}
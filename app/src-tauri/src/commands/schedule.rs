@@ -0,0 +1,76 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::scheduler::{self, Schedule};
+use crate::storage::WorkspacePath;
+use crate::tasks::{TaskList, TaskRecord, TaskTrigger};
+use crate::SharedAppState;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledJobInfo {
+    pub name: String,
+    pub label: String,
+    pub enabled: bool,
+    pub at: Option<String>,
+    pub after_edit_minutes: Option<u32>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub next_run: Option<String>,
+}
+
+/// List the jobs in .chronicle/schedule.toml with their last and next run
+#[tauri::command]
+pub async fn get_schedule(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<ScheduledJobInfo>, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let workspace = workspace.opened_root();
+    let schedule =
+        Schedule::load(workspace).map_err(|e| format!("Failed to load schedule: {}", e))?;
+    let runs = scheduler::read_schedule_runs(workspace);
+    let now = Local::now().naive_local();
+
+    Ok(schedule
+        .jobs
+        .iter()
+        .map(|job| ScheduledJobInfo {
+            name: job.name.clone(),
+            label: job.action.label(),
+            enabled: job.enabled,
+            at: job.at.clone(),
+            after_edit_minutes: job.after_edit_minutes,
+            last_run: runs.get(&job.name).copied(),
+            next_run: job
+                .enabled
+                .then(|| job.next_slot(now))
+                .flatten()
+                .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        })
+        .collect())
+}
+
+/// Run a scheduled job now, regardless of its schedule
+#[tauri::command]
+pub async fn run_scheduled_job(
+    app_handle: AppHandle,
+    workspace_path: String,
+    name: String,
+    state: State<'_, SharedAppState>,
+) -> Result<(), String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let schedule = Schedule::load(workspace.opened_root())
+        .map_err(|e| format!("Failed to load schedule: {}", e))?;
+    let job = schedule
+        .job(&name)
+        .ok_or_else(|| format!("Unknown scheduled job: {}", name))?;
+
+    scheduler::run_job(&app_handle, &workspace_path, job, TaskTrigger::Manual).await
+}
+
+/// Recent background tasks, newest first
+#[tauri::command]
+pub fn list_tasks(tasks: State<'_, TaskList>) -> Vec<TaskRecord> {
+    tasks.list()
+}
@@ -1,17 +1,15 @@
 use serde::Serialize;
 use std::fs;
 use std::path::Path;
+use tauri::State;
 use walkdir::WalkDir;
 
+use crate::storage::{FilesConfig, WorkspaceConfig, WorkspacePath};
+use crate::SharedAppState;
+
 /// Maximum line content length in search results
 const MAX_LINE_LENGTH: usize = 200;
 
-/// Default maximum number of search results
-const DEFAULT_MAX_RESULTS: usize = 50;
-
-/// Directories to skip during search
-const SKIP_DIRS: &[&str] = &[".meta", ".raw", ".chronicle", ".git", ".claude", "node_modules"];
-
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
@@ -28,17 +26,86 @@ pub async fn search_notes(
     workspace_path: String,
     query: String,
     max_results: Option<usize>,
+    state: State<'_, SharedAppState>,
 ) -> Result<Vec<SearchResult>, String> {
     if query.is_empty() {
         return Ok(Vec::new());
     }
 
-    let workspace = Path::new(&workspace_path);
-    if !workspace.is_dir() {
-        return Err("Workspace path is not a directory".to_string());
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let config = WorkspaceConfig::load(workspace.opened_root());
+    let max = max_results.unwrap_or(config.search.max_results);
+    Ok(search_workspace(workspace.opened_root(), &config.files, &query, max))
+}
+
+/// A search result from one of the open workspaces
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSearchResult {
+    pub workspace_id: String,
+    pub workspace_name: String,
+    #[serde(flatten)]
+    pub result: SearchResult,
+}
+
+/// Search every open workspace, the active one first, sharing one result limit
+#[tauri::command]
+pub async fn search_all_workspaces(
+    query: String,
+    max_results: Option<usize>,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<WorkspaceSearchResult>, String> {
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut workspaces: Vec<(bool, String, String)> = {
+        let app_state = state.read().await;
+        app_state
+            .workspaces
+            .values()
+            .map(|w| {
+                let active = app_state.active_workspace.as_deref() == Some(w.id.as_str());
+                (!active, w.id.clone(), w.path.clone())
+            })
+            .collect()
+    };
+    workspaces.sort();
+
+    // The active workspace's settings decide the shared limit
+    let max = max_results.unwrap_or_else(|| {
+        workspaces
+            .first()
+            .map(|(_, _, path)| WorkspaceConfig::load(Path::new(path)).search.max_results)
+            .unwrap_or_default()
+    });
+    let mut results = Vec::new();
+    for (_, id, path) in workspaces {
+        if results.len() >= max {
+            break;
+        }
+        let name = crate::commands::workspace_name(Path::new(&path));
+        let files = WorkspaceConfig::load(Path::new(&path)).files;
+        results.extend(
+            search_workspace(Path::new(&path), &files, &query, max - results.len())
+                .into_iter()
+                .map(|result| WorkspaceSearchResult {
+                    workspace_id: id.clone(),
+                    workspace_name: name.clone(),
+                    result,
+                }),
+        );
     }
+    Ok(results)
+}
 
-    let max = max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+/// Search the notes in a workspace, most recently modified first
+fn search_workspace(
+    workspace: &Path,
+    files: &FilesConfig,
+    query: &str,
+    max: usize,
+) -> Vec<SearchResult> {
     let query_lower = query.to_lowercase();
 
     // Collect all markdown files with their modification times
@@ -49,12 +116,12 @@ pub async fn search_notes(
         .into_iter()
         .filter_entry(|e| {
             let name = e.file_name().to_string_lossy();
-            // Skip hidden dirs and special directories
+            // Skip hidden dirs (.chronicle, .git, ...) and the configured ones
             if e.depth() > 0 && name.starts_with('.') {
                 return false;
             }
             if e.file_type().is_dir() {
-                return !SKIP_DIRS.contains(&name.as_ref());
+                return !files.skips(&name);
             }
             true
         })
@@ -108,7 +175,30 @@ pub async fn search_notes(
             .display()
             .to_string();
 
-        for (i, line) in lines.iter().enumerate() {
+        // Front matter matches by field; the raw YAML lines aren't searched
+        let body_start = front_matter_lines(&content);
+        for (field, value) in front_matter_fields(&content) {
+            if results.len() >= max {
+                break;
+            }
+            if value.to_lowercase().contains(&query_lower) {
+                let line_number = lines[..body_start]
+                    .iter()
+                    .position(|l| l.starts_with(&format!("{}:", field)))
+                    .map(|i| i + 1)
+                    .unwrap_or(1);
+                results.push(SearchResult {
+                    file_path: relative_path.clone(),
+                    file_name: file_name.clone(),
+                    line_number,
+                    line_content: format!("{}: {}", field, value),
+                    context_before: String::new(),
+                    context_after: String::new(),
+                });
+            }
+        }
+
+        for (i, line) in lines.iter().enumerate().skip(body_start) {
             if results.len() >= max {
                 break;
             }
@@ -154,5 +244,35 @@ pub async fn search_notes(
         }
     }
 
-    Ok(results)
+    results
+}
+
+/// Number of lines taken by a note's front matter block, delimiters included
+fn front_matter_lines(content: &str) -> usize {
+    match crate::storage::split_front_matter(content) {
+        (Some(block), _) => block.lines().count() + 2,
+        (None, _) => 0,
+    }
+}
+
+/// Searchable front matter fields with their values
+fn front_matter_fields(content: &str) -> Vec<(&'static str, String)> {
+    let Ok((front_matter, _)) = crate::storage::parse_front_matter(content) else {
+        return Vec::new();
+    };
+
+    let mut fields = Vec::new();
+    if let Some(title) = front_matter.title {
+        fields.push(("title", title));
+    }
+    if !front_matter.tags.is_empty() {
+        fields.push(("tags", front_matter.tags.join(", ")));
+    }
+    if !front_matter.attendees.is_empty() {
+        fields.push(("attendees", front_matter.attendees.join(", ")));
+    }
+    if let Some(project) = front_matter.project {
+        fields.push(("project", project));
+    }
+    fields
 }
@@ -1,15 +1,44 @@
-use crate::session::{TrackerInfo, TrackerManager};
-use crate::storage::{load_metadata, save_metadata, NoteMeta};
+use crate::session::{
+    export_report, journal_id, orphaned_journals, read_journal, record_session, recover_journal,
+    remove_journal, session_record, time_report, time_stats, write_journal, NoteTracker,
+    OrphanedSession, RecoveredSession, ReportFormat, ReportGroup, SessionJournal, TimeReport,
+    TimeStats, TrackerInfo, TrackerManager, DEFAULT_PANE,
+};
+use crate::storage::{extract_title, load_metadata, save_metadata, NoteMeta, WorkspacePath};
+use crate::SharedAppState;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
 use std::path::Path;
-use std::sync::Mutex;
-use tauri::State;
+use std::sync::{Mutex, MutexGuard};
+use tauri::{AppHandle, State};
 
-/// Wrapper for thread-safe tracker manager (Tauri managed state)
-pub struct TrackerManagerState(pub Mutex<TrackerManager>);
+/// One tracker manager per open workspace, keyed by workspace ID (Tauri managed state)
+pub struct TrackerManagerState(pub Mutex<HashMap<String, TrackerManager>>);
 
 impl TrackerManagerState {
     pub fn new() -> Self {
-        Self(Mutex::new(TrackerManager::new()))
+        Self(Mutex::new(HashMap::new()))
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, HashMap<String, TrackerManager>>, String> {
+        self.0
+            .lock()
+            .map_err(|e| format!("Failed to acquire tracker lock: {}", e))
+    }
+
+    /// Run `f` with a workspace's tracker manager, creating it on first use
+    pub fn with<R>(
+        &self,
+        workspace_id: &str,
+        f: impl FnOnce(&TrackerManager) -> R,
+    ) -> Result<R, String> {
+        let mut managers = self.lock()?;
+        Ok(f(managers.entry(workspace_id.to_string()).or_default()))
+    }
+
+    /// Take a closed workspace's tracker manager out of the state
+    pub fn remove(&self, workspace_id: &str) -> Option<TrackerManager> {
+        self.lock().ok()?.remove(workspace_id)
     }
 }
 
@@ -19,47 +48,355 @@ impl Default for TrackerManagerState {
     }
 }
 
-/// Get current tracker info (duration, path)
+fn pane(pane_id: Option<String>) -> String {
+    pane_id.unwrap_or_else(|| DEFAULT_PANE.to_string())
+}
+
+/// The workspace a tracking command is for: the one named by ID, or the active one
+async fn tracked_workspace(
+    state: &SharedAppState,
+    workspace_id: Option<String>,
+) -> Result<WorkspacePath, String> {
+    match workspace_id {
+        Some(id) => WorkspacePath::check_workspace(state, &id).await,
+        None => WorkspacePath::workspace(state).await,
+    }
+}
+
+/// Rewrite a pane's session journal. Failures are logged: the journal is a safety
+/// net and shouldn't interrupt editing.
+fn journal_pane(
+    workspace: &Path,
+    manager: &TrackerManager,
+    pane: &str,
+    unsaved_content: Option<String>,
+) {
+    let Some(tracker) = manager.snapshot(pane) else {
+        return;
+    };
+    let journal = SessionJournal::new(manager.run_id(), pane, &tracker, unsaved_content);
+    if let Err(e) = write_journal(workspace, &journal) {
+        tracing::warn!("Failed to write session journal for pane {}: {}", pane, e);
+    }
+}
+
+/// Get current tracker info (active duration, path) for a pane
 #[tauri::command]
-pub fn get_tracker_info(tracker_state: State<'_, TrackerManagerState>) -> Result<Option<TrackerInfo>, String> {
-    let manager = tracker_state.0.lock()
-        .map_err(|e| format!("Failed to acquire tracker lock: {}", e))?;
-    Ok(manager.get_info())
+pub async fn get_tracker_info(
+    pane_id: Option<String>,
+    workspace_id: Option<String>,
+    tracker_state: State<'_, TrackerManagerState>,
+    state: State<'_, SharedAppState>,
+) -> Result<Option<TrackerInfo>, String> {
+    let workspace = tracked_workspace(&state, workspace_id).await?;
+    tracker_state.with(workspace.id(), |manager| manager.get_info(&pane(pane_id)))
 }
 
-/// Start tracking a note (called when opening a note)
+/// Tracker info for every pane of a workspace
 #[tauri::command]
-pub fn start_tracking(note_path: String, tracker_state: State<'_, TrackerManagerState>) -> Result<(), String> {
-    let manager = tracker_state.0.lock()
-        .map_err(|e| format!("Failed to acquire tracker lock: {}", e))?;
-    manager.start_tracking(&note_path);
+pub async fn list_trackers(
+    workspace_id: Option<String>,
+    tracker_state: State<'_, TrackerManagerState>,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<TrackerInfo>, String> {
+    let workspace = tracked_workspace(&state, workspace_id).await?;
+    tracker_state.with(workspace.id(), |manager| manager.list())
+}
+
+/// Start tracking a note (called when opening a note in a pane). The note's
+/// workspace tracks it.
+#[tauri::command]
+pub async fn start_tracking(
+    note_path: String,
+    pane_id: Option<String>,
+    tracker_state: State<'_, TrackerManagerState>,
+    state: State<'_, SharedAppState>,
+) -> Result<(), String> {
+    let pane = pane(pane_id);
+    let note = WorkspacePath::resolve(&state, &note_path).await?;
+    // The absolute path under the opened root, which sessions are recorded against
+    let note_path = note.display_path().display().to_string();
+    tracker_state.with(note.id(), |manager| {
+        manager.start_tracking(&pane, &note_path);
+        journal_pane(note.opened_root(), manager, &pane, None);
+    })
+}
+
+/// Record editor activity in a pane and journal the session, with the editor's
+/// unsaved content if it has any. Returns false when the pane isn't tracking.
+#[tauri::command]
+pub async fn tracker_heartbeat(
+    pane_id: Option<String>,
+    workspace_id: Option<String>,
+    unsaved_content: Option<String>,
+    tracker_state: State<'_, TrackerManagerState>,
+    state: State<'_, SharedAppState>,
+) -> Result<bool, String> {
+    let pane = pane(pane_id);
+    let workspace = tracked_workspace(&state, workspace_id).await?;
+    tracker_state.with(workspace.id(), |manager| {
+        if !manager.heartbeat(&pane) {
+            return false;
+        }
+        journal_pane(workspace.opened_root(), manager, &pane, unsaved_content);
+        true
+    })
+}
+
+/// Pause time tracking in a pane, or in every pane when none is given. Without a
+/// workspace, every open workspace's trackers pause (e.g. the window lost focus).
+#[tauri::command]
+pub fn pause_tracking(
+    pane_id: Option<String>,
+    workspace_id: Option<String>,
+    tracker_state: State<'_, TrackerManagerState>,
+) -> Result<(), String> {
+    set_paused(&tracker_state, pane_id, workspace_id, true)
+}
+
+/// Resume time tracking in a pane, or in every pane when none is given
+#[tauri::command]
+pub fn resume_tracking(
+    pane_id: Option<String>,
+    workspace_id: Option<String>,
+    tracker_state: State<'_, TrackerManagerState>,
+) -> Result<(), String> {
+    set_paused(&tracker_state, pane_id, workspace_id, false)
+}
+
+fn set_paused(
+    tracker_state: &TrackerManagerState,
+    pane_id: Option<String>,
+    workspace_id: Option<String>,
+    paused: bool,
+) -> Result<(), String> {
+    let managers = tracker_state.lock()?;
+    for (id, manager) in managers.iter() {
+        if workspace_id.as_ref().is_none_or(|w| w == id) {
+            manager.set_paused(pane_id.as_deref(), paused);
+        }
+    }
     Ok(())
 }
 
-/// Stop tracking and return duration info (called when closing/switching notes)
-/// Returns the tracker data for use in commit
+/// Stop tracking a pane and return duration info (called when closing/switching notes)
+/// Records the session in the note's metadata history and returns the tracker data
+/// for use in commit
 #[tauri::command]
-pub fn stop_tracking(tracker_state: State<'_, TrackerManagerState>) -> Result<Option<TrackerInfo>, String> {
-    let manager = tracker_state.0.lock()
-        .map_err(|e| format!("Failed to acquire tracker lock: {}", e))?;
-    let tracker = manager.stop_tracking();
-    Ok(tracker.map(|t| {
-        let duration = t.duration_minutes();
-        TrackerInfo {
-            note_path: t.note_path,
-            duration_minutes: duration,
-            opened_at: t.opened_at,
+pub async fn stop_tracking(
+    pane_id: Option<String>,
+    workspace_id: Option<String>,
+    tracker_state: State<'_, TrackerManagerState>,
+    state: State<'_, SharedAppState>,
+) -> Result<Option<TrackerInfo>, String> {
+    let pane = pane(pane_id);
+    let workspace = tracked_workspace(&state, workspace_id).await?;
+    let now = chrono::Utc::now();
+    let (tracker, idle_timeout, run_id) = tracker_state.with(workspace.id(), |manager| {
+        (manager.stop_tracking(&pane), manager.idle_timeout(), manager.run_id().to_string())
+    })?;
+
+    if let Some(tracker) = tracker.as_ref() {
+        finish_tracked_session(workspace.opened_root(), &pane, tracker, now, idle_timeout, &run_id);
+    }
+
+    Ok(tracker.map(|t| TrackerInfo::new(&pane, &t, now, idle_timeout)))
+}
+
+/// Record a pane's finished session and drop its journal
+fn finish_tracked_session(
+    workspace: &Path,
+    pane: &str,
+    tracker: &NoteTracker,
+    ended_at: DateTime<Utc>,
+    idle_timeout: Duration,
+    run_id: &str,
+) {
+    record_tracked_session(workspace, tracker, ended_at, idle_timeout);
+    if let Err(e) = remove_journal(workspace, &journal_id(run_id, pane)) {
+        tracing::warn!("Failed to remove session journal for pane {}: {}", pane, e);
+    }
+}
+
+/// Finish every session a closing workspace is still tracking
+pub fn stop_workspace_tracking(tracker_state: &TrackerManagerState, workspace: &WorkspacePath) {
+    let Some(manager) = tracker_state.remove(workspace.id()) else {
+        return;
+    };
+    let now = chrono::Utc::now();
+    for info in manager.list() {
+        if let Some(tracker) = manager.stop_tracking(&info.pane_id) {
+            finish_tracked_session(
+                workspace.opened_root(),
+                &info.pane_id,
+                &tracker,
+                now,
+                manager.idle_timeout(),
+                manager.run_id(),
+            );
+        }
+    }
+}
+
+/// Append a finished session to the note's metadata. Failures are logged: losing a
+/// session record shouldn't block closing the note.
+fn record_tracked_session(
+    workspace_path: &Path,
+    tracker: &NoteTracker,
+    ended_at: DateTime<Utc>,
+    idle_timeout: Duration,
+) {
+    let note = Path::new(&tracker.note_path);
+    if !note.is_file() || !note.starts_with(workspace_path) {
+        return;
+    }
+
+    let content = std::fs::read_to_string(note).ok();
+    let Some(session) = session_record(tracker, ended_at, idle_timeout, content.as_deref()) else {
+        return;
+    };
+    if let Err(e) = record_session(workspace_path, note, session) {
+        tracing::warn!("Failed to record session for {}: {}", note.display(), e);
+    }
+}
+
+/// Sessions left unfinished by an earlier run of the app (it quit or crashed
+/// while notes were open)
+#[tauri::command]
+pub async fn list_orphaned_sessions(
+    workspace_path: String,
+    tracker_state: State<'_, TrackerManagerState>,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<OrphanedSession>, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let (run_id, idle_timeout) = tracker_state.with(workspace.id(), |manager| {
+        (manager.run_id().to_string(), manager.idle_timeout())
+    })?;
+    Ok(orphaned_journals(workspace.opened_root(), &run_id)
+        .iter()
+        .map(|journal| OrphanedSession::new(journal, idle_timeout))
+        .collect())
+}
+
+/// Recover an orphaned session: optionally restore its unsaved content, record the
+/// session with its active duration and commit it
+#[tauri::command]
+pub async fn recover_session(
+    app_handle: AppHandle,
+    workspace_path: String,
+    session_id: String,
+    restore_content: bool,
+    tracker_state: State<'_, TrackerManagerState>,
+    state: State<'_, SharedAppState>,
+) -> Result<RecoveredSession, String> {
+    let workspace_root = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    let idle_timeout = tracker_state.with(workspace_root.id(), |manager| manager.idle_timeout())?;
+    let workspace = workspace_root.opened_root();
+    let journal = read_journal(workspace, &session_id)
+        .map_err(|e| format!("Failed to read session journal: {}", e))?;
+    let mut recovered = recover_journal(workspace, &journal, restore_content, idle_timeout)
+        .map_err(|e| format!("Failed to recover session: {}", e))?;
+
+    let note = workspace_root.join(&recovered.note_path);
+    if let Some(note) = note.ok().filter(|note| note.is_file()) {
+        let content = std::fs::read_to_string(&note).unwrap_or_default();
+        let title = extract_title(&content).unwrap_or_else(|| {
+            recovered
+                .note_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("note")
+                .to_string()
+        });
+        match super::commit_note_session(app_handle, &note, &title, recovered.duration_minutes) {
+            Ok(commit_id) => recovered.commit_id = Some(commit_id),
+            Err(e) => tracing::warn!("Recovered session was not committed: {}", e),
         }
-    }))
+    }
+    Ok(recovered)
+}
+
+/// Drop an orphaned session without recording it
+#[tauri::command]
+pub async fn discard_orphaned_session(
+    workspace_path: String,
+    session_id: String,
+    state: State<'_, SharedAppState>,
+) -> Result<(), String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    remove_journal(workspace.root(), &session_id)
+        .map_err(|e| format!("Failed to discard session: {}", e))
+}
+
+/// Time spent per note and per day from recorded sessions. `from` and `to` are
+/// inclusive local dates (YYYY-MM-DD).
+#[tauri::command]
+pub async fn get_time_stats(
+    workspace_path: String,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    state: State<'_, SharedAppState>,
+) -> Result<TimeStats, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    Ok(time_stats(workspace.opened_root(), from, to, &chrono::Local))
+}
+
+/// Time spent grouped by note, folder, tag or day, from recorded sessions and
+/// session commits. `tag` limits the report to notes with a tag starting with it
+/// (e.g. `project:`).
+#[tauri::command]
+pub async fn get_time_report(
+    workspace_path: String,
+    group_by: ReportGroup,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    tag: Option<String>,
+    state: State<'_, SharedAppState>,
+) -> Result<TimeReport, String> {
+    let workspace = WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    Ok(time_report(
+        workspace.opened_root(),
+        group_by,
+        from,
+        to,
+        tag.as_deref(),
+        &chrono::Local,
+    ))
+}
+
+/// Write a time report to `output_path` as CSV or JSON
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_time_report(
+    workspace_path: String,
+    group_by: ReportGroup,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    tag: Option<String>,
+    format: ReportFormat,
+    output_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<(), String> {
+    // The export destination is picked by the user in a save dialog, so it isn't sandboxed
+    let report = get_time_report(workspace_path, group_by, from, to, tag, state).await?;
+    let content = export_report(&report, format)
+        .map_err(|e| format!("Failed to export time report: {}", e))?;
+    crate::storage::write_file_atomic(Path::new(&output_path), &content)
+        .map_err(|e| format!("Failed to write time report: {}", e))
 }
 
 /// Update metadata with the latest file info
 #[tauri::command]
-pub fn update_note_metadata(note_path: String) -> Result<(), String> {
-    let path = Path::new(&note_path);
+pub async fn update_note_metadata(
+    note_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<(), String> {
+    let note = WorkspacePath::resolve(&state, &note_path).await?;
+    let workspace_path = note.root();
+    let path = note.as_path();
 
     // Load or create metadata
-    let mut meta = load_metadata(path)
+    let mut meta = load_metadata(workspace_path, path)
         .map_err(|e| format!("Failed to load metadata: {}", e))?
         .unwrap_or_else(|| NoteMeta::new(path));
 
@@ -67,5 +404,12 @@ pub fn update_note_metadata(note_path: String) -> Result<(), String> {
     meta.update_file();
 
     // Save
-    save_metadata(path, &meta).map_err(|e| format!("Failed to save metadata: {}", e))
+    save_metadata(workspace_path, path, &meta).map_err(|e| format!("Failed to save metadata: {}", e))?;
+
+    // Register the note's stable ID (new notes get theirs here)
+    if let Some(relative) = crate::storage::relative_note_path(workspace_path, path) {
+        crate::notes::ensure_note_id(workspace_path, &relative)
+            .map_err(|e| format!("Failed to register note ID: {}", e))?;
+    }
+    Ok(())
 }
@@ -1,17 +1,30 @@
-use crate::commands::chronicle::init_chronicle_dir;
+use crate::commands::chronicle::{context_with_sections, init_chronicle_dir};
+use crate::commands::session::{stop_workspace_tracking, TrackerManagerState};
 use crate::git;
-use crate::models::{FileNode, Workspace, WorkspaceInfo};
-use crate::storage;
+use crate::models::{
+    FileNode, OpenWorkspace, RecentWorkspace, RecentWorkspaces, Workspace, WorkspaceInfo,
+    WorkspaceStats,
+};
+use crate::pipeline::{changed_files, AgentScope};
+use crate::presets::{self, Preset};
+use crate::scheduler::Scheduler;
+use crate::storage::{self, DirectoryCache, SortMode, WorkspaceConfig};
 use crate::watcher::ChronicleWatcher;
+use crate::{SharedAppState, WorkspaceSession};
 use chrono::Utc;
+use serde::Serialize;
 use serde_json::json;
-use std::path::Path;
-use tauri::Manager;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Manager, State};
 
+/// Open a workspace and make it the active one. Several workspaces can be open at
+/// once; opening one that already is only switches to it. Opening is the only way a
+/// workspace that every other command is sandboxed to is added.
 #[tauri::command]
 pub async fn open_workspace(
     app_handle: tauri::AppHandle,
     path: String,
+    state: State<'_, SharedAppState>,
 ) -> Result<WorkspaceInfo, String> {
     let workspace_path = Path::new(&path);
 
@@ -20,7 +33,211 @@ pub async fn open_workspace(
         return Err(format!("Path is not a directory: {}", path));
     }
 
-    tracing::info!("Opening workspace");
+    let session =
+        WorkspaceSession::new(&path).map_err(|e| format!("Failed to open workspace: {}", e))?;
+    let (id, path, already_open) = {
+        let mut app_state = state.write().await;
+        let already_open = app_state.workspaces.contains_key(&session.id);
+        let session = app_state.activate(session);
+        (session.id.clone(), session.path.clone(), already_open)
+    };
+    let workspace_path = Path::new(&path);
+
+    // Agents, commands, the scheduler and MCP auto-approvals wait for the user's trust
+    let trusted = storage::is_workspace_trusted(workspace_path);
+
+    let is_git_repo = if already_open {
+        tracing::info!("Switching workspace");
+        git::is_git_repo(workspace_path)
+    } else {
+        tracing::info!("Opening workspace");
+        prepare_workspace(&app_handle, &id, &path, trusted)
+    };
+
+    // Note IDs, the watcher's note tree and the note count are built in the
+    // background; big workspaces open without waiting
+    start_note_scan(&app_handle, &id, &path, !already_open);
+
+    let name = workspace_name(workspace_path);
+
+    // Save to recent workspaces
+    let workspace = Workspace {
+        path: path.clone(),
+        name: name.clone(),
+        last_opened: Utc::now(),
+        pinned: false,
+        display_name: None,
+    };
+
+    if let Err(e) = storage::save_recent_workspace(&workspace) {
+        tracing::warn!("Failed to save recent workspace: {}", e);
+    }
+
+    Ok(WorkspaceInfo {
+        id,
+        path,
+        name,
+        is_git_repo,
+        file_count: None,
+        trusted,
+    })
+}
+
+/// Create a workspace folder named `name` in `parent` from a preset, with its
+/// folders, templates, prompts, commands and context sections, and commit it all
+/// as the first commit. Returns the new workspace's path; `open_workspace` opens it.
+#[tauri::command]
+pub async fn create_workspace(
+    parent: String,
+    name: String,
+    preset: String,
+) -> Result<String, String> {
+    let parent = Path::new(&parent);
+    if !parent.is_dir() {
+        return Err(format!("Path is not a directory: {}", parent.display()));
+    }
+    let name = name.trim();
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!("Not a valid folder name: {:?}", name));
+    }
+    let workspace_path = parent.join(name);
+    if workspace_path.exists() {
+        return Err(format!("{} already exists", workspace_path.display()));
+    }
+    let preset = presets::find_preset(&preset).map_err(|e| e.to_string())?;
+
+    if let Err(e) = scaffold_workspace(&workspace_path, &preset) {
+        // Leave nothing half-created behind
+        std::fs::remove_dir_all(&workspace_path).ok();
+        return Err(e);
+    }
+
+    tracing::info!(
+        "Created workspace {} from the {} preset",
+        workspace_path.display(),
+        preset.id
+    );
+    Ok(workspace_path.display().to_string())
+}
+
+/// Write a preset's files into a new workspace folder, fill in the rest of
+/// .chronicle/ and make the initial commit
+fn scaffold_workspace(workspace_path: &Path, preset: &Preset) -> Result<(), String> {
+    storage::ensure_dir(workspace_path)
+        .map_err(|e| format!("Failed to create workspace: {}", e))?;
+    preset
+        .scaffold(workspace_path)
+        .map_err(|e| format!("Failed to apply preset: {}", e))?;
+    if let Some(sections) = preset.context_sections() {
+        storage::write_file(
+            &workspace_path.join(".chronicle").join("context.md"),
+            &context_with_sections(&sections),
+        )
+        .map_err(|e| format!("Failed to write context.md: {}", e))?;
+    }
+    // Defaults for everything the preset doesn't bring
+    init_chronicle_dir(workspace_path)?;
+
+    let message = format!("Initial commit: {} workspace", preset.manifest.name);
+    git::init_repo_with_commit(workspace_path, &message)
+        .map_err(|e| format!("Failed to create initial commit: {}", e))?;
+    Ok(())
+}
+
+/// Step of the background work after a workspace is opened
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanStage {
+    /// Giving notes stable IDs and converting index references to them
+    NoteIds,
+    /// Recording every note so the watcher can tell what changes outside the app
+    Watcher,
+    /// Counting notes
+    Notes,
+}
+
+/// Payload of `workspace:scan-progress`, emitted as each stage of the background
+/// scan starts, while notes are counted and once more when the count is final
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    pub workspace_id: String,
+    pub stage: ScanStage,
+    /// Files and folders looked at so far
+    pub scanned: usize,
+    /// Notes found so far
+    pub notes: usize,
+    pub done: bool,
+}
+
+/// Scan a workspace's notes on a background thread. A newly opened workspace
+/// (`prepare`) first gets its note IDs synced and its watcher started, which both
+/// walk every note.
+fn start_note_scan(app_handle: &tauri::AppHandle, id: &str, path: &str, prepare: bool) {
+    let app_handle = app_handle.clone();
+    let id = id.to_string();
+    let path = path.to_string();
+    std::thread::spawn(move || {
+        let root = PathBuf::from(&path);
+        let emit = |stage, scanned, notes, done| {
+            let progress = ScanProgress {
+                workspace_id: id.clone(),
+                stage,
+                scanned,
+                notes,
+                done,
+            };
+            app_handle.emit("workspace:scan-progress", progress).ok();
+        };
+
+        if prepare {
+            emit(ScanStage::NoteIds, 0, 0, false);
+            sync_note_ids(&root);
+
+            emit(ScanStage::Watcher, 0, 0, false);
+            start_watcher(&app_handle, &id, &path);
+        }
+
+        let files = WorkspaceConfig::load(&root).files;
+        let notes = storage::count_notes(&root, &files, |scanned, notes| {
+            emit(ScanStage::Notes, scanned, notes, false)
+        });
+        emit(ScanStage::Notes, notes, notes, true);
+    });
+}
+
+/// Give every note a stable ID and make the indexes refer to notes by ID
+fn sync_note_ids(workspace_path: &Path) {
+    match crate::notes::sync_note_ids(workspace_path) {
+        Ok(registry) => {
+            if let Err(e) = crate::notes::migrate_index_references(workspace_path, &registry) {
+                tracing::warn!("Failed to convert index references to note IDs: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to sync note IDs: {}", e),
+    }
+}
+
+/// Start the filesystem watcher on the workspace's notes and .chronicle/, unless
+/// the workspace was closed while the scan ran
+fn start_watcher(app_handle: &tauri::AppHandle, id: &str, path: &str) {
+    let still_open = app_handle
+        .try_state::<SharedAppState>()
+        .is_some_and(|state| state.blocking_read().workspaces.contains_key(id));
+    if !still_open {
+        return;
+    }
+    if let Some(watcher) = app_handle.try_state::<ChronicleWatcher>() {
+        if let Err(e) = watcher.start(id, path, app_handle.clone()) {
+            tracing::warn!("Failed to start chronicle watcher: {}", e);
+        }
+    }
+}
+
+/// Set up a newly opened workspace and start its scheduler. Returns whether it is
+/// a git repository.
+fn prepare_workspace(app_handle: &tauri::AppHandle, id: &str, path: &str, trusted: bool) -> bool {
+    let workspace_path = Path::new(path);
 
     // Initialize or open git repo
     let is_git_repo = match git::init_or_open_repo(workspace_path) {
@@ -32,13 +249,14 @@ pub async fn open_workspace(
     };
 
     // Create .mcp.json for Claude Code integration
-    if let Err(e) = create_mcp_config(&app_handle, workspace_path) {
+    if let Err(e) = create_mcp_config(app_handle, workspace_path) {
         tracing::warn!("Failed to create .mcp.json: {}", e);
     }
 
-    // Create .claude/settings.json to auto-approve Chronicle MCP tools
-    if let Err(e) = create_claude_settings(workspace_path) {
-        tracing::warn!("Failed to create .claude/settings.json: {}", e);
+    if trusted {
+        if let Err(e) = create_claude_settings(workspace_path) {
+            tracing::warn!("Failed to create .claude/settings.json: {}", e);
+        }
     }
 
     // Initialize .chronicle/ directory structure
@@ -46,41 +264,91 @@ pub async fn open_workspace(
         tracing::warn!("Failed to initialize .chronicle/: {}", e);
     }
 
-    // Start filesystem watcher on .chronicle/
-    if let Some(watcher) = app_handle.try_state::<ChronicleWatcher>() {
-        if let Err(e) = watcher.start(&path, app_handle.clone()) {
-            tracing::warn!("Failed to start chronicle watcher: {}", e);
+    // Move processed outputs from filename keys to relative-path keys (once)
+    if let Err(e) = crate::pipeline::migrate_processed_outputs(workspace_path) {
+        tracing::warn!("Failed to migrate processed outputs: {}", e);
+    }
+
+    // Move per-folder .meta/ files into .chronicle/meta/ (once)
+    if let Err(e) = crate::storage::migrate_legacy_metadata(workspace_path) {
+        tracing::warn!("Failed to migrate note metadata: {}", e);
+    }
+
+    // Note IDs and the filesystem watcher are set up by the background scan
+
+    // Start the job scheduler (catches up runs missed while closed)
+    if let Some(scheduler) = app_handle.try_state::<Scheduler>() {
+        if !trusted {
+            scheduler.stop(id);
+        } else if let Err(e) = scheduler.start(id, path, app_handle.clone()) {
+            tracing::warn!("Failed to start scheduler: {}", e);
         }
     }
 
-    // List files
-    let files = storage::list_files(workspace_path).map_err(|e| e.to_string())?;
-    let file_count = storage::count_files(&files);
+    is_git_repo
+}
 
-    // Get workspace name from path
-    let name = workspace_path
+/// Display name of a workspace: its folder name
+pub fn workspace_name(workspace_path: &Path) -> String {
+    workspace_path
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("Workspace")
-        .to_string();
+        .to_string()
+}
 
-    // Save to recent workspaces
-    let workspace = Workspace {
-        path: path.clone(),
-        name: name.clone(),
-        last_opened: Utc::now(),
-    };
+/// The open workspaces, by name
+#[tauri::command]
+pub async fn list_open_workspaces(
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<OpenWorkspace>, String> {
+    let app_state = state.read().await;
+    let mut workspaces: Vec<OpenWorkspace> = app_state
+        .workspaces
+        .values()
+        .map(|w| OpenWorkspace {
+            id: w.id.clone(),
+            path: w.path.clone(),
+            name: workspace_name(Path::new(&w.path)),
+            active: app_state.active_workspace.as_deref() == Some(w.id.as_str()),
+            trusted: storage::is_workspace_trusted(&w.root),
+        })
+        .collect();
+    workspaces.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    Ok(workspaces)
+}
 
-    if let Err(e) = storage::save_recent_workspace(&workspace) {
-        tracing::warn!("Failed to save recent workspace: {}", e);
+/// Close an open workspace: its watcher and scheduler stop and its tracked sessions
+/// are recorded. Closing the active workspace leaves none active until another is
+/// opened.
+#[tauri::command]
+pub async fn close_workspace(
+    app_handle: tauri::AppHandle,
+    workspace_id: String,
+    state: State<'_, SharedAppState>,
+    tracker_state: State<'_, TrackerManagerState>,
+) -> Result<(), String> {
+    let workspace = storage::WorkspacePath::check_workspace(&state, &workspace_id).await?;
+    let id = workspace.id().to_string();
+
+    if let Some(watcher) = app_handle.try_state::<ChronicleWatcher>() {
+        watcher.stop(&id);
+    }
+    if let Some(scheduler) = app_handle.try_state::<Scheduler>() {
+        scheduler.stop(&id);
+    }
+    stop_workspace_tracking(&tracker_state, &workspace);
+    if let Some(cache) = app_handle.try_state::<DirectoryCache>() {
+        cache.clear_under(workspace.opened_root());
     }
 
-    Ok(WorkspaceInfo {
-        path,
-        name,
-        is_git_repo,
-        file_count,
-    })
+    let mut app_state = state.write().await;
+    app_state.workspaces.remove(&id);
+    if app_state.active_workspace.as_deref() == Some(id.as_str()) {
+        app_state.active_workspace = None;
+    }
+    tracing::info!("Closed workspace");
+    Ok(())
 }
 
 /// Create .mcp.json in the workspace for Claude Code auto-discovery
@@ -141,7 +409,8 @@ fn create_mcp_config(app_handle: &tauri::AppHandle, workspace_path: &Path) -> Re
     let config_str =
         serde_json::to_string_pretty(&mcp_config).map_err(|e| format!("JSON error: {}", e))?;
 
-    std::fs::write(&mcp_path, config_str).map_err(|e| format!("Failed to write .mcp.json: {}", e))?;
+    crate::storage::write_file_atomic(&mcp_path, &config_str)
+        .map_err(|e| format!("Failed to write .mcp.json: {}", e))?;
 
     // Set restrictive file permissions on Unix (0o600 = owner read/write only)
     #[cfg(unix)]
@@ -191,7 +460,7 @@ fn create_claude_settings(workspace_path: &Path) -> Result<(), String> {
     let settings_str =
         serde_json::to_string_pretty(&settings).map_err(|e| format!("JSON error: {}", e))?;
 
-    std::fs::write(&settings_path, settings_str)
+    crate::storage::write_file_atomic(&settings_path, &settings_str)
         .map_err(|e| format!("Failed to write .claude/settings.json: {}", e))?;
 
     tracing::info!("Created .claude/settings.json at {:?}", settings_path);
@@ -199,14 +468,172 @@ fn create_claude_settings(workspace_path: &Path) -> Result<(), String> {
     Ok(())
 }
 
+/// List a folder of an open workspace `depth` levels deep (1 by default), for the
+/// file tree to load as folders are expanded
+#[tauri::command]
+pub async fn list_directory(
+    path: String,
+    depth: Option<usize>,
+    sort: Option<SortMode>,
+    state: State<'_, SharedAppState>,
+    cache: State<'_, DirectoryCache>,
+) -> Result<Vec<FileNode>, String> {
+    let dir = storage::WorkspacePath::resolve(&state, &path).await?;
+    let files = WorkspaceConfig::load(dir.root()).files;
+    storage::list_directory(
+        &cache,
+        &dir.display_path(),
+        depth.unwrap_or(1).max(1),
+        sort.unwrap_or_default(),
+        &files,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Every note in an open workspace, workspace-relative and sorted
+#[tauri::command]
+pub async fn list_notes(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<String>, String> {
+    let workspace = storage::WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    Ok(changed_files(workspace.root(), AgentScope::Notes))
+}
+
+/// The whole file tree of an open workspace. Large workspaces should be listed
+/// with `list_directory` instead.
+#[tauri::command]
+pub async fn list_workspace_files(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<Vec<FileNode>, String> {
+    let workspace = storage::WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    storage::list_files(workspace.opened_root()).map_err(|e| e.to_string())
+}
+
+/// Whether the user trusts an open workspace to run agents and commands
 #[tauri::command]
-pub async fn list_workspace_files(workspace_path: String) -> Result<Vec<FileNode>, String> {
-    storage::list_files(Path::new(&workspace_path)).map_err(|e| e.to_string())
+pub async fn get_workspace_trust(
+    workspace_path: String,
+    state: State<'_, SharedAppState>,
+) -> Result<bool, String> {
+    let workspace = storage::WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    Ok(storage::is_workspace_trusted(workspace.root()))
+}
+
+/// Trust or distrust an open workspace. Trusting it auto-approves Chronicle's MCP
+/// tools and starts its scheduled jobs; distrusting it stops them.
+#[tauri::command]
+pub async fn set_workspace_trust(
+    app_handle: tauri::AppHandle,
+    workspace_path: String,
+    trusted: bool,
+    state: State<'_, SharedAppState>,
+) -> Result<(), String> {
+    let workspace = storage::WorkspacePath::check_workspace(&state, &workspace_path).await?;
+    storage::set_workspace_trust(workspace.root(), trusted)
+        .map_err(|e| format!("Failed to save workspace trust: {}", e))?;
+
+    if trusted {
+        if let Err(e) = create_claude_settings(workspace.opened_root()) {
+            tracing::warn!("Failed to create .claude/settings.json: {}", e);
+        }
+    }
+    if let Some(scheduler) = app_handle.try_state::<Scheduler>() {
+        if trusted {
+            scheduler.start(
+                workspace.id(),
+                &workspace.opened_root().display().to_string(),
+                app_handle.clone(),
+            )?;
+        } else {
+            scheduler.stop(workspace.id());
+        }
+    }
+    Ok(())
+}
+
+/// Recent workspaces as the welcome screen lists them, flagging folders that are gone
+fn recent_list(recent: RecentWorkspaces) -> Vec<RecentWorkspace> {
+    recent
+        .workspaces
+        .into_iter()
+        .map(|workspace| RecentWorkspace {
+            missing: !Path::new(&workspace.path).is_dir(),
+            workspace,
+        })
+        .collect()
 }
 
 #[tauri::command]
-pub async fn get_recent_workspaces() -> Result<Vec<Workspace>, String> {
+pub async fn get_recent_workspaces() -> Result<Vec<RecentWorkspace>, String> {
     storage::get_recent_workspaces()
-        .map(|r| r.workspaces)
+        .map(recent_list)
         .map_err(|e| e.to_string())
 }
+
+/// Pin a recent workspace so it stays listed, or unpin it
+#[tauri::command]
+pub async fn set_recent_workspace_pinned(
+    path: String,
+    pinned: bool,
+) -> Result<Vec<RecentWorkspace>, String> {
+    storage::update_recent_workspaces(|recent| recent.set_pinned(&path, pinned))
+        .map(recent_list)
+        .map_err(|e| format!("Failed to update recent workspaces: {}", e))
+}
+
+/// Take a workspace off the recent list. Its folder is left alone.
+#[tauri::command]
+pub async fn remove_recent_workspace(path: String) -> Result<Vec<RecentWorkspace>, String> {
+    storage::update_recent_workspaces(|recent| recent.remove(&path))
+        .map(recent_list)
+        .map_err(|e| format!("Failed to update recent workspaces: {}", e))
+}
+
+/// Give a recent workspace a display name; no name shows its folder name again
+#[tauri::command]
+pub async fn rename_recent_workspace(
+    path: String,
+    name: Option<String>,
+) -> Result<Vec<RecentWorkspace>, String> {
+    storage::update_recent_workspaces(|recent| recent.rename(&path, name.as_deref()))
+        .map(recent_list)
+        .map_err(|e| format!("Failed to update recent workspaces: {}", e))
+}
+
+/// Point a recent workspace at the folder it was moved to. Trust is not carried
+/// over: the new folder has to be trusted again before agents run there.
+#[tauri::command]
+pub async fn relocate_workspace(from: String, to: String) -> Result<Vec<RecentWorkspace>, String> {
+    let new_path = PathBuf::from(&to);
+    if !new_path.is_dir() {
+        return Err(format!("Workspace path does not exist: {}", to));
+    }
+    let name = workspace_name(&new_path);
+
+    storage::update_recent_workspaces(|recent| recent.relocate(&from, &to, &name))
+        .map(recent_list)
+        .map_err(|e| format!("Failed to relocate workspace: {}", e))
+}
+
+/// Note count and git details of a recent workspace, for its entry in the list
+#[tauri::command]
+pub async fn get_workspace_stats(path: String) -> Result<WorkspaceStats, String> {
+    let recent = storage::get_recent_workspaces().map_err(|e| e.to_string())?;
+    if !recent.workspaces.iter().any(|w| w.path == path) {
+        return Err(format!("Not a recent workspace: {}", path));
+    }
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err(format!("Workspace path does not exist: {}", path));
+    }
+
+    let files = WorkspaceConfig::load(&root).files;
+    Ok(WorkspaceStats {
+        note_count: storage::count_notes(&root, &files, |_, _| {}),
+        last_commit: git::last_commit(&root)
+            .map_err(|e| format!("Failed to read git history: {}", e))?,
+        remote: git::remote_url(&root).map_err(|e| format!("Failed to read git remotes: {}", e))?,
+    })
+}
@@ -1,4 +1,7 @@
+use chrono::{DateTime, Utc};
 use git2::{Repository, Signature, StatusOptions};
+use serde::Serialize;
+use std::collections::HashSet;
 use std::path::Path;
 use thiserror::Error;
 
@@ -17,6 +20,11 @@ pub enum GitError {
 const DEFAULT_GITIGNORE: &str = r#"# Chronicle app state (not content)
 .chronicle/state.json
 .chronicle/agent-runs.json
+.chronicle/agent-files.json
+.chronicle/schedule-runs.json
+.chronicle/snapshots/
+.chronicle/sessions/
+.chronicle/*.bak
 
 # MCP configuration (auto-generated, machine-specific paths)
 .mcp.json
@@ -47,7 +55,14 @@ pub fn is_git_repo(path: &Path) -> bool {
 pub fn init_or_open_repo(workspace_path: &Path) -> Result<Repository, GitError> {
     if is_git_repo(workspace_path) {
         tracing::info!("Opening existing git repository at {}", workspace_path.display());
-        Repository::open(workspace_path).map_err(GitError::Git)
+        let repo = Repository::open(workspace_path)?;
+
+        // Workspaces created before an app state file was ignored would commit it
+        if let Err(e) = ignore_app_state(&repo, workspace_path) {
+            tracing::warn!("Failed to update .gitignore: {}", e);
+        }
+
+        Ok(repo)
     } else {
         tracing::info!("Initializing new git repository at {}", workspace_path.display());
         let repo = Repository::init(workspace_path)?;
@@ -72,6 +87,69 @@ fn create_default_gitignore(workspace_path: &Path) -> Result<std::path::PathBuf,
     Ok(gitignore_path)
 }
 
+/// App state entries of the default .gitignore
+fn app_state_ignores() -> impl Iterator<Item = &'static str> {
+    DEFAULT_GITIGNORE
+        .lines()
+        .filter(|line| line.starts_with(".chronicle/"))
+}
+
+/// Whether a workspace-relative path matches an app state entry: a folder
+/// (`dir/`), a `*` pattern within one folder, or an exact path
+fn is_app_state(pattern: &str, path: &str) -> bool {
+    if let Some(dir) = pattern.strip_suffix('/') {
+        return path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'));
+    }
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => path
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+            .is_some_and(|middle| !middle.contains('/')),
+        None => path == pattern,
+    }
+}
+
+/// Add the app state entries missing from an existing workspace's .gitignore and
+/// stop tracking app state files that were already committed; they stay on disk
+fn ignore_app_state(repo: &Repository, workspace_path: &Path) -> Result<(), GitError> {
+    let gitignore_path = workspace_path.join(".gitignore");
+    let existing = std::fs::read_to_string(&gitignore_path).unwrap_or_default();
+    let listed: HashSet<&str> = existing.lines().map(str::trim).collect();
+    let missing: Vec<&str> = app_state_ignores()
+        .filter(|entry| !listed.contains(entry))
+        .collect();
+    if !missing.is_empty() {
+        let mut content = existing.clone();
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str("\n# Chronicle app state (not content)\n");
+        for entry in &missing {
+            content.push_str(entry);
+            content.push('\n');
+        }
+        crate::storage::write_file(&gitignore_path, &content)?;
+        tracing::info!("Added {} app state entries to .gitignore", missing.len());
+    }
+
+    let mut index = repo.index()?;
+    let tracked: Vec<String> = index
+        .iter()
+        .filter_map(|entry| String::from_utf8(entry.path).ok())
+        .filter(|path| app_state_ignores().any(|pattern| is_app_state(pattern, path)))
+        .collect();
+    if !tracked.is_empty() {
+        for path in &tracked {
+            index.remove_path(Path::new(path))?;
+        }
+        index.write()?;
+        tracing::info!("Stopped tracking {} app state files", tracked.len());
+    }
+    Ok(())
+}
+
 /// Create the initial commit with .gitignore
 fn create_initial_commit(repo: &Repository, gitignore_path: &Path) -> Result<(), GitError> {
     let mut index = repo.index()?;
@@ -99,6 +177,26 @@ fn create_initial_commit(repo: &Repository, gitignore_path: &Path) -> Result<(),
     Ok(())
 }
 
+/// Initialize a repository for a new workspace with the default .gitignore and
+/// commit everything in it as the first commit. Returns the short commit ID.
+pub fn init_repo_with_commit(workspace_path: &Path, message: &str) -> Result<String, GitError> {
+    let repo = Repository::init(workspace_path)?;
+    create_default_gitignore(workspace_path)?;
+
+    let mut index = repo.index()?;
+    index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+
+    let tree_id = index.write_tree()?;
+    let tree = repo.find_tree(tree_id)?;
+    let sig = Signature::now("Chronicle", "chronicle@localhost")?;
+    let commit_id = repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[])?;
+
+    let short_id = commit_id.to_string()[..7].to_string();
+    tracing::info!("Created initial commit {}: {}", short_id, message);
+    Ok(short_id)
+}
+
 /// Commit type for semantic commits
 #[derive(Debug, Clone, Copy)]
 #[allow(dead_code)] // Process, Annotate, Snapshot used in future milestones
@@ -210,6 +308,92 @@ pub fn commit_snapshot(workspace_path: &Path, title: &str) -> Result<String, Git
     Ok(short_id)
 }
 
+/// Full ID of the HEAD commit, or None when the workspace has no repository or commits
+pub fn head_commit_id(workspace_path: &Path) -> Result<Option<String>, GitError> {
+    if !is_git_repo(workspace_path) {
+        return Ok(None);
+    }
+
+    let repo = Repository::open(workspace_path)?;
+    let commit_id = match repo.head() {
+        Ok(head) => head.peel_to_commit()?.id().to_string(),
+        Err(_) => return Ok(None),
+    };
+    Ok(Some(commit_id))
+}
+
+/// The commit at HEAD, summarized for display
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitSummary {
+    /// Short commit ID
+    pub id: String,
+    /// First line of the message
+    pub summary: String,
+    pub committed_at: DateTime<Utc>,
+}
+
+/// The HEAD commit, or None when the workspace has no repository or commits
+pub fn last_commit(workspace_path: &Path) -> Result<Option<CommitSummary>, GitError> {
+    if !is_git_repo(workspace_path) {
+        return Ok(None);
+    }
+
+    let repo = Repository::open(workspace_path)?;
+    let commit = match repo.head() {
+        Ok(head) => head.peel_to_commit()?,
+        Err(_) => return Ok(None),
+    };
+    Ok(Some(CommitSummary {
+        id: commit.id().to_string()[..7].to_string(),
+        summary: commit.summary().unwrap_or_default().to_string(),
+        committed_at: DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default(),
+    }))
+}
+
+/// URL of the workspace's `origin` remote, or of its first remote
+pub fn remote_url(workspace_path: &Path) -> Result<Option<String>, GitError> {
+    if !is_git_repo(workspace_path) {
+        return Ok(None);
+    }
+
+    let repo = Repository::open(workspace_path)?;
+    let names = repo.remotes()?;
+    let name = names
+        .iter()
+        .flatten()
+        .find(|n| *n == "origin")
+        .or_else(|| names.iter().flatten().next());
+    let Some(name) = name else {
+        return Ok(None);
+    };
+    let remote = repo.find_remote(name)?;
+    let url = remote.url().map(|u| u.to_string());
+    Ok(url)
+}
+
+/// Read a workspace-relative file as it was at a commit.
+/// Returns None when the file isn't part of that commit.
+pub fn read_file_at_commit(
+    workspace_path: &Path,
+    commit_id: &str,
+    relative_path: &Path,
+) -> Result<Option<String>, GitError> {
+    if !is_git_repo(workspace_path) {
+        return Err(GitError::RepoNotFound(workspace_path.display().to_string()));
+    }
+
+    let repo = Repository::open(workspace_path)?;
+    let tree = repo.revparse_single(commit_id)?.peel_to_commit()?.tree()?;
+    let entry = match tree.get_path(relative_path) {
+        Ok(entry) => entry,
+        Err(_) => return Ok(None),
+    };
+    let blob = entry.to_object(&repo)?.peel_to_blob()?;
+
+    Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+}
+
 /// Check if there are uncommitted changes
 #[allow(dead_code)] // Will be used for dirty checking in future
 pub fn has_changes(workspace_path: &Path) -> Result<bool, GitError> {
@@ -260,6 +444,75 @@ pub fn get_uncommitted_files(workspace_path: &Path) -> Result<Vec<String>, GitEr
     Ok(files)
 }
 
+/// A `session:` commit: one editing session on one note
+#[derive(Debug, Clone)]
+pub struct SessionCommit {
+    /// Workspace-relative note path at the time of the commit
+    pub note_path: String,
+    pub duration_minutes: u32,
+    pub committed_at: DateTime<Utc>,
+}
+
+/// Duration of a `session: Title (45m)` commit
+fn session_duration(message: &str) -> Option<u32> {
+    let rest = message
+        .lines()
+        .next()?
+        .strip_prefix(CommitType::Session.prefix())?
+        .strip_prefix(": ")?;
+    let (_, detail) = rest.rsplit_once(" (")?;
+    detail.strip_suffix("m)")?.parse().ok()
+}
+
+/// Session commits in the workspace history, newest first
+pub fn session_commits(workspace_path: &Path) -> Result<Vec<SessionCommit>, GitError> {
+    if !is_git_repo(workspace_path) {
+        return Ok(vec![]);
+    }
+
+    let repo = Repository::open(workspace_path)?;
+    if repo.head().is_err() {
+        return Ok(vec![]);
+    }
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+
+    let mut sessions = Vec::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let Some(duration_minutes) = commit.message().and_then(session_duration) else {
+            continue;
+        };
+
+        // The note is the markdown file the commit changed
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+        let note_path = diff.deltas().find_map(|delta| {
+            let path = delta.new_file().path()?;
+            let is_note = path.extension().and_then(|e| e.to_str()) == Some("md")
+                && !path.starts_with(".chronicle");
+            is_note.then(|| path.to_string_lossy().replace('\\', "/"))
+        });
+        let Some(note_path) = note_path else {
+            continue;
+        };
+
+        let Some(committed_at) = DateTime::from_timestamp(commit.time().seconds(), 0) else {
+            continue;
+        };
+        sessions.push(SessionCommit {
+            note_path,
+            duration_minutes,
+            committed_at,
+        });
+    }
+    Ok(sessions)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,6 +543,41 @@ mod tests {
         assert!(repo.head().is_ok());
     }
 
+    #[test]
+    fn test_open_ignores_app_state_in_old_workspaces() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let repo = Repository::init(ws).unwrap();
+        std::fs::write(ws.join(".gitignore"), ".chronicle/state.json\n.mcp.json").unwrap();
+        std::fs::create_dir_all(ws.join(".chronicle/sessions")).unwrap();
+        std::fs::write(ws.join(".chronicle/sessions/run.json"), "{}").unwrap();
+        std::fs::write(ws.join(".chronicle/tags.json"), "{}").unwrap();
+        std::fs::write(ws.join(".chronicle/tags.json.bak"), "{}").unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), git2::IndexAddOption::FORCE, None)
+            .unwrap();
+        index.write().unwrap();
+
+        init_or_open_repo(ws).unwrap();
+        init_or_open_repo(ws).unwrap();
+
+        let gitignore = std::fs::read_to_string(ws.join(".gitignore")).unwrap();
+        assert!(gitignore.starts_with(".chronicle/state.json\n.mcp.json\n"));
+        assert_eq!(gitignore.matches(".chronicle/sessions/").count(), 1);
+        assert_eq!(gitignore.matches(".chronicle/state.json").count(), 1);
+
+        let index = Repository::open(ws).unwrap().index().unwrap();
+        let tracked: Vec<String> = index
+            .iter()
+            .map(|e| String::from_utf8(e.path).unwrap())
+            .collect();
+        assert!(tracked.contains(&".chronicle/tags.json".to_string()));
+        assert!(!tracked.contains(&".chronicle/sessions/run.json".to_string()));
+        assert!(!tracked.contains(&".chronicle/tags.json.bak".to_string()));
+        assert!(ws.join(".chronicle/sessions/run.json").exists());
+    }
+
     #[test]
     fn test_commit_files() {
         let dir = tempdir().unwrap();
@@ -311,4 +599,95 @@ mod tests {
 
         assert_eq!(commit_id.len(), 7);
     }
+
+    #[test]
+    fn test_init_repo_with_commit() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".chronicle")).unwrap();
+        std::fs::write(dir.path().join(".chronicle/context.md"), "# Context").unwrap();
+        std::fs::write(dir.path().join(".chronicle/state.json"), "{}").unwrap();
+
+        init_repo_with_commit(dir.path(), "Initial commit: Research workspace").unwrap();
+        let last = last_commit(dir.path()).unwrap().unwrap();
+        assert_eq!(last.summary, "Initial commit: Research workspace");
+
+        let commit = head_commit_id(dir.path()).unwrap().unwrap();
+        let read = |path: &str| read_file_at_commit(dir.path(), &commit, Path::new(path)).unwrap();
+        assert!(read(".gitignore").is_some());
+        assert_eq!(read(".chronicle/context.md").as_deref(), Some("# Context"));
+        // App state stays out of history
+        assert!(read(".chronicle/state.json").is_none());
+        assert!(!has_changes(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn test_read_file_at_commit() {
+        let dir = tempdir().unwrap();
+        init_or_open_repo(dir.path()).unwrap();
+
+        let note_path = dir.path().join("test-note.md");
+        std::fs::write(&note_path, "first").unwrap();
+        commit_files(
+            dir.path(),
+            &[Path::new("test-note.md")],
+            CommitType::Session,
+            "Test Note",
+            "5m",
+        )
+        .unwrap();
+        let commit = head_commit_id(dir.path()).unwrap().unwrap();
+
+        std::fs::write(&note_path, "second").unwrap();
+        let content = read_file_at_commit(dir.path(), &commit, Path::new("test-note.md")).unwrap();
+        assert_eq!(content.as_deref(), Some("first"));
+
+        let missing = read_file_at_commit(dir.path(), &commit, Path::new("missing.md")).unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn test_session_commits() {
+        let dir = tempdir().unwrap();
+        init_or_open_repo(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("standup.md"), "# Standup").unwrap();
+        commit_files(
+            dir.path(),
+            &[Path::new("standup.md")],
+            CommitType::Session,
+            "Standup (team)",
+            "45m",
+        )
+        .unwrap();
+        commit_snapshot(dir.path(), "Manual").unwrap();
+
+        let sessions = session_commits(dir.path()).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].note_path, "standup.md");
+        assert_eq!(sessions[0].duration_minutes, 45);
+    }
+
+    #[test]
+    fn test_last_commit_and_remote() {
+        let dir = tempdir().unwrap();
+        assert!(last_commit(dir.path()).unwrap().is_none());
+
+        let repo = init_or_open_repo(dir.path()).unwrap();
+        assert_eq!(remote_url(dir.path()).unwrap(), None);
+        repo.remote("backup", "https://example.com/backup.git").unwrap();
+        assert_eq!(
+            remote_url(dir.path()).unwrap().as_deref(),
+            Some("https://example.com/backup.git")
+        );
+        repo.remote("origin", "git@example.com:me/notes.git").unwrap();
+        assert_eq!(
+            remote_url(dir.path()).unwrap().as_deref(),
+            Some("git@example.com:me/notes.git")
+        );
+
+        commit_snapshot(dir.path(), "Manual").unwrap();
+        let last = last_commit(dir.path()).unwrap().unwrap();
+        assert_eq!(last.id.len(), 7);
+        assert!(last.summary.contains("Manual"));
+    }
 }
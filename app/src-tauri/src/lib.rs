@@ -1,19 +1,110 @@
 mod commands;
 mod git;
 mod models;
+mod notes;
+mod pipeline;
+mod presets;
+mod scheduler;
 mod session;
 mod storage;
+mod tasks;
 mod watcher;
 
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-/// Shared application state for tracking current file and workspace
-#[derive(Default)]
-pub struct AppState {
+/// A workspace open in the app. Several can be open at once; each one's watcher,
+/// scheduler, tracker and auto-process queue are keyed by its `id`.
+#[derive(Debug, Clone)]
+pub struct WorkspaceSession {
+    /// Stable ID derived from the canonical root, so reopening a folder finds the
+    /// same session
+    pub id: String,
+    /// Workspace root as it was opened
+    pub path: String,
+    /// Canonical workspace root
+    pub root: PathBuf,
     pub current_file_path: Option<String>,
     pub current_file_content: Option<String>,
-    pub workspace_path: Option<String>,
+    /// When a note was last written through `write_file`
+    pub last_edited_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl WorkspaceSession {
+    pub fn new(path: &str) -> std::io::Result<Self> {
+        let root = std::path::Path::new(path).canonicalize()?;
+        Ok(Self {
+            id: workspace_id(&root),
+            path: path.to_string(),
+            root,
+            current_file_path: None,
+            current_file_content: None,
+            last_edited_at: None,
+        })
+    }
+}
+
+/// ID of a workspace: a short hash of its canonical root
+pub fn workspace_id(root: &std::path::Path) -> String {
+    use sha2::{Digest, Sha256};
+    let hash = Sha256::digest(root.to_string_lossy().as_bytes());
+    hash.iter().take(6).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Shared application state for tracking open workspaces and their current files
+#[derive(Default)]
+pub struct AppState {
+    /// Open workspaces by ID
+    pub workspaces: HashMap<String, WorkspaceSession>,
+    /// The workspace shown in the window. Relative paths resolve against it.
+    pub active_workspace: Option<String>,
+    /// Files the user changed through the app (canonical paths), and when. Agent
+    /// runs don't roll these back.
+    pub user_writes: HashMap<PathBuf, chrono::DateTime<chrono::Utc>>,
+}
+
+impl AppState {
+    /// Remember that the user changed a file, forgetting changes older than a day
+    pub fn record_user_write(&mut self, path: PathBuf) {
+        let now = chrono::Utc::now();
+        self.user_writes
+            .retain(|_, at| now - *at < chrono::Duration::days(1));
+        self.user_writes.insert(path, now);
+    }
+
+    /// Add a workspace session unless it is already open, and make it active
+    pub fn activate(&mut self, session: WorkspaceSession) -> &mut WorkspaceSession {
+        let id = session.id.clone();
+        self.active_workspace = Some(id.clone());
+        self.workspaces.entry(id).or_insert(session)
+    }
+
+    pub fn active(&self) -> Option<&WorkspaceSession> {
+        self.workspaces.get(self.active_workspace.as_deref()?)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut WorkspaceSession> {
+        self.workspaces.get_mut(self.active_workspace.as_deref()?)
+    }
+
+    /// An open workspace named by ID or by its root path
+    pub fn find_workspace(&self, id_or_path: &str) -> Option<&WorkspaceSession> {
+        if let Some(session) = self.workspaces.get(id_or_path) {
+            return Some(session);
+        }
+        let root = std::path::Path::new(id_or_path).canonicalize().ok()?;
+        self.workspaces.values().find(|s| s.root == root)
+    }
+
+    /// The open workspace a canonical path is in (the innermost, if they nest)
+    pub fn workspace_containing(&self, path: &std::path::Path) -> Option<&WorkspaceSession> {
+        self.workspaces
+            .values()
+            .filter(|s| path.starts_with(&s.root))
+            .max_by_key(|s| s.root.components().count())
+    }
 }
 
 pub type SharedAppState = Arc<RwLock<AppState>>;
@@ -34,26 +125,62 @@ pub fn run() {
         .manage(commands::TrackerManagerState::new())
         .manage(app_state.clone())
         .manage(watcher::ChronicleWatcher::new())
+        .manage(scheduler::Scheduler::new())
+        .manage(tasks::TaskList::new())
+        .manage(pipeline::AutoProcessQueue::new())
+        .manage(pipeline::AgentSandbox::new())
+        .manage(storage::DirectoryCache::default())
         .invoke_handler(tauri::generate_handler![
             // Workspace commands
             commands::open_workspace,
+            commands::list_open_workspaces,
+            commands::close_workspace,
             commands::list_workspace_files,
+            commands::list_directory,
+            commands::list_notes,
             commands::get_recent_workspaces,
+            commands::set_recent_workspace_pinned,
+            commands::remove_recent_workspace,
+            commands::rename_recent_workspace,
+            commands::relocate_workspace,
+            commands::get_workspace_stats,
+            commands::create_workspace,
+            commands::list_workspace_presets,
+            commands::install_workspace_preset,
+            commands::export_workspace_preset,
+            commands::get_workspace_trust,
+            commands::set_workspace_trust,
             // File commands
             commands::read_file,
             commands::write_file,
             commands::file_exists,
             commands::suggest_rename,
             commands::rename_file,
+            commands::read_note,
+            commands::update_front_matter,
             commands::generate_note_path,
             commands::get_default_shell,
             commands::read_processed_file,
             commands::delete_file,
+            commands::import_attachment,
+            commands::import_attachment_data,
+            commands::open_attachment,
+            commands::list_orphaned_attachments,
             commands::create_folder,
             // Tracking commands (simplified)
             commands::get_tracker_info,
             commands::start_tracking,
             commands::stop_tracking,
+            commands::list_trackers,
+            commands::tracker_heartbeat,
+            commands::list_orphaned_sessions,
+            commands::recover_session,
+            commands::discard_orphaned_session,
+            commands::pause_tracking,
+            commands::resume_tracking,
+            commands::get_time_stats,
+            commands::get_time_report,
+            commands::export_time_report,
             commands::update_note_metadata,
             // Git commands
             commands::commit_session,
@@ -64,6 +191,7 @@ pub fn run() {
             commands::get_mcp_status,
             // Search commands
             commands::search_notes,
+            commands::search_all_workspaces,
             // Claude commands
             commands::run_claude_task,
             commands::process_note,
@@ -91,8 +219,16 @@ pub fn run() {
             // Digest commands
             commands::generate_digest,
             commands::list_digests,
+            // Settings commands
+            commands::get_config,
+            commands::update_config,
+            // Schedule commands
+            commands::get_schedule,
+            commands::run_scheduled_job,
+            commands::list_tasks,
             // Archive commands
             commands::list_processed_notes,
+            commands::reprocess_stale_notes,
             // Entity commands
             commands::read_entities,
             commands::list_all_entities,
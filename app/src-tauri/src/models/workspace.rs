@@ -7,15 +7,58 @@ pub struct Workspace {
     pub path: String,
     pub name: String,
     pub last_opened: DateTime<Utc>,
+    /// Pinned workspaces are listed first and never drop off the recent list
+    #[serde(default)]
+    pub pinned: bool,
+    /// Name the user gave the workspace in the recent list, shown instead of `name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+}
+
+/// An entry of the recent workspaces list, as shown to the user
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentWorkspace {
+    #[serde(flatten)]
+    pub workspace: Workspace,
+    /// The folder no longer exists, e.g. it was moved or deleted
+    pub missing: bool,
+}
+
+/// Details of a recent workspace, loaded on demand
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStats {
+    pub note_count: usize,
+    pub last_commit: Option<crate::git::CommitSummary>,
+    /// URL of the git remote notes are pushed to, if any
+    pub remote: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkspaceInfo {
+    /// ID of the open workspace, for commands that act on it
+    pub id: String,
     pub path: String,
     pub name: String,
     pub is_git_repo: bool,
-    pub file_count: usize,
+    /// Number of notes, once the background scan has counted them
+    pub file_count: Option<usize>,
+    /// Whether the user trusts the workspace to run agents and commands
+    pub trusted: bool,
+}
+
+/// A workspace open in the app, one tab of the workspace switcher
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenWorkspace {
+    pub id: String,
+    pub path: String,
+    pub name: String,
+    /// Whether it is the workspace shown in the window
+    pub active: bool,
+    pub trusted: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,13 +72,52 @@ pub struct FileNode {
     pub children: Option<Vec<FileNode>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub modified_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// What an attachment holds, for attachment nodes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachment_kind: Option<AttachmentKind>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum FileNodeType {
+    /// A markdown note
     File,
     Directory,
+    /// A non-markdown file notes can link to: an image, PDF, recording...
+    Attachment,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AttachmentKind {
+    Image,
+    Pdf,
+    Audio,
+    Video,
+    Document,
+}
+
+impl AttachmentKind {
+    /// Kind of an attachment by its extension, or None for files that aren't
+    /// attachments
+    pub fn of(path: &std::path::Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "bmp" | "heic" => {
+                Some(AttachmentKind::Image)
+            }
+            "pdf" => Some(AttachmentKind::Pdf),
+            "mp3" | "m4a" | "wav" | "ogg" | "flac" | "aac" => Some(AttachmentKind::Audio),
+            "mp4" | "mov" | "webm" | "mkv" => Some(AttachmentKind::Video),
+            "txt" | "csv" | "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "key"
+            | "pages" | "numbers" | "odt" | "ods" | "odp" | "rtf" | "zip" => {
+                Some(AttachmentKind::Document)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
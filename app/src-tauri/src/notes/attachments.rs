@@ -0,0 +1,169 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::Path;
+use walkdir::WalkDir;
+
+use super::{link_targets, parent_dir, relative_link};
+use crate::models::AttachmentKind;
+use crate::pipeline::{changed_files, AgentScope};
+use crate::storage::{self, StorageError};
+
+/// Folder, at the workspace root, that attachments are imported into
+pub const ATTACHMENTS_DIR: &str = "assets";
+
+/// An attachment copied into the workspace
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedAttachment {
+    /// Workspace-relative path
+    pub path: String,
+    pub kind: AttachmentKind,
+    /// Markdown that links to it from the note (embeds it, for images)
+    pub markdown: String,
+}
+
+/// Name an attachment is stored under: a hash of its content, so importing the
+/// same file twice stores it once
+pub fn attachment_name(content: &[u8], extension: &str) -> String {
+    let hash = Sha256::digest(content);
+    let hex: String = hash.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    format!("{}.{}", hex, extension.to_ascii_lowercase())
+}
+
+/// Copy an attachment into the assets folder and build the markdown linking it
+/// from a workspace-relative note. `file_name` is the attachment's original name:
+/// its extension decides the kind and it becomes the link text.
+pub fn import_attachment(
+    workspace_path: &Path,
+    note: &str,
+    file_name: &str,
+    content: &[u8],
+) -> Result<ImportedAttachment, StorageError> {
+    let original = Path::new(file_name);
+    let kind = AttachmentKind::of(original)
+        .ok_or_else(|| StorageError::InvalidPath(format!("Not an attachment: {}", file_name)))?;
+    let extension = original
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+
+    let relative = format!(
+        "{}/{}",
+        ATTACHMENTS_DIR,
+        attachment_name(content, extension)
+    );
+    let target = workspace_path.join(&relative);
+    if !target.exists() {
+        storage::ensure_dir(&workspace_path.join(ATTACHMENTS_DIR))?;
+        storage::write_bytes_atomic(&target, content)?;
+    }
+
+    let link = relative_link(parent_dir(note), &relative).replace(' ', "%20");
+    let text: String = match kind {
+        AttachmentKind::Image => original.file_stem().and_then(|s| s.to_str()),
+        _ => original.file_name().and_then(|s| s.to_str()),
+    }
+    .unwrap_or(ATTACHMENTS_DIR)
+    .chars()
+    .filter(|c| !matches!(c, '[' | ']'))
+    .collect();
+    let markdown = match kind {
+        AttachmentKind::Image => format!("![{}]({})", text, link),
+        _ => format!("[{}]({})", text, link),
+    };
+
+    Ok(ImportedAttachment {
+        path: relative,
+        kind,
+        markdown,
+    })
+}
+
+/// Attachments in the assets folder that no note links to, workspace-relative
+pub fn find_orphaned_attachments(workspace_path: &Path) -> Vec<String> {
+    let assets = workspace_path.join(ATTACHMENTS_DIR);
+    if !assets.is_dir() {
+        return Vec::new();
+    }
+
+    let mut linked = HashSet::new();
+    for note in changed_files(workspace_path, AgentScope::Notes) {
+        if let Ok(content) = std::fs::read_to_string(workspace_path.join(&note)) {
+            linked.extend(link_targets(&content, &note));
+        }
+    }
+
+    let mut orphans: Vec<String> = WalkDir::new(&assets)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && AttachmentKind::of(e.path()).is_some())
+        .filter_map(|e| {
+            let relative = e
+                .path()
+                .strip_prefix(workspace_path)
+                .ok()?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let name = e.file_name().to_string_lossy();
+            // Wiki embeds name attachments by file name alone
+            let used = linked.contains(&relative) || linked.contains(name.as_ref());
+            (!used).then_some(relative)
+        })
+        .collect();
+    orphans.sort();
+    orphans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_import_stores_by_content_hash() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+
+        let first = import_attachment(root, "team/standup.md", "Whiteboard.PNG", b"png").unwrap();
+        assert_eq!(first.kind, AttachmentKind::Image);
+        assert!(first.path.starts_with("assets/") && first.path.ends_with(".png"));
+        assert!(root.join(&first.path).is_file());
+        assert_eq!(first.markdown, format!("![Whiteboard](../{})", first.path));
+
+        // The same content under another name is stored once
+        let again = import_attachment(root, "standup.md", "copy.png", b"png").unwrap();
+        assert_eq!(again.path, first.path);
+        assert_eq!(again.markdown, format!("![copy]({})", first.path));
+
+        let pdf = import_attachment(root, "standup.md", "Q3 plan.pdf", b"pdf").unwrap();
+        assert_eq!(pdf.markdown, format!("[Q3 plan.pdf]({})", pdf.path));
+
+        assert!(import_attachment(root, "standup.md", "script.sh", b"#!").is_err());
+    }
+
+    #[test]
+    fn test_orphaned_attachments() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let linked = import_attachment(root, "team/standup.md", "a.png", b"a").unwrap();
+        let embedded = import_attachment(root, "ideas.md", "b.pdf", b"b").unwrap();
+        let unused = import_attachment(root, "ideas.md", "c.png", b"c").unwrap();
+
+        std::fs::create_dir_all(root.join("team")).unwrap();
+        std::fs::write(
+            root.join("team/standup.md"),
+            format!("# Standup\n\n{}\n", linked.markdown),
+        )
+        .unwrap();
+        let embedded_name = embedded.path.trim_start_matches("assets/");
+        std::fs::write(
+            root.join("ideas.md"),
+            format!("# Ideas\n\n![[{}]]\n", embedded_name),
+        )
+        .unwrap();
+
+        assert_eq!(find_orphaned_attachments(root), vec![unused.path]);
+    }
+}
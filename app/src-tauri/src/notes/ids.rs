@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::{rewrite_index_files, rewrite_references};
+use crate::pipeline::{changed_files, AgentScope};
+use crate::storage::{self, NoteMeta, StorageError};
+
+/// Registry of stable note IDs and their current paths, kept in .chronicle/
+pub const NOTE_IDS_FILE: &str = "note-ids.json";
+
+const REGISTRY_VERSION: u32 = 1;
+
+/// Note IDs mapped to workspace-relative paths
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NoteRegistry {
+    pub version: u32,
+    #[serde(default)]
+    pub notes: BTreeMap<String, String>,
+}
+
+impl Default for NoteRegistry {
+    fn default() -> Self {
+        Self {
+            version: REGISTRY_VERSION,
+            notes: BTreeMap::new(),
+        }
+    }
+}
+
+impl NoteRegistry {
+    pub fn path(workspace_path: &Path) -> PathBuf {
+        workspace_path.join(".chronicle").join(NOTE_IDS_FILE)
+    }
+
+    /// Load the registry, or an empty one when it is missing or unreadable
+    pub fn load(workspace_path: &Path) -> Self {
+        std::fs::read_to_string(Self::path(workspace_path))
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, workspace_path: &Path) -> Result<(), StorageError> {
+        let content = serde_json::to_string_pretty(self)?;
+        storage::write_file_atomic(&Self::path(workspace_path), &content)
+    }
+
+    /// Current path of a note
+    pub fn path_of(&self, id: &str) -> Option<&str> {
+        self.notes.get(id).map(|p| p.as_str())
+    }
+
+    /// ID of the note at a path
+    pub fn id_of(&self, path: &str) -> Option<&str> {
+        self.notes
+            .iter()
+            .find(|(_, p)| p.as_str() == path)
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Point an ID at a path. Returns whether the registry changed.
+    pub fn set(&mut self, id: &str, path: &str) -> bool {
+        if self.path_of(id) == Some(path) {
+            return false;
+        }
+        // A path belongs to one note at a time
+        self.notes.retain(|_, p| p != path);
+        self.notes.insert(id.to_string(), path.to_string());
+        true
+    }
+}
+
+/// ID a note declares in its own front matter
+fn front_matter_id(note: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(note).ok()?;
+    let (front_matter, _) = storage::parse_front_matter(&content).ok()?;
+    front_matter.id.filter(|id| storage::is_note_id(id))
+}
+
+/// Give a note a new ID in its front matter
+fn replace_front_matter_id(note: &Path, id: &str) -> Result<(), StorageError> {
+    let content = storage::read_file(note)?;
+    let (mut front_matter, _) = storage::parse_front_matter(&content)?;
+    front_matter.id = Some(id.to_string());
+    storage::write_file_atomic(note, &storage::set_front_matter(&content, &front_matter)?)
+}
+
+/// Load a note's metadata, creating it or upgrading a legacy ID as needed.
+/// An ID in the note's front matter wins, so it survives renames outside the app,
+/// unless `taken` says another note has it: then the note is a copy and gets an
+/// ID of its own, written back to its front matter.
+/// Returns the metadata and whether it was saved.
+fn note_meta_with_id(
+    workspace_path: &Path,
+    note: &Path,
+    taken: impl Fn(&str) -> bool,
+) -> Result<(NoteMeta, bool), StorageError> {
+    let (mut meta, mut changed) = match storage::load_metadata(workspace_path, note)? {
+        Some(meta) => (meta, false),
+        None => (NoteMeta::new(note), true),
+    };
+    match front_matter_id(note) {
+        Some(id) if taken(&id) => {
+            if meta.id == id || taken(&meta.id) || !storage::is_note_id(&meta.id) {
+                meta.id = storage::new_note_id();
+            }
+            replace_front_matter_id(note, &meta.id)?;
+            changed = true;
+        }
+        Some(id) if id != meta.id => {
+            meta.id = id;
+            changed = true;
+        }
+        Some(_) => {}
+        None => changed |= meta.upgrade_id(),
+    }
+    if changed {
+        storage::save_metadata(workspace_path, note, &meta)?;
+    }
+    Ok((meta, changed))
+}
+
+/// Whether `registry` gives `id` to a note other than `relative` that still exists
+fn held_by_other(workspace_path: &Path, registry: &NoteRegistry, id: &str, relative: &str) -> bool {
+    registry
+        .path_of(id)
+        .is_some_and(|path| path != relative && workspace_path.join(path).is_file())
+}
+
+/// Stable ID of a workspace-relative note, assigning and registering one if needed
+pub fn ensure_note_id(workspace_path: &Path, relative: &str) -> Result<String, StorageError> {
+    let mut registry = NoteRegistry::load(workspace_path);
+    let (meta, _) = note_meta_with_id(workspace_path, &workspace_path.join(relative), |id| {
+        held_by_other(workspace_path, &registry, id, relative)
+    })?;
+
+    if registry.set(&meta.id, relative) {
+        registry.save(workspace_path)?;
+    }
+    Ok(meta.id)
+}
+
+/// Give every note in the workspace a stable ID and rebuild the registry from the
+/// notes on disk. Notes that no longer exist drop out of the registry, and a copy
+/// sharing another note's front-matter ID gets its own.
+pub fn sync_note_ids(workspace_path: &Path) -> Result<NoteRegistry, StorageError> {
+    let previous = NoteRegistry::load(workspace_path);
+    let mut registry = NoteRegistry::default();
+    let mut assigned = 0;
+
+    for relative in changed_files(workspace_path, AgentScope::Notes) {
+        let note = workspace_path.join(&relative);
+        let taken = |id: &str| {
+            registry.path_of(id).is_some()
+                || held_by_other(workspace_path, &previous, id, &relative)
+        };
+        match note_meta_with_id(workspace_path, &note, taken) {
+            Ok((meta, saved)) => {
+                if saved {
+                    assigned += 1;
+                }
+                registry.set(&meta.id, &relative);
+            }
+            Err(e) => tracing::warn!("Skipping note ID for {}: {}", relative, e),
+        }
+    }
+
+    if registry != previous {
+        registry.save(workspace_path)?;
+    }
+    if assigned > 0 {
+        tracing::info!("Assigned stable IDs to {} notes", assigned);
+    }
+    Ok(registry)
+}
+
+/// Replace note paths in the index files with note IDs. Covers indexes written
+/// before stable IDs and agents that wrote paths anyway.
+/// Returns the number of index files changed.
+pub fn migrate_index_references(
+    workspace_path: &Path,
+    registry: &NoteRegistry,
+) -> Result<usize, StorageError> {
+    let mut map: HashMap<String, Option<String>> = HashMap::new();
+    for (id, path) in &registry.notes {
+        map.insert(path.clone(), Some(id.clone()));
+        map.insert(
+            workspace_path.join(path).display().to_string(),
+            Some(id.clone()),
+        );
+    }
+
+    let changed = rewrite_index_files(workspace_path, &map, |path, content| {
+        storage::write_file_atomic(path, content)
+    })?;
+    if changed > 0 {
+        tracing::info!("Converted note paths to IDs in {} index files", changed);
+    }
+    Ok(changed)
+}
+
+/// Replace note IDs in an index value with the notes' current paths.
+/// IDs of notes that no longer exist are left as they are.
+pub fn resolve_note_ids(value: &mut Value, registry: &NoteRegistry) {
+    let map: HashMap<String, Option<String>> = registry
+        .notes
+        .iter()
+        .map(|(id, path)| (id.clone(), Some(path.clone())))
+        .collect();
+    rewrite_references(value, &map);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_sync_assigns_and_upgrades_ids() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join("projects")).unwrap();
+        std::fs::write(ws.join("standup.md"), "# Standup").unwrap();
+        std::fs::write(ws.join("projects/review.md"), "# Review").unwrap();
+
+        // Metadata from before stable IDs
+        let review = ws.join("projects/review.md");
+        let mut legacy = NoteMeta::new(&review);
+        legacy.id = "review-20260101120000".to_string();
+        storage::save_metadata(ws, &review, &legacy).unwrap();
+
+        let registry = sync_note_ids(ws).unwrap();
+        assert_eq!(registry.notes.len(), 2);
+        let id = registry.id_of("projects/review.md").unwrap().to_string();
+        assert!(storage::is_note_id(&id));
+        assert_eq!(registry.path_of(&id), Some("projects/review.md"));
+
+        // IDs are stable across syncs
+        let again = sync_note_ids(ws).unwrap();
+        assert_eq!(again, registry);
+        assert_eq!(ensure_note_id(ws, "projects/review.md").unwrap(), id);
+    }
+
+    #[test]
+    fn test_front_matter_id_wins() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let id = storage::new_note_id();
+        std::fs::write(
+            ws.join("renamed.md"),
+            format!("---\nid: {}\n---\n# Renamed elsewhere", id),
+        )
+        .unwrap();
+
+        assert_eq!(ensure_note_id(ws, "renamed.md").unwrap(), id);
+        assert_eq!(NoteRegistry::load(ws).path_of(&id), Some("renamed.md"));
+    }
+
+    #[test]
+    fn test_copied_note_gets_its_own_id() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join(".chronicle")).unwrap();
+        let id = storage::new_note_id();
+        let content = format!("---\nid: {}\n---\n# Standup", id);
+        std::fs::write(ws.join("standup.md"), &content).unwrap();
+        assert_eq!(ensure_note_id(ws, "standup.md").unwrap(), id);
+        let tags = json!({ "byNote": { &id: ["topic:sync"] }, "byTag": { "topic:sync": [&id] } });
+        std::fs::write(ws.join(".chronicle/tags.json"), tags.to_string()).unwrap();
+
+        // Duplicated outside the app, front matter and all
+        std::fs::write(ws.join("standup copy.md"), &content).unwrap();
+        let copy_id = ensure_note_id(ws, "standup copy.md").unwrap();
+        assert_ne!(copy_id, id);
+        assert_eq!(
+            front_matter_id(&ws.join("standup copy.md")),
+            Some(copy_id.clone())
+        );
+        let registry = NoteRegistry::load(ws);
+        assert_eq!(registry.path_of(&id), Some("standup.md"));
+        assert_eq!(registry.path_of(&copy_id), Some("standup copy.md"));
+
+        // Deleting the copy leaves the original's index entries alone
+        std::fs::remove_file(ws.join("standup copy.md")).unwrap();
+        crate::notes::record_external_moves(ws, &[("standup copy.md".to_string(), None)]).unwrap();
+        let stored: Value = serde_json::from_str(
+            &std::fs::read_to_string(ws.join(".chronicle/tags.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stored, tags);
+        assert_eq!(NoteRegistry::load(ws).path_of(&id), Some("standup.md"));
+    }
+
+    #[test]
+    fn test_sync_gives_copies_their_own_ids() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let id = storage::new_note_id();
+        let content = format!("---\nid: {}\n---\n# Standup", id);
+        std::fs::write(ws.join("b.md"), &content).unwrap();
+        ensure_note_id(ws, "b.md").unwrap();
+        // Copied while the app was closed, to a name that is walked first
+        std::fs::write(ws.join("a.md"), &content).unwrap();
+
+        let registry = sync_note_ids(ws).unwrap();
+        assert_eq!(registry.path_of(&id), Some("b.md"));
+        let copy_id = registry.id_of("a.md").unwrap();
+        assert_ne!(copy_id, id);
+    }
+
+    #[test]
+    fn test_indexes_store_ids_and_resolve_paths() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join(".chronicle")).unwrap();
+        std::fs::write(ws.join("standup.md"), "# Standup").unwrap();
+
+        let tags = json!({
+            "categories": { "topic": { "label": "Topics" } },
+            "byNote": { "standup.md": ["topic:sync"] },
+            "byTag": { "topic:sync": ["standup.md"] }
+        });
+        std::fs::write(ws.join(".chronicle/tags.json"), tags.to_string()).unwrap();
+
+        let registry = sync_note_ids(ws).unwrap();
+        let id = registry.id_of("standup.md").unwrap();
+        assert_eq!(migrate_index_references(ws, &registry).unwrap(), 1);
+
+        let stored: Value = serde_json::from_str(
+            &std::fs::read_to_string(ws.join(".chronicle/tags.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stored["byNote"][id], json!(["topic:sync"]));
+        assert_eq!(stored["byTag"]["topic:sync"], json!([id]));
+        // Category names are untouched
+        assert_eq!(stored["categories"]["topic"]["label"], "Topics");
+
+        let mut resolved = stored.clone();
+        resolve_note_ids(&mut resolved, &registry);
+        assert_eq!(resolved, tags);
+    }
+}
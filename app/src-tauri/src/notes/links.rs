@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+/// Folder of a workspace-relative path (`""` for the workspace root)
+pub fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+/// Resolve a relative link target against a folder, both workspace-relative.
+/// Returns None when the target escapes the workspace.
+pub fn resolve_relative(base_dir: &str, target: &str) -> Option<String> {
+    let mut parts: Vec<&str> = base_dir.split('/').filter(|p| !p.is_empty()).collect();
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Relative link from a folder to a workspace-relative path
+pub fn relative_link(from_dir: &str, target: &str) -> String {
+    let from: Vec<&str> = from_dir.split('/').filter(|p| !p.is_empty()).collect();
+    let to: Vec<&str> = target.split('/').filter(|p| !p.is_empty()).collect();
+
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    // The last component of `to` is the file itself, never a shared folder
+    let common = common.min(to.len().saturating_sub(1));
+
+    let mut parts: Vec<&str> = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+fn is_external(target: &str) -> bool {
+    target.is_empty()
+        || target.starts_with('#')
+        || target.starts_with('/')
+        || target.contains("://")
+        || target.starts_with("mailto:")
+}
+
+fn strip_md(path: &str) -> &str {
+    path.strip_suffix(".md").unwrap_or(path)
+}
+
+fn file_stem(path: &str) -> &str {
+    strip_md(path.rsplit('/').next().unwrap_or(path))
+}
+
+/// Rewrite the target of one markdown link, or None when it doesn't change
+fn rewrite_markdown_target(
+    raw: &str,
+    note_old: &str,
+    note_new: &str,
+    moves: &HashMap<String, String>,
+) -> Option<String> {
+    let (wrapped, inner) = match raw.strip_prefix('<').and_then(|r| r.strip_suffix('>')) {
+        Some(inner) => (true, inner),
+        None => (false, raw),
+    };
+    let (path, fragment) = match inner.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (inner, None),
+    };
+    if is_external(path) {
+        return None;
+    }
+
+    let decoded = path.replace("%20", " ");
+    let resolved = resolve_relative(parent_dir(note_old), &decoded)?;
+    let target = moves.get(&resolved).unwrap_or(&resolved);
+    let link = relative_link(parent_dir(note_new), target);
+    if link == decoded {
+        return None;
+    }
+
+    let link = if wrapped {
+        format!("<{}", link)
+    } else {
+        link.replace(' ', "%20")
+    };
+    let mut rewritten = link;
+    if let Some(fragment) = fragment {
+        rewritten.push('#');
+        rewritten.push_str(fragment);
+    }
+    if wrapped {
+        rewritten.push('>');
+    }
+    Some(rewritten)
+}
+
+/// Rewrite the target of one `[[wiki link]]`, or None when it doesn't change
+fn rewrite_wiki_target(target: &str, moves: &HashMap<String, String>) -> Option<String> {
+    let bare = strip_md(target);
+    let suffix = if bare.len() < target.len() { ".md" } else { "" };
+
+    for (old, new) in moves {
+        if !old.ends_with(".md") || !new.ends_with(".md") {
+            continue;
+        }
+        if bare.contains('/') {
+            if bare == strip_md(old) && strip_md(old) != strip_md(new) {
+                return Some(format!("{}{}", strip_md(new), suffix));
+            }
+        } else if bare == file_stem(old) && file_stem(old) != file_stem(new) {
+            return Some(format!("{}{}", file_stem(new), suffix));
+        }
+    }
+    None
+}
+
+/// Rewrite links in a note after notes moved. `note_old` and `note_new` are the
+/// note's own location before and after the move (the same when it didn't move);
+/// `moves` maps old to new workspace-relative paths. Relative markdown links are
+/// re-pointed from the note's new folder; `[[wiki links]]` follow renamed notes.
+/// Returns None when nothing changed.
+pub fn rewrite_links(
+    content: &str,
+    note_old: &str,
+    note_new: &str,
+    moves: &HashMap<String, String>,
+) -> Option<String> {
+    let mut output = String::with_capacity(content.len());
+    let mut changed = false;
+    let mut rest = content;
+
+    loop {
+        let markdown = rest.find("](");
+        let wiki = rest.find("[[");
+        let (start, is_wiki) = match (markdown, wiki) {
+            (Some(m), Some(w)) if w < m => (w, true),
+            (Some(m), _) => (m, false),
+            (None, Some(w)) => (w, true),
+            (None, None) => break,
+        };
+
+        let open = start + 2;
+        let close = if is_wiki {
+            rest[open..].find("]]")
+        } else {
+            rest[open..].find(')')
+        };
+        let Some(close) = close.map(|c| open + c) else {
+            break;
+        };
+
+        output.push_str(&rest[..open]);
+        let inner = &rest[open..close];
+
+        let replacement = if is_wiki {
+            let split = inner.find(['|', '#']).unwrap_or(inner.len());
+            rewrite_wiki_target(&inner[..split], moves).map(|t| format!("{}{}", t, &inner[split..]))
+        } else {
+            // Keep an optional link title: [text](target "title")
+            let split = inner.find(' ').filter(|_| !inner.starts_with('<'));
+            let split = split.unwrap_or(inner.len());
+            rewrite_markdown_target(&inner[..split], note_old, note_new, moves)
+                .map(|t| format!("{}{}", t, &inner[split..]))
+        };
+
+        match replacement {
+            Some(replacement) => {
+                output.push_str(&replacement);
+                changed = true;
+            }
+            None => output.push_str(inner),
+        }
+        rest = &rest[close..];
+    }
+
+    if !changed {
+        return None;
+    }
+    output.push_str(rest);
+    Some(output)
+}
+
+/// Everything a note links to: the workspace-relative targets of its relative
+/// markdown links, and the targets of its `[[wiki links]]` as written
+pub fn link_targets(content: &str, note: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find(['(', '[']) {
+        let (open, close) = match &rest[start..] {
+            r if r.starts_with("[[") => (start + 2, "]]"),
+            _ if start > 0 && rest[..start].ends_with(']') && rest[start..].starts_with('(') => {
+                (start + 1, ")")
+            }
+            _ => {
+                rest = &rest[start + 1..];
+                continue;
+            }
+        };
+        let Some(end) = rest[open..].find(close).map(|c| open + c) else {
+            break;
+        };
+        let inner = &rest[open..end];
+
+        if close == "]]" {
+            let split = inner.find(['|', '#']).unwrap_or(inner.len());
+            targets.push(inner[..split].trim().to_string());
+        } else {
+            let inner = inner.trim();
+            let raw = match inner.strip_prefix('<') {
+                Some(wrapped) => wrapped.split('>').next().unwrap_or(wrapped),
+                None => inner.split(' ').next().unwrap_or(inner),
+            };
+            let path = raw.split('#').next().unwrap_or(raw);
+            if !is_external(path) {
+                let decoded = path.replace("%20", " ");
+                if let Some(resolved) = resolve_relative(parent_dir(note), &decoded) {
+                    targets.push(resolved);
+                }
+            }
+        }
+        rest = &rest[end..];
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        assert_eq!(
+            resolve_relative("projects", "../standup.md").as_deref(),
+            Some("standup.md")
+        );
+        assert_eq!(resolve_relative("", "a/./b.md").as_deref(), Some("a/b.md"));
+        assert!(resolve_relative("", "../outside.md").is_none());
+
+        assert_eq!(relative_link("projects", "projects/a.md"), "a.md");
+        assert_eq!(relative_link("projects", "team/a.md"), "../team/a.md");
+        assert_eq!(relative_link("", "team/a.md"), "team/a.md");
+        assert_eq!(relative_link("a/b", "a/b.md"), "../b.md");
+    }
+
+    #[test]
+    fn test_links_to_moved_note_are_rewritten() {
+        let m = moves(&[("standup.md", "team/daily standup.md")]);
+        let content = "See [standup](standup.md#notes \"Daily\") and [[standup|the standup]].\n\
+                       Also [web](https://example.com) and [other](other.md).";
+
+        let rewritten = rewrite_links(content, "review.md", "review.md", &m).unwrap();
+        assert_eq!(
+            rewritten,
+            "See [standup](team/daily%20standup.md#notes \"Daily\") and [[daily standup|the standup]].\n\
+             Also [web](https://example.com) and [other](other.md)."
+        );
+    }
+
+    #[test]
+    fn test_moved_note_keeps_its_own_links_working() {
+        let m = moves(&[("review.md", "archive/review.md")]);
+        let content = "Follow-up of [standup](standup.md)";
+
+        let rewritten = rewrite_links(content, "review.md", "archive/review.md", &m).unwrap();
+        assert_eq!(rewritten, "Follow-up of [standup](../standup.md)");
+
+        // Unrelated note is left alone
+        assert!(rewrite_links(content, "other.md", "other.md", &m).is_none());
+    }
+}
@@ -0,0 +1,11 @@
+mod attachments;
+mod ids;
+mod links;
+mod references;
+mod relocate;
+
+pub use attachments::*;
+pub use ids::*;
+pub use links::*;
+pub use references::*;
+pub use relocate::*;
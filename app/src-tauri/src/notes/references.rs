@@ -0,0 +1,88 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::storage::StorageError;
+
+/// Index files in .chronicle/ that reference notes
+pub const NOTE_INDEX_FILES: &[&str] = &["tags.json", "actions.json", "links.json"];
+
+/// Apply `rewrite_references` to every index file, handing changed files to `write`.
+/// Unreadable indexes are skipped. Returns the number of files changed.
+pub fn rewrite_index_files<F>(
+    workspace_path: &Path,
+    map: &HashMap<String, Option<String>>,
+    mut write: F,
+) -> Result<usize, StorageError>
+where
+    F: FnMut(&Path, &str) -> Result<(), StorageError>,
+{
+    let mut changed = 0;
+    for file in NOTE_INDEX_FILES {
+        let path = workspace_path.join(".chronicle").join(file);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(mut json) = serde_json::from_str::<Value>(&content) else {
+            tracing::warn!("Skipping unreadable index {}", path.display());
+            continue;
+        };
+        if rewrite_references(&mut json, map) {
+            write(&path, &serde_json::to_string_pretty(&json)?)?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Apply path changes throughout a JSON value: object keys and string values equal
+/// to an old path are renamed. For deleted paths, keys are dropped, and array
+/// entries that are the path (or objects with a field equal to it) are removed.
+/// Returns whether anything changed.
+pub fn rewrite_references(value: &mut Value, map: &HashMap<String, Option<String>>) -> bool {
+    let is_deleted = |s: &str| matches!(map.get(s), Some(None));
+
+    match value {
+        Value::String(s) => match map.get(s.as_str()) {
+            Some(Some(new)) => {
+                *s = new.clone();
+                true
+            }
+            _ => false,
+        },
+        Value::Array(items) => {
+            let before = items.len();
+            items.retain(|item| match item {
+                Value::String(s) => !is_deleted(s),
+                Value::Object(fields) => {
+                    !fields.values().any(|v| v.as_str().is_some_and(is_deleted))
+                }
+                _ => true,
+            });
+            let mut changed = items.len() != before;
+            for item in items.iter_mut() {
+                changed |= rewrite_references(item, map);
+            }
+            changed
+        }
+        Value::Object(fields) => {
+            let mut changed = false;
+            let entries = std::mem::take(fields);
+            for (key, mut v) in entries {
+                changed |= rewrite_references(&mut v, map);
+                match map.get(&key) {
+                    Some(Some(new)) => {
+                        fields.insert(new.clone(), v);
+                        changed = true;
+                    }
+                    Some(None) => changed = true,
+                    None => {
+                        fields.insert(key, v);
+                    }
+                }
+            }
+            changed
+        }
+        _ => false,
+    }
+}
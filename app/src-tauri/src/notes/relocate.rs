@@ -0,0 +1,497 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+use super::{rewrite_index_files, rewrite_links, NoteRegistry};
+use crate::pipeline::{
+    changed_files, output_files, AgentScope, ProcessedManifest, SOURCE_PATH_KEY,
+};
+use crate::storage::{self, FileTransaction, StorageError};
+
+#[derive(Error, Debug)]
+pub enum RelocateError {
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error("Path not found: {0}")]
+    NotFound(String),
+
+    #[error("Failed to delete {0}: {1}")]
+    DeleteFailed(String, String),
+}
+
+/// One note moved or removed by a relocation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteMove {
+    pub from: String,
+    /// None when the note was deleted
+    pub to: Option<String>,
+}
+
+/// What a rename, move or delete touched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelocationReport {
+    /// Final location (after conflict suffixes); None for deletes
+    pub path: Option<PathBuf>,
+    pub notes: Vec<NoteMove>,
+    /// Other notes whose links were rewritten
+    pub updated_links: Vec<String>,
+}
+
+/// Files under a workspace-relative path (the path itself for a file), relative, sorted
+fn files_under(workspace_path: &Path, relative: &str) -> Vec<String> {
+    let root = workspace_path.join(relative);
+    if root.is_file() {
+        return vec![relative.to_string()];
+    }
+
+    let mut files: Vec<String> = WalkDir::new(&root)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| storage::relative_note_path(workspace_path, e.path()))
+        .collect();
+    files.sort();
+    files
+}
+
+fn is_note(relative: &str) -> bool {
+    relative.ends_with(".md")
+}
+
+/// Rename or move a note or folder inside the workspace, carrying its metadata,
+/// processed outputs, index references and links along. `from` and `to` are
+/// workspace-relative. If `to` exists a numeric suffix is added. Every change is
+/// rolled back if any step fails.
+pub fn move_note(
+    workspace_path: &Path,
+    from: &str,
+    to: &str,
+) -> Result<RelocationReport, RelocateError> {
+    let source = workspace_path.join(from);
+    if !source.exists() {
+        return Err(RelocateError::NotFound(source.display().to_string()));
+    }
+    let target = storage::available_path(&source, &workspace_path.join(to))?;
+    let Some(to) = storage::relative_note_path(workspace_path, &target) else {
+        return Err(StorageError::InvalidPath(target.display().to_string()).into());
+    };
+    if from == to {
+        return Ok(RelocationReport {
+            path: Some(target),
+            notes: Vec::new(),
+            updated_links: Vec::new(),
+        });
+    }
+
+    let moves: Vec<(String, String)> = files_under(workspace_path, from)
+        .into_iter()
+        .map(|old| {
+            let new = format!("{}{}", to, &old[from.len()..]);
+            (old, new)
+        })
+        .collect();
+
+    let mut tx = FileTransaction::new();
+    tx.rename(&source, &target)?;
+
+    let changes: Vec<(String, Option<String>)> = moves
+        .iter()
+        .filter(|(old, _)| is_note(old))
+        .map(|(old, new)| (old.clone(), Some(new.clone())))
+        .collect();
+    let deleted_ids = update_companions(&mut tx, workspace_path, &changes)?;
+    update_indexes(&mut tx, workspace_path, &changes, &deleted_ids)?;
+
+    let move_map: HashMap<String, String> = moves.iter().cloned().collect();
+    let updated_links = update_links(&mut tx, workspace_path, &move_map)?;
+
+    tx.commit();
+    tracing::info!("Moved {} to {}", from, to);
+
+    Ok(RelocationReport {
+        path: Some(target),
+        notes: changes
+            .into_iter()
+            .map(|(from, to)| NoteMove { from, to })
+            .collect(),
+        updated_links,
+    })
+}
+
+/// Delete a note or folder along with its metadata, processed outputs and index
+/// references. `remove` performs the final deletion (e.g. moving to the OS trash);
+/// if it fails, every other change is rolled back.
+pub fn delete_note<F>(
+    workspace_path: &Path,
+    path: &str,
+    remove: F,
+) -> Result<RelocationReport, RelocateError>
+where
+    F: FnOnce(&Path) -> Result<(), String>,
+{
+    let target = workspace_path.join(path);
+    if !target.exists() {
+        return Err(RelocateError::NotFound(target.display().to_string()));
+    }
+
+    let changes: Vec<(String, Option<String>)> = files_under(workspace_path, path)
+        .into_iter()
+        .filter(|f| is_note(f))
+        .map(|f| (f, None))
+        .collect();
+
+    let mut tx = FileTransaction::new();
+    let deleted_ids = update_companions(&mut tx, workspace_path, &changes)?;
+    update_indexes(&mut tx, workspace_path, &changes, &deleted_ids)?;
+
+    remove(&target).map_err(|e| RelocateError::DeleteFailed(path.to_string(), e))?;
+    tx.commit();
+    tracing::info!("Deleted {}", path);
+
+    Ok(RelocationReport {
+        path: None,
+        notes: changes
+            .into_iter()
+            .map(|(from, to)| NoteMove { from, to })
+            .collect(),
+        updated_links: Vec::new(),
+    })
+}
+
+/// Catch up with notes renamed (`Some(new)`) or deleted (`None`) outside the app:
+/// the files have already moved, so only their metadata, processed outputs, IDs
+/// and index references follow. Changes the app made itself are already applied
+/// and leave nothing to do. Returns the files it wrote, removed or moved.
+pub fn record_external_moves(
+    workspace_path: &Path,
+    changes: &[(String, Option<String>)],
+) -> Result<Vec<PathBuf>, StorageError> {
+    let mut tx = FileTransaction::new();
+    let deleted_ids = update_companions(&mut tx, workspace_path, changes)?;
+    update_indexes(&mut tx, workspace_path, changes, &deleted_ids)?;
+    let touched = tx.touched_paths();
+    tx.commit();
+    Ok(touched)
+}
+
+/// Move or remove each note's metadata, processed outputs, manifest entry and
+/// registered ID. Returns the IDs of deleted notes.
+fn update_companions(
+    tx: &mut FileTransaction,
+    workspace_path: &Path,
+    changes: &[(String, Option<String>)],
+) -> Result<Vec<String>, StorageError> {
+    let mut manifest = ProcessedManifest::load(workspace_path);
+    let mut manifest_changed = false;
+    let mut registry = NoteRegistry::load(workspace_path);
+    let mut registry_changed = false;
+    let mut deleted_ids = Vec::new();
+
+    for (old, new) in changes {
+        let old_note = workspace_path.join(old);
+        let old_meta = storage::get_meta_path(workspace_path, &old_note)?;
+        let old_key = storage::note_key(workspace_path, &old_note)?;
+
+        if manifest.notes.remove(old).is_some() {
+            manifest_changed = true;
+        }
+
+        let meta: Option<Value> = std::fs::read_to_string(&old_meta)
+            .ok()
+            .map(|content| serde_json::from_str(&content))
+            .transpose()?;
+        let id = meta
+            .as_ref()
+            .and_then(|m| m.get("id"))
+            .and_then(|id| id.as_str())
+            .or_else(|| registry.id_of(old))
+            .map(|id| id.to_string());
+
+        let Some(new) = new else {
+            if let Some(id) = id {
+                registry_changed |= registry.notes.remove(&id).is_some();
+                deleted_ids.push(id);
+            }
+            tx.remove(&old_meta)?;
+            for file in output_files(workspace_path, &old_key) {
+                tx.remove(&file)?;
+            }
+            continue;
+        };
+
+        let new_note = workspace_path.join(new);
+        let new_key = storage::note_key(workspace_path, &new_note)?;
+
+        if let Some(id) = &id {
+            registry_changed |= registry.set(id, new);
+        }
+
+        // Metadata records the file name, so it's rewritten rather than just moved
+        if let Some(mut meta) = meta {
+            if let Some(file) = meta.get_mut("file").and_then(|f| f.as_object_mut()) {
+                let name = new.rsplit('/').next().unwrap_or(new);
+                file.insert("name".to_string(), Value::String(name.to_string()));
+            }
+            let new_meta = storage::get_meta_path(workspace_path, &new_note)?;
+            tx.remove(&new_meta)?;
+            tx.write(&new_meta, &serde_json::to_string_pretty(&meta)?)?;
+            tx.remove(&old_meta)?;
+        }
+
+        let [old_json, old_rest @ ..] = output_files(workspace_path, &old_key);
+        let [new_json, new_rest @ ..] = output_files(workspace_path, &new_key);
+        if let Ok(content) = std::fs::read_to_string(&old_json) {
+            let mut json: Value = serde_json::from_str(&content)?;
+            if let Some(object) = json.as_object_mut() {
+                object.insert(SOURCE_PATH_KEY.to_string(), Value::String(new.clone()));
+            }
+            tx.remove(&new_json)?;
+            tx.write(&new_json, &serde_json::to_string_pretty(&json)?)?;
+            tx.remove(&old_json)?;
+
+            manifest.record(new, &new_key);
+            manifest_changed = true;
+        }
+        for (old_file, new_file) in old_rest.iter().zip(&new_rest) {
+            if old_file.exists() {
+                tx.remove(new_file)?;
+                tx.rename(old_file, new_file)?;
+            }
+        }
+    }
+
+    if manifest_changed {
+        tx.write(
+            &ProcessedManifest::path(workspace_path),
+            &serde_json::to_string_pretty(&manifest)?,
+        )?;
+    }
+    if registry_changed {
+        tx.write(
+            &NoteRegistry::path(workspace_path),
+            &serde_json::to_string_pretty(&registry)?,
+        )?;
+    }
+    Ok(deleted_ids)
+}
+
+/// Rewrite note references in tags.json, actions.json and links.json. Indexes refer
+/// to notes by ID, so only references to deleted notes and legacy paths change.
+fn update_indexes(
+    tx: &mut FileTransaction,
+    workspace_path: &Path,
+    changes: &[(String, Option<String>)],
+    deleted_ids: &[String],
+) -> Result<(), StorageError> {
+    let mut map: HashMap<String, Option<String>> =
+        deleted_ids.iter().map(|id| (id.clone(), None)).collect();
+    // Indexes written before stable IDs may hold workspace-relative or absolute paths
+    for (old, new) in changes {
+        map.insert(old.clone(), new.clone());
+        map.insert(
+            workspace_path.join(old).display().to_string(),
+            new.as_ref()
+                .map(|n| workspace_path.join(n).display().to_string()),
+        );
+    }
+
+    rewrite_index_files(workspace_path, &map, |path, content| {
+        tx.write(path, content)
+    })?;
+    Ok(())
+}
+
+/// Rewrite links in every note affected by the moves. Returns the notes changed.
+fn update_links(
+    tx: &mut FileTransaction,
+    workspace_path: &Path,
+    moves: &HashMap<String, String>,
+) -> Result<Vec<String>, StorageError> {
+    let moved_from: HashMap<&String, &String> = moves.iter().map(|(o, n)| (n, o)).collect();
+    let mut updated = Vec::new();
+
+    // Runs after the move, so notes are listed at their new locations
+    for note in changed_files(workspace_path, AgentScope::Notes) {
+        let old = moved_from.get(&note).map(|o| o.as_str()).unwrap_or(&note);
+
+        let path = workspace_path.join(&note);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(rewritten) = rewrite_links(&content, old, &note, moves) {
+            tx.write(&path, &rewritten)?;
+            updated.push(note);
+        }
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn setup() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join(".chronicle/processed")).unwrap();
+        std::fs::write(ws.join("standup.md"), "# Standup").unwrap();
+        std::fs::write(ws.join("review.md"), "See [standup](standup.md)").unwrap();
+
+        let note = ws.join("standup.md");
+        let mut meta = storage::NoteMeta::new(&note);
+        meta.update_file();
+        storage::save_metadata(ws, &note, &meta).unwrap();
+
+        std::fs::write(ws.join(".chronicle/processed/standup.json"), "{}").unwrap();
+        std::fs::write(ws.join(".chronicle/processed/standup.md"), "## TL;DR").unwrap();
+        crate::pipeline::record_processed_source(ws, &note, "# Standup").unwrap();
+
+        let tags = json!({
+            "byNote": { "standup.md": ["topic:sync"], "review.md": ["topic:review"] },
+            "byTag": { "topic:sync": ["standup.md"], "topic:review": ["review.md"] }
+        });
+        std::fs::write(ws.join(".chronicle/tags.json"), tags.to_string()).unwrap();
+        let actions = json!([
+            { "text": "Ship it", "source": "standup.md", "status": "open" },
+            { "text": "Review", "source": "review.md", "status": "open" }
+        ]);
+        std::fs::write(ws.join(".chronicle/actions.json"), actions.to_string()).unwrap();
+        dir
+    }
+
+    fn read_json(path: PathBuf) -> Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_move_note_carries_everything() {
+        let dir = setup();
+        let ws = dir.path();
+
+        let report = move_note(ws, "standup.md", "team/daily.md").unwrap();
+        assert_eq!(report.path, Some(ws.join("team/daily.md")));
+        assert_eq!(report.updated_links, vec!["review.md".to_string()]);
+
+        assert!(ws.join("team/daily.md").exists());
+        assert!(!ws.join(".chronicle/meta/standup.json").exists());
+        let meta = read_json(ws.join(".chronicle/meta/team/daily.json"));
+        assert_eq!(meta["file"]["name"], "daily.md");
+
+        let processed = read_json(ws.join(".chronicle/processed/team/daily.json"));
+        assert_eq!(processed[SOURCE_PATH_KEY], "team/daily.md");
+        assert!(ws.join(".chronicle/processed/team/daily.md").exists());
+        assert!(ws.join(".chronicle/snapshots/team/daily.md").exists());
+        let manifest = ProcessedManifest::load(ws);
+        assert_eq!(
+            manifest.note_for_output("team/daily"),
+            Some("team/daily.md")
+        );
+
+        let tags = read_json(ws.join(".chronicle/tags.json"));
+        assert_eq!(tags["byNote"]["team/daily.md"], json!(["topic:sync"]));
+        assert_eq!(tags["byTag"]["topic:sync"], json!(["team/daily.md"]));
+        let actions = read_json(ws.join(".chronicle/actions.json"));
+        assert_eq!(actions[0]["source"], "team/daily.md");
+
+        let review = std::fs::read_to_string(ws.join("review.md")).unwrap();
+        assert_eq!(review, "See [standup](team/daily.md)");
+    }
+
+    #[test]
+    fn test_external_rename_carries_companions() {
+        let dir = setup();
+        let ws = dir.path();
+        std::fs::create_dir(ws.join("team")).unwrap();
+        std::fs::rename(ws.join("standup.md"), ws.join("team/daily.md")).unwrap();
+
+        let changes = vec![("standup.md".to_string(), Some("team/daily.md".to_string()))];
+        record_external_moves(ws, &changes).unwrap();
+
+        assert!(!ws.join(".chronicle/meta/standup.json").exists());
+        assert!(ws.join(".chronicle/meta/team/daily.json").exists());
+        assert!(ws.join(".chronicle/processed/team/daily.json").exists());
+        let tags = read_json(ws.join(".chronicle/tags.json"));
+        assert_eq!(tags["byNote"]["team/daily.md"], json!(["topic:sync"]));
+
+        // Applying it again (the app's own move, seen by the watcher) changes nothing
+        record_external_moves(ws, &changes).unwrap();
+        assert!(ws.join(".chronicle/meta/team/daily.json").exists());
+        // Links in other notes are left to the user
+        let review = std::fs::read_to_string(ws.join("review.md")).unwrap();
+        assert_eq!(review, "See [standup](standup.md)");
+    }
+
+    #[test]
+    fn test_delete_note_removes_references() {
+        let dir = setup();
+        let ws = dir.path();
+
+        delete_note(ws, "standup.md", |p| {
+            std::fs::remove_file(p).map_err(|e| e.to_string())
+        })
+        .unwrap();
+
+        assert!(!ws.join("standup.md").exists());
+        assert!(!ws.join(".chronicle/meta/standup.json").exists());
+        assert!(!ws.join(".chronicle/processed/standup.json").exists());
+        assert!(ProcessedManifest::load(ws).notes.is_empty());
+
+        let tags = read_json(ws.join(".chronicle/tags.json"));
+        assert!(tags["byNote"].get("standup.md").is_none());
+        assert_eq!(tags["byTag"]["topic:sync"], json!([]));
+        let actions = read_json(ws.join(".chronicle/actions.json"));
+        assert_eq!(actions.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_note_ids_survive_moves_and_leave_with_deletes() {
+        let dir = setup();
+        let ws = dir.path();
+        let registry = crate::notes::sync_note_ids(ws).unwrap();
+        crate::notes::migrate_index_references(ws, &registry).unwrap();
+        let id = registry.id_of("standup.md").unwrap().to_string();
+
+        move_note(ws, "standup.md", "team/daily.md").unwrap();
+        let registry = NoteRegistry::load(ws);
+        assert_eq!(registry.path_of(&id), Some("team/daily.md"));
+        // Indexes keep the ID; it resolves to the new path
+        let tags = read_json(ws.join(".chronicle/tags.json"));
+        assert_eq!(tags["byTag"]["topic:sync"], json!([id]));
+
+        delete_note(ws, "team/daily.md", |p| {
+            std::fs::remove_file(p).map_err(|e| e.to_string())
+        })
+        .unwrap();
+        assert!(NoteRegistry::load(ws).path_of(&id).is_none());
+        let tags = read_json(ws.join(".chronicle/tags.json"));
+        assert!(tags["byNote"].get(&id).is_none());
+        assert_eq!(tags["byTag"]["topic:sync"], json!([]));
+        let actions = read_json(ws.join(".chronicle/actions.json"));
+        assert_eq!(actions.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_failed_delete_rolls_back() {
+        let dir = setup();
+        let ws = dir.path();
+        let tags_before = std::fs::read_to_string(ws.join(".chronicle/tags.json")).unwrap();
+
+        let result = delete_note(ws, "standup.md", |_| Err("trash unavailable".to_string()));
+        assert!(matches!(result, Err(RelocateError::DeleteFailed(..))));
+
+        assert!(ws.join("standup.md").exists());
+        assert!(ws.join(".chronicle/meta/standup.json").exists());
+        assert!(ws.join(".chronicle/processed/standup.json").exists());
+        let tags_after = std::fs::read_to_string(ws.join(".chronicle/tags.json")).unwrap();
+        assert_eq!(tags_before, tags_after);
+    }
+}
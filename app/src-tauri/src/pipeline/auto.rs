@@ -0,0 +1,187 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{process_decision, Pipeline, ProcessDecision};
+use crate::commands::process_note;
+use crate::tasks::{TaskList, TaskTrigger};
+
+/// Async locks keyed by workspace ID, so work in one open workspace never waits
+/// on another's
+#[derive(Default)]
+pub struct WorkspaceLocks {
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl WorkspaceLocks {
+    pub async fn lock(&self, workspace_id: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = match self.locks.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            locks.entry(workspace_id.to_string()).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+/// Managed state that processes notes one at a time per workspace after their
+/// session ends
+pub struct AutoProcessQueue {
+    running: WorkspaceLocks,
+    pending: Mutex<HashSet<String>>,
+}
+
+impl AutoProcessQueue {
+    pub fn new() -> Self {
+        Self {
+            running: WorkspaceLocks::default(),
+            pending: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Acquire the pending set with poison recovery
+    fn lock_pending(&self) -> MutexGuard<'_, HashSet<String>> {
+        match self.pending.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                tracing::warn!("Recovering from poisoned auto-process lock");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    /// Hold a workspace's queue while running processing outside it, e.g. a bulk
+    /// reprocess
+    pub async fn exclusive(&self, workspace_id: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        self.running.lock(workspace_id).await
+    }
+
+    /// Queue a note whose session just ended. The workspace's auto_process policy
+    /// decides whether it is actually processed; a note already waiting is not queued twice.
+    pub fn enqueue(
+        &self,
+        app_handle: AppHandle,
+        workspace_id: String,
+        workspace_path: String,
+        note_path: String,
+    ) {
+        if !self.lock_pending().insert(note_path.clone()) {
+            return;
+        }
+
+        tauri::async_runtime::spawn(async move {
+            let queue = app_handle.state::<AutoProcessQueue>();
+            let _running = queue.running.lock(&workspace_id).await;
+            queue.lock_pending().remove(&note_path);
+
+            auto_process(&app_handle, &workspace_path, &note_path).await;
+        });
+    }
+}
+
+impl Default for AutoProcessQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn auto_process(app_handle: &AppHandle, workspace_path: &str, note_path: &str) {
+    let workspace = Path::new(workspace_path);
+    let policy = match Pipeline::load(workspace) {
+        Ok(pipeline) => pipeline.auto_process,
+        Err(e) => {
+            tracing::warn!("Skipping auto-process: {}", e);
+            return;
+        }
+    };
+    if !policy.enabled {
+        return;
+    }
+
+    let content = match tokio::fs::read_to_string(note_path).await {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::warn!("Skipping auto-process of {}: {}", note_path, e);
+            return;
+        }
+    };
+
+    match process_decision(
+        workspace,
+        Path::new(note_path),
+        &content,
+        policy.min_changed_lines,
+    ) {
+        ProcessDecision::Unchanged => {
+            tracing::debug!("{} unchanged since last processed", note_path);
+        }
+        ProcessDecision::BelowThreshold { changed_lines } => {
+            tracing::debug!(
+                "{} changed by {} line(s), below threshold of {}",
+                note_path,
+                changed_lines,
+                policy.min_changed_lines
+            );
+        }
+        ProcessDecision::Process { changed_lines } => {
+            tracing::info!(
+                "Auto-processing {} ({:?} lines changed)",
+                note_path,
+                changed_lines
+            );
+
+            let _ = run_process_task(
+                app_handle,
+                workspace_path,
+                note_path,
+                TaskTrigger::SessionEnd,
+            )
+            .await;
+        }
+    }
+}
+
+/// Run `process_note` as a tracked task in the task list
+pub async fn run_process_task(
+    app_handle: &AppHandle,
+    workspace_path: &str,
+    note_path: &str,
+    trigger: TaskTrigger,
+) -> Result<(), String> {
+    let tasks = app_handle.state::<TaskList>();
+    let name = Path::new(note_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(note_path);
+    let record = tasks.start(
+        "process",
+        &format!("Process {}", name),
+        workspace_path,
+        trigger,
+    );
+    app_handle.emit("tasks:updated", record.clone()).ok();
+
+    let result = process_note(
+        app_handle.clone(),
+        workspace_path.to_string(),
+        note_path.to_string(),
+        app_handle.state(),
+    )
+    .await
+    .and_then(|r| {
+        if r.success {
+            Ok(())
+        } else {
+            Err(r.error.unwrap_or_else(|| "Process failed".to_string()))
+        }
+    });
+
+    if let Some(done) = tasks.finish(record.id, result.clone()) {
+        app_handle.emit("tasks:updated", done).ok();
+    }
+
+    result
+}
@@ -0,0 +1,280 @@
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use walkdir::WalkDir;
+
+use super::AgentScope;
+use crate::storage::{self, StorageError};
+
+/// Last-run timestamps per agent, kept in .chronicle/
+pub const AGENT_RUNS_FILE: &str = "agent-runs.json";
+
+/// Read the last successful run time of every agent from agent-runs.json
+pub fn read_agent_runs(workspace_path: &Path) -> HashMap<String, DateTime<Utc>> {
+    storage::read_timestamps(&workspace_path.join(".chronicle").join(AGENT_RUNS_FILE))
+}
+
+/// Record a successful agent run in agent-runs.json, preserving other entries
+pub fn record_agent_run(
+    workspace_path: &Path,
+    agent: &str,
+    at: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    storage::write_timestamp(
+        &workspace_path.join(".chronicle").join(AGENT_RUNS_FILE),
+        agent,
+        at,
+    )
+}
+
+/// Files in each agent's scope at its last successful run, kept in .chronicle/
+pub const AGENT_FILES_FILE: &str = "agent-files.json";
+
+/// Workspace-relative files in an agent's scope, with their note IDs for notes
+pub type ScopeFiles = BTreeMap<String, Option<String>>;
+
+fn read_all_agent_files(workspace_path: &Path) -> HashMap<String, ScopeFiles> {
+    std::fs::read_to_string(workspace_path.join(".chronicle").join(AGENT_FILES_FILE))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+/// Files an agent saw at its last successful run; empty when never recorded
+pub fn read_agent_files(workspace_path: &Path, agent: &str) -> ScopeFiles {
+    read_all_agent_files(workspace_path)
+        .remove(agent)
+        .unwrap_or_default()
+}
+
+/// Record the files an agent saw in a successful run, preserving other agents
+pub fn record_agent_files(
+    workspace_path: &Path,
+    agent: &str,
+    files: ScopeFiles,
+) -> Result<(), StorageError> {
+    let mut all: BTreeMap<String, ScopeFiles> =
+        read_all_agent_files(workspace_path).into_iter().collect();
+    all.insert(agent.to_string(), files);
+    let content = serde_json::to_string_pretty(&all)?;
+    storage::write_file_atomic(
+        &workspace_path.join(".chronicle").join(AGENT_FILES_FILE),
+        &content,
+    )
+}
+
+/// What changed in an agent's scope since its last run
+#[derive(Debug, Default, PartialEq)]
+pub struct ScopeChanges {
+    /// Files modified after the last run, sorted
+    pub changed: Vec<String>,
+    /// Files seen at the last run that no longer exist, with their note IDs
+    pub deleted: Vec<(String, Option<String>)>,
+    /// Every file in scope now
+    pub current: Vec<String>,
+}
+
+impl ScopeChanges {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.deleted.is_empty()
+    }
+}
+
+/// Files in an agent's scope modified after `since`, and those in `previous` (the
+/// files seen at its last run) that have been deleted since
+pub fn scope_changes(
+    workspace_path: &Path,
+    scope: AgentScope,
+    since: Option<DateTime<Utc>>,
+    previous: &ScopeFiles,
+) -> ScopeChanges {
+    let current = changed_files(workspace_path, scope);
+    let changed = match since {
+        Some(since) => current
+            .iter()
+            .filter(|f| modified_after(&workspace_path.join(f), since))
+            .cloned()
+            .collect(),
+        None => current.clone(),
+    };
+    let present: HashSet<&str> = current.iter().map(|f| f.as_str()).collect();
+    let deleted = previous
+        .iter()
+        .filter(|(f, _)| !present.contains(f.as_str()))
+        .map(|(f, id)| (f.clone(), id.clone()))
+        .collect();
+
+    ScopeChanges {
+        changed,
+        deleted,
+        current,
+    }
+}
+
+/// Whether a file was modified after `since`; unreadable times count as modified
+fn modified_after(path: &Path, since: DateTime<Utc>) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map(|t| DateTime::<Utc>::from(t) > since)
+        .unwrap_or(true)
+}
+
+/// Every file in an agent's scope, as workspace-relative paths with `/`
+/// separators, sorted
+pub fn changed_files(workspace_path: &Path, scope: AgentScope) -> Vec<String> {
+    let (root, extension) = match scope {
+        AgentScope::Notes => (workspace_path.to_path_buf(), "md"),
+        AgentScope::Processed => (workspace_path.join(".chronicle").join("processed"), "json"),
+        AgentScope::All => return Vec::new(),
+    };
+    if !root.is_dir() {
+        return Vec::new();
+    }
+
+    let mut files: Vec<String> = WalkDir::new(&root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !e
+                    .file_name()
+                    .to_str()
+                    .map(|s| s.starts_with('.'))
+                    .unwrap_or(false)
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+        })
+        .filter_map(|e| storage::relative_note_path(workspace_path, e.path()))
+        .collect();
+
+    files.sort();
+    files
+}
+
+/// Append the explicit change list to an agent prompt. `deleted` lists files gone
+/// since the last run, whose entries the agent should remove.
+pub fn scoped_prompt(
+    prompt: &str,
+    since: Option<DateTime<Utc>>,
+    files: &[String],
+    deleted: &[String],
+) -> String {
+    let header = match since {
+        Some(ts) => format!("Files changed since your last run ({}):", ts.to_rfc3339()),
+        None => "This is your first run. Files to process:".to_string(),
+    };
+    let mut scoped = format!("{}\n\n{}\n{}", prompt, header, bullet_list(files));
+    if !deleted.is_empty() {
+        scoped.push_str(&format!(
+            "\n\nFiles deleted since your last run:\n{}\n\nRemove everything you keep about the deleted files: their tags, action items, links and summaries.",
+            bullet_list(deleted)
+        ));
+    }
+    scoped.push_str(
+        "\n\nOnly work on the files listed above. Paths are relative to the workspace root.",
+    );
+    scoped
+}
+
+fn bullet_list(files: &[String]) -> String {
+    if files.is_empty() {
+        return "(none)".to_string();
+    }
+    files
+        .iter()
+        .map(|f| format!("- {}", f))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_changed_notes_skip_hidden_dirs() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join("projects")).unwrap();
+        std::fs::create_dir_all(ws.join(".chronicle/processed")).unwrap();
+        std::fs::write(ws.join("a.md"), "# A").unwrap();
+        std::fs::write(ws.join("projects/b.md"), "# B").unwrap();
+        std::fs::write(ws.join("notes.txt"), "not markdown").unwrap();
+        std::fs::write(ws.join(".chronicle/processed/a.json"), "{}").unwrap();
+
+        let notes = changed_files(ws, AgentScope::Notes);
+        assert_eq!(notes, vec!["a.md".to_string(), "projects/b.md".to_string()]);
+
+        let processed = changed_files(ws, AgentScope::Processed);
+        assert_eq!(processed, vec![".chronicle/processed/a.json".to_string()]);
+
+        assert!(changed_files(ws, AgentScope::All).is_empty());
+    }
+
+    #[test]
+    fn test_scope_changes_respect_since() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::write(ws.join("a.md"), "# A").unwrap();
+
+        let past = Utc::now() - chrono::Duration::hours(1);
+        let future = Utc::now() + chrono::Duration::hours(1);
+        let previous = ScopeFiles::new();
+        let changes = scope_changes(ws, AgentScope::Notes, Some(past), &previous);
+        assert_eq!(changes.changed, vec!["a.md".to_string()]);
+        let changes = scope_changes(ws, AgentScope::Notes, Some(future), &previous);
+        assert!(changes.changed.is_empty());
+        assert_eq!(changes.current, vec!["a.md".to_string()]);
+    }
+
+    #[test]
+    fn test_scoped_prompt_lists_files() {
+        let prompt = scoped_prompt("Tag notes.", None, &["a.md".to_string()], &[]);
+        assert!(prompt.starts_with("Tag notes."));
+        assert!(prompt.contains("- a.md"));
+        assert!(!prompt.contains("deleted"));
+
+        let prompt = scoped_prompt("Tag notes.", None, &[], &["b.md".to_string()]);
+        assert!(prompt.contains("(none)"));
+        assert!(prompt.contains("Files deleted since your last run:\n- b.md"));
+    }
+
+    #[test]
+    fn test_scope_changes_report_deleted_notes() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join(".chronicle")).unwrap();
+        std::fs::write(ws.join("a.md"), "# A").unwrap();
+        std::fs::write(ws.join("b.md"), "# B").unwrap();
+
+        let first = scope_changes(ws, AgentScope::Notes, None, &ScopeFiles::new());
+        assert_eq!(first.changed, vec!["a.md".to_string(), "b.md".to_string()]);
+        assert!(first.deleted.is_empty());
+        let seen: ScopeFiles = first
+            .current
+            .iter()
+            .map(|f| (f.clone(), Some(format!("id-{}", f))))
+            .collect();
+        record_agent_files(ws, "tagger", seen).unwrap();
+
+        // Nothing modified since the run, but b.md is gone
+        std::fs::remove_file(ws.join("b.md")).unwrap();
+        let since = Some(Utc::now() + chrono::Duration::hours(1));
+        let previous = read_agent_files(ws, "tagger");
+        let changes = scope_changes(ws, AgentScope::Notes, since, &previous);
+        assert!(changes.changed.is_empty());
+        assert_eq!(
+            changes.deleted,
+            vec![("b.md".to_string(), Some("id-b.md".to_string()))]
+        );
+        assert!(!changes.is_empty());
+
+        assert!(read_agent_files(ws, "actions").is_empty());
+    }
+}
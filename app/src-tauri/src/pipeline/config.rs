@@ -0,0 +1,541 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use thiserror::Error;
+
+use super::{AgentPermissions, DEFAULT_AGENT_WRITES};
+use crate::storage::{AgentsConfig, WorkspaceConfig};
+
+/// Pipeline definition file inside .chronicle/
+pub const PIPELINE_FILE: &str = "pipeline.toml";
+
+/// Tools granted to an agent that doesn't declare its own
+pub const DEFAULT_ALLOWED_TOOLS: &[&str] = &["Read", "Write", "Edit", "Glob", "Grep"];
+
+/// Max turns for an agent that doesn't declare its own
+pub const DEFAULT_MAX_TURNS: u32 = 15;
+
+/// Changed lines needed before a finished session triggers reprocessing
+pub const DEFAULT_MIN_CHANGED_LINES: usize = 3;
+
+/// Default pipeline written to .chronicle/pipeline.toml.
+/// Mirrors the original tagger → actions → context-updater sequence.
+pub const DEFAULT_PIPELINE: &str = r#"# Chronicle background agent pipeline.
+#
+# Each [[agents]] entry runs the prompt at .chronicle/prompts/{prompt}.
+# Agents run one at a time, after everything listed in depends_on.
+#
+#   prompt              - prompt file name (defaults to "{name}.md")
+#   max_turns           - turn limit passed to Claude (default: agents.max_turns
+#                         in config.toml, 15)
+#   allowed_tools       - tools Claude may use (default: agents.allowed_tools in
+#                         config.toml, Read, Write, Edit, Glob, Grep);
+#                         one of Read, Write, Edit, MultiEdit, Glob, Grep, LS
+#   writes              - workspace-relative files the agent may write, or folders
+#                         ending in "/" (default: the .chronicle/ indexes, digests
+#                         and entities). Anything else it writes is rolled back.
+#   scope               - what the agent works on; Chronicle passes it only the files
+#                         changed since its last run and skips it when nothing changed:
+#                         "notes" (default), "processed" (.chronicle/processed/*.json),
+#                         or "all" (always runs, no file list)
+#   depends_on          - agents that must succeed before this one runs
+#   continue_on_failure - if false, a failure stops the whole pipeline;
+#                         if true, only agents depending on this one are skipped
+#
+# [auto_process] queues note processing when an editing session ends:
+#   enabled           - opt in (default false)
+#   min_changed_lines - lines that must differ from the last processed version
+#                       (default 3); notes whose content is unchanged are never
+#                       reprocessed
+version = 1
+
+[auto_process]
+enabled = false
+min_changed_lines = 3
+
+[[agents]]
+name = "tagger"
+prompt = "tagger.md"
+max_turns = 15
+scope = "notes"
+writes = [".chronicle/tags.json"]
+continue_on_failure = false
+
+[[agents]]
+name = "actions"
+prompt = "actions.md"
+max_turns = 15
+scope = "notes"
+writes = [".chronicle/actions.json"]
+depends_on = ["tagger"]
+continue_on_failure = true
+
+[[agents]]
+name = "context-updater"
+prompt = "context-updater.md"
+max_turns = 15
+scope = "processed"
+writes = [".chronicle/context.md"]
+depends_on = ["tagger"]
+continue_on_failure = true
+"#;
+
+#[derive(Error, Debug)]
+pub enum PipelineError {
+    #[error("Failed to read {0}: {1}")]
+    ReadFailed(String, #[source] std::io::Error),
+
+    #[error("Invalid pipeline file: {0}")]
+    ParseError(String),
+
+    #[error("Unsupported pipeline version: {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Duplicate agent name: {0}")]
+    DuplicateAgent(String),
+
+    #[error("Invalid agent name: {0:?}")]
+    InvalidName(String),
+
+    #[error("Agent {0} has an invalid prompt file: {1}")]
+    InvalidPrompt(String, String),
+
+    #[error("Agent {0} uses an unsupported tool: {1}")]
+    UnknownTool(String, String),
+
+    #[error("Agent {0} declares an invalid write path: {1}")]
+    InvalidWrite(String, String),
+
+    #[error("Agent {0} depends on unknown agent {1}")]
+    UnknownDependency(String, String),
+
+    #[error("Dependency cycle between agents: {0}")]
+    Cycle(String),
+}
+
+/// Which files an agent works on, used to compute its change set
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentScope {
+    /// Markdown notes in the workspace
+    #[default]
+    Notes,
+    /// Processed outputs in .chronicle/processed/
+    Processed,
+    /// No change tracking; the agent always runs
+    All,
+}
+
+/// A single agent declared in pipeline.toml
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AgentSpec {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(default = "default_max_turns")]
+    pub max_turns: u32,
+    #[serde(default = "default_allowed_tools")]
+    pub allowed_tools: Vec<String>,
+    #[serde(default = "default_writes")]
+    pub writes: Vec<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub continue_on_failure: bool,
+    #[serde(default)]
+    pub scope: AgentScope,
+}
+
+fn default_max_turns() -> u32 {
+    DEFAULT_MAX_TURNS
+}
+
+fn default_allowed_tools() -> Vec<String> {
+    DEFAULT_ALLOWED_TOOLS.iter().map(|t| t.to_string()).collect()
+}
+
+fn default_writes() -> Vec<String> {
+    DEFAULT_AGENT_WRITES.iter().map(|w| w.to_string()).collect()
+}
+
+impl AgentSpec {
+    /// Spec for an agent that isn't declared in the pipeline, with the workspace's
+    /// default turns and tools. Undeclared agents always run over the whole workspace.
+    pub fn named(name: &str, defaults: &AgentsConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            prompt: None,
+            max_turns: defaults.max_turns,
+            allowed_tools: defaults.allowed_tools.clone(),
+            writes: default_writes(),
+            depends_on: Vec::new(),
+            continue_on_failure: false,
+            scope: AgentScope::All,
+        }
+    }
+
+    /// Prompt filename inside .chronicle/prompts/
+    pub fn prompt_file(&self) -> String {
+        self.prompt
+            .clone()
+            .unwrap_or_else(|| format!("{}.md", self.name))
+    }
+
+    /// The tools the agent gets and what it may write
+    pub fn permissions(&self) -> AgentPermissions {
+        AgentPermissions::new(self.allowed_tools.clone(), self.writes.clone())
+    }
+
+    /// Check the name, that the prompt resolves inside .chronicle/prompts/ and
+    /// the declared tools and writes
+    pub fn validate(&self) -> Result<(), PipelineError> {
+        if !is_valid_name(&self.name) {
+            return Err(PipelineError::InvalidName(self.name.clone()));
+        }
+
+        let prompt = self.prompt_file();
+        if !is_valid_prompt_file(&prompt) {
+            return Err(PipelineError::InvalidPrompt(self.name.clone(), prompt));
+        }
+
+        let permissions = self.permissions();
+        if let Some(tool) = permissions.unknown_tool() {
+            return Err(PipelineError::UnknownTool(self.name.clone(), tool.to_string()));
+        }
+        if let Some(path) = permissions.invalid_write() {
+            return Err(PipelineError::InvalidWrite(self.name.clone(), path.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Policy for processing notes automatically when a session ends
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AutoProcessPolicy {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_min_changed_lines")]
+    pub min_changed_lines: usize,
+}
+
+fn default_min_changed_lines() -> usize {
+    DEFAULT_MIN_CHANGED_LINES
+}
+
+impl Default for AutoProcessPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_changed_lines: DEFAULT_MIN_CHANGED_LINES,
+        }
+    }
+}
+
+/// Background agent pipeline loaded from .chronicle/pipeline.toml
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Pipeline {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub auto_process: AutoProcessPolicy,
+    #[serde(default)]
+    pub agents: Vec<AgentSpec>,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::parse(DEFAULT_PIPELINE).expect("default pipeline is valid")
+    }
+}
+
+impl Pipeline {
+    /// Parse and validate a pipeline definition
+    pub fn parse(content: &str) -> Result<Self, PipelineError> {
+        Self::parse_with(content, &AgentsConfig::default())
+    }
+
+    /// Parse and validate a pipeline definition, giving agents that don't set their
+    /// turns or tools the workspace's defaults
+    pub fn parse_with(content: &str, defaults: &AgentsConfig) -> Result<Self, PipelineError> {
+        let mut table: toml::Table =
+            toml::from_str(content).map_err(|e| PipelineError::ParseError(e.to_string()))?;
+        if let Some(toml::Value::Array(agents)) = table.get_mut("agents") {
+            for agent in agents.iter_mut().filter_map(|a| a.as_table_mut()) {
+                agent
+                    .entry("max_turns")
+                    .or_insert_with(|| toml::Value::Integer(defaults.max_turns.into()));
+                agent.entry("allowed_tools").or_insert_with(|| {
+                    toml::Value::Array(
+                        defaults
+                            .allowed_tools
+                            .iter()
+                            .cloned()
+                            .map(toml::Value::String)
+                            .collect(),
+                    )
+                });
+            }
+        }
+        let pipeline: Pipeline = table
+            .try_into()
+            .map_err(|e: toml::de::Error| PipelineError::ParseError(e.to_string()))?;
+        pipeline.validate()?;
+        Ok(pipeline)
+    }
+
+    /// Load the workspace pipeline, falling back to the default when none exists
+    pub fn load(workspace_path: &Path) -> Result<Self, PipelineError> {
+        let path = workspace_path.join(".chronicle").join(PIPELINE_FILE);
+        let defaults = WorkspaceConfig::load(workspace_path).agents;
+        if !path.exists() {
+            return Self::parse_with(DEFAULT_PIPELINE, &defaults);
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| PipelineError::ReadFailed(path.display().to_string(), e))?;
+        Self::parse_with(&content, &defaults)
+    }
+
+    /// Look up an agent by name
+    pub fn agent(&self, name: &str) -> Option<&AgentSpec> {
+        self.agents.iter().find(|a| a.name == name)
+    }
+
+    /// Check names, prompt files and dependencies, and reject cycles
+    pub fn validate(&self) -> Result<(), PipelineError> {
+        if self.version != 1 {
+            return Err(PipelineError::UnsupportedVersion(self.version));
+        }
+
+        let mut names = HashSet::new();
+        for agent in &self.agents {
+            agent.validate()?;
+            if !names.insert(agent.name.as_str()) {
+                return Err(PipelineError::DuplicateAgent(agent.name.clone()));
+            }
+        }
+
+        for agent in &self.agents {
+            for dep in &agent.depends_on {
+                if !names.contains(dep.as_str()) {
+                    return Err(PipelineError::UnknownDependency(
+                        agent.name.clone(),
+                        dep.clone(),
+                    ));
+                }
+            }
+        }
+
+        self.execution_order().map(|_| ())
+    }
+
+    /// Topological order of agents. Ties keep declaration order so the
+    /// pipeline file reads top to bottom.
+    pub fn execution_order(&self) -> Result<Vec<&AgentSpec>, PipelineError> {
+        let mut remaining: HashMap<&str, usize> = self
+            .agents
+            .iter()
+            .map(|a| (a.name.as_str(), a.depends_on.len()))
+            .collect();
+        let mut done: HashSet<&str> = HashSet::new();
+        let mut order = Vec::with_capacity(self.agents.len());
+
+        while order.len() < self.agents.len() {
+            let next = self
+                .agents
+                .iter()
+                .find(|a| !done.contains(a.name.as_str()) && remaining[a.name.as_str()] == 0);
+
+            let Some(agent) = next else {
+                let mut stuck: Vec<&str> = self
+                    .agents
+                    .iter()
+                    .map(|a| a.name.as_str())
+                    .filter(|n| !done.contains(n))
+                    .collect();
+                stuck.sort_unstable();
+                return Err(PipelineError::Cycle(stuck.join(", ")));
+            };
+
+            done.insert(agent.name.as_str());
+            for other in &self.agents {
+                let count = other
+                    .depends_on
+                    .iter()
+                    .filter(|d| d.as_str() == agent.name)
+                    .count();
+                if count > 0 {
+                    if let Some(r) = remaining.get_mut(other.name.as_str()) {
+                        *r -= count;
+                    }
+                }
+            }
+            order.push(agent);
+        }
+
+        Ok(order)
+    }
+}
+
+/// Agent names double as task names and agent-runs.json keys
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Prompt files must be plain markdown filenames inside .chronicle/prompts/
+fn is_valid_prompt_file(prompt: &str) -> bool {
+    prompt.ends_with(".md")
+        && !prompt.starts_with('.')
+        && !prompt.contains(['/', '\\'])
+        && prompt.len() > ".md".len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn names<'a>(order: &[&'a AgentSpec]) -> Vec<&'a str> {
+        order.iter().map(|a| a.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_default_pipeline() {
+        let pipeline = Pipeline::default();
+        let order = pipeline.execution_order().unwrap();
+        assert_eq!(names(&order), vec!["tagger", "actions", "context-updater"]);
+
+        let tagger = pipeline.agent("tagger").unwrap();
+        assert!(!tagger.continue_on_failure);
+        assert_eq!(tagger.max_turns, 15);
+        assert_eq!(tagger.permissions().tools_arg(), "Read,Write,Edit,Glob,Grep");
+        assert!(tagger.permissions().allows_write(Path::new(".chronicle/tags.json")));
+        assert!(!tagger.permissions().allows_write(Path::new(".chronicle/actions.json")));
+
+        let updater = pipeline.agent("context-updater").unwrap();
+        assert_eq!(updater.scope, AgentScope::Processed);
+    }
+
+    #[test]
+    fn test_auto_process_is_opt_in() {
+        assert!(!Pipeline::default().auto_process.enabled);
+
+        let content = "[auto_process]\nenabled = true\n";
+        let pipeline = Pipeline::parse(content).unwrap();
+        assert!(pipeline.auto_process.enabled);
+        assert_eq!(
+            pipeline.auto_process.min_changed_lines,
+            DEFAULT_MIN_CHANGED_LINES
+        );
+    }
+
+    #[test]
+    fn test_defaults_for_minimal_agent() {
+        let pipeline = Pipeline::parse("[[agents]]\nname = \"decisions\"\n").unwrap();
+        let agent = pipeline.agent("decisions").unwrap();
+        assert_eq!(agent.prompt_file(), "decisions.md");
+        assert_eq!(agent.max_turns, DEFAULT_MAX_TURNS);
+        assert!(agent.depends_on.is_empty());
+        assert!(!agent.continue_on_failure);
+        assert_eq!(agent.scope, AgentScope::Notes);
+        assert_eq!(agent.writes, default_writes());
+    }
+
+    #[test]
+    fn test_agents_take_workspace_defaults() {
+        let defaults = AgentsConfig {
+            max_turns: 5,
+            allowed_tools: vec!["Read".to_string()],
+            ..AgentsConfig::default()
+        };
+        let content = "[[agents]]\nname = \"a\"\n\n[[agents]]\nname = \"b\"\nmax_turns = 20\n";
+        let pipeline = Pipeline::parse_with(content, &defaults).unwrap();
+        let a = pipeline.agent("a").unwrap();
+        assert_eq!((a.max_turns, a.allowed_tools.clone()), (5, vec!["Read".to_string()]));
+        assert_eq!(pipeline.agent("b").unwrap().max_turns, 20);
+    }
+
+    #[test]
+    fn test_unsafe_permissions_rejected() {
+        let bash = "[[agents]]\nname = \"a\"\nallowed_tools = [\"Read\", \"Bash\"]\n";
+        assert!(matches!(Pipeline::parse(bash), Err(PipelineError::UnknownTool(_, _))));
+
+        let escape = "[[agents]]\nname = \"a\"\nwrites = [\"../elsewhere/\"]\n";
+        assert!(matches!(Pipeline::parse(escape), Err(PipelineError::InvalidWrite(_, _))));
+    }
+
+    #[test]
+    fn test_order_follows_dependencies() {
+        let content = r#"
+[[agents]]
+name = "risks"
+depends_on = ["decisions"]
+
+[[agents]]
+name = "tagger"
+
+[[agents]]
+name = "decisions"
+depends_on = ["tagger"]
+"#;
+        let pipeline = Pipeline::parse(content).unwrap();
+        let order = pipeline.execution_order().unwrap();
+        assert_eq!(names(&order), vec!["tagger", "decisions", "risks"]);
+    }
+
+    #[test]
+    fn test_cycle_rejected() {
+        let content = r#"
+[[agents]]
+name = "a"
+depends_on = ["b"]
+
+[[agents]]
+name = "b"
+depends_on = ["a"]
+"#;
+        assert!(matches!(Pipeline::parse(content), Err(PipelineError::Cycle(_))));
+    }
+
+    #[test]
+    fn test_unknown_dependency_rejected() {
+        let content = "[[agents]]\nname = \"a\"\ndepends_on = [\"missing\"]\n";
+        assert!(matches!(
+            Pipeline::parse(content),
+            Err(PipelineError::UnknownDependency(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_agent_rejected() {
+        let content = "[[agents]]\nname = \"a\"\n\n[[agents]]\nname = \"a\"\n";
+        assert!(matches!(
+            Pipeline::parse(content),
+            Err(PipelineError::DuplicateAgent(_))
+        ));
+    }
+
+    #[test]
+    fn test_prompt_outside_prompts_dir_rejected() {
+        let content = "[[agents]]\nname = \"a\"\nprompt = \"../../secrets.md\"\n";
+        assert!(matches!(
+            Pipeline::parse(content),
+            Err(PipelineError::InvalidPrompt(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_load_falls_back_to_default() {
+        let dir = tempdir().unwrap();
+        let pipeline = Pipeline::load(dir.path()).unwrap();
+        assert_eq!(pipeline, Pipeline::default());
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{
+    changed_files, list_processed_outputs, move_outputs, processed_key, AgentScope, ProcessedSource,
+};
+use crate::storage::{self, StorageError};
+
+/// Map of notes to their processed outputs, kept in .chronicle/
+pub const PROCESSED_MANIFEST_FILE: &str = "processed-manifest.json";
+
+const MANIFEST_VERSION: u32 = 1;
+
+/// Where a note's processed output lives
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    /// Output key inside .chronicle/processed/, without extension
+    pub output: String,
+    pub processed_at: DateTime<Utc>,
+}
+
+/// Processed outputs keyed by workspace-relative note path
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProcessedManifest {
+    pub version: u32,
+    #[serde(default)]
+    pub notes: BTreeMap<String, ManifestEntry>,
+}
+
+impl Default for ProcessedManifest {
+    fn default() -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            notes: BTreeMap::new(),
+        }
+    }
+}
+
+impl ProcessedManifest {
+    pub fn path(workspace_path: &Path) -> std::path::PathBuf {
+        workspace_path
+            .join(".chronicle")
+            .join(PROCESSED_MANIFEST_FILE)
+    }
+
+    /// Load the manifest, or an empty one when it is missing or unreadable
+    pub fn load(workspace_path: &Path) -> Self {
+        std::fs::read_to_string(Self::path(workspace_path))
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, workspace_path: &Path) -> Result<(), StorageError> {
+        let content = serde_json::to_string_pretty(self)?;
+        storage::write_file_atomic(&Self::path(workspace_path), &content)
+    }
+
+    /// Record that a note was processed into an output key
+    pub fn record(&mut self, note: &str, output: &str) {
+        self.notes.insert(
+            note.to_string(),
+            ManifestEntry {
+                output: output.to_string(),
+                processed_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Note that produced an output key
+    pub fn note_for_output(&self, output: &str) -> Option<&str> {
+        self.notes
+            .iter()
+            .find(|(_, entry)| entry.output == output)
+            .map(|(note, _)| note.as_str())
+    }
+}
+
+/// Move outputs written before path keying (`.chronicle/processed/{filename}.json`)
+/// to their mirrored location and build the manifest. Runs once per workspace: an
+/// existing manifest means the workspace is already migrated.
+/// Returns the number of outputs moved.
+pub fn migrate_processed_outputs(workspace_path: &Path) -> Result<usize, StorageError> {
+    if ProcessedManifest::path(workspace_path).exists() {
+        return Ok(0);
+    }
+
+    let notes = changed_files(workspace_path, AgentScope::Notes);
+    let mut manifest = ProcessedManifest::default();
+    let mut moved = 0;
+
+    for (key, json_path) in list_processed_outputs(workspace_path) {
+        let json: Value = match storage::read_file(&json_path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+        {
+            Some(json) => json,
+            None => continue,
+        };
+
+        // Prefer the recorded source, then the note at the mirrored path. Legacy outputs
+        // are keyed by file name: only migrate those when the note is unambiguous.
+        let source = ProcessedSource::from_json(&json);
+        let mirrored = format!("{}.md", key);
+        let mut matches: Vec<&String> = match &source.path {
+            Some(path) if notes.contains(path) => vec![path],
+            _ if notes.contains(&mirrored) => vec![&mirrored],
+            _ => notes
+                .iter()
+                .filter(|n| Path::new(n).file_name().and_then(|f| f.to_str()) == Some(&mirrored))
+                .collect(),
+        };
+        if matches.len() != 1 {
+            if matches.len() > 1 {
+                tracing::warn!(
+                    "Leaving processed output {} in place: {} notes share its name",
+                    key,
+                    matches.len()
+                );
+            }
+            continue;
+        }
+        let note = matches.remove(0);
+
+        let new_key = processed_key(workspace_path, Path::new(note))?;
+        if new_key != key {
+            move_outputs(workspace_path, &key, &new_key)?;
+            moved += 1;
+        }
+
+        let processed_at = json
+            .get("processedAt")
+            .and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(Utc::now);
+        manifest.notes.insert(
+            note.clone(),
+            ManifestEntry {
+                output: new_key,
+                processed_at,
+            },
+        );
+    }
+
+    manifest.save(workspace_path)?;
+    if moved > 0 {
+        tracing::info!("Migrated {} processed outputs to path keys", moved);
+    }
+
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_migrate_moves_unambiguous_outputs() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let processed = ws.join(".chronicle/processed");
+        std::fs::create_dir_all(&processed).unwrap();
+        std::fs::create_dir_all(ws.join("projects")).unwrap();
+        std::fs::create_dir_all(ws.join("team")).unwrap();
+
+        std::fs::write(ws.join("top.md"), "# Top").unwrap();
+        std::fs::write(ws.join("projects/review.md"), "# Review").unwrap();
+        std::fs::write(ws.join("projects/standup.md"), "# A").unwrap();
+        std::fs::write(ws.join("team/standup.md"), "# B").unwrap();
+
+        for name in ["top", "review", "standup"] {
+            std::fs::write(processed.join(format!("{}.json", name)), "{}").unwrap();
+            std::fs::write(processed.join(format!("{}.md", name)), "## TL;DR").unwrap();
+        }
+
+        assert_eq!(migrate_processed_outputs(ws).unwrap(), 1);
+
+        // Nested note moved into the mirrored tree, markdown alongside
+        assert!(processed.join("projects/review.json").exists());
+        assert!(processed.join("projects/review.md").exists());
+        assert!(!processed.join("review.json").exists());
+        // Top-level note keeps its key; the ambiguous name stays put
+        assert!(processed.join("top.json").exists());
+        assert!(processed.join("standup.json").exists());
+
+        let manifest = ProcessedManifest::load(ws);
+        assert_eq!(
+            manifest.note_for_output("projects/review"),
+            Some("projects/review.md")
+        );
+        assert_eq!(manifest.note_for_output("top"), Some("top.md"));
+        assert!(manifest.note_for_output("standup").is_none());
+
+        // Second run is a no-op
+        assert_eq!(migrate_processed_outputs(ws).unwrap(), 0);
+    }
+}
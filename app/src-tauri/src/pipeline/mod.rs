@@ -0,0 +1,13 @@
+mod auto;
+mod changes;
+mod config;
+mod manifest;
+mod processed;
+mod sandbox;
+
+pub use auto::*;
+pub use changes::*;
+pub use config::*;
+pub use manifest::*;
+pub use processed::*;
+pub use sandbox::*;
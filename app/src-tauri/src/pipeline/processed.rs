@@ -0,0 +1,520 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{changed_files, AgentScope, ProcessedManifest};
+use crate::storage::{self, StorageError};
+
+/// Key in the processed JSON holding the hash of the note it was produced from
+pub const SOURCE_HASH_KEY: &str = "sourceHash";
+
+/// Key in the processed JSON holding the HEAD commit at processing time
+pub const SOURCE_COMMIT_KEY: &str = "sourceCommit";
+
+/// Key in the processed JSON holding the workspace-relative note path
+pub const SOURCE_PATH_KEY: &str = "sourcePath";
+
+/// Processed outputs, mirroring the notes' folder structure, kept in .chronicle/
+pub const PROCESSED_DIR: &str = "processed";
+
+/// Copies of notes as they were when last processed, kept in .chronicle/
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// Check a processed output against its note. Returns the note (if it still exists)
+/// and its staleness; outputs whose note is gone are never reported stale.
+pub fn processed_freshness(
+    workspace_path: &Path,
+    key: &str,
+    json: &Value,
+    manifest: &ProcessedManifest,
+) -> (Option<PathBuf>, Staleness) {
+    let source = ProcessedSource::from_json(json);
+    let Some(note) = resolve_source_note(workspace_path, key, &source, manifest) else {
+        return (None, Staleness::default());
+    };
+
+    let freshness = std::fs::read_to_string(&note)
+        .map(|content| staleness(workspace_path, &note, &content, &source))
+        .unwrap_or_default();
+    (Some(note), freshness)
+}
+
+/// Whether a note needs processing after a session
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProcessDecision {
+    /// Content matches the version the processed output was produced from
+    Unchanged,
+    /// Content changed, but by fewer lines than the policy requires
+    BelowThreshold { changed_lines: usize },
+    /// Content changed enough (or was never processed)
+    Process { changed_lines: Option<usize> },
+}
+
+/// What a processed output was produced from, as recorded in its JSON
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessedSource {
+    pub hash: Option<String>,
+    pub commit: Option<String>,
+    pub path: Option<String>,
+}
+
+impl ProcessedSource {
+    /// Read the source fields from a processed JSON value
+    pub fn from_json(json: &Value) -> Self {
+        let field = |key: &str| json.get(key).and_then(|v| v.as_str()).map(String::from);
+        Self {
+            hash: field(SOURCE_HASH_KEY),
+            commit: field(SOURCE_COMMIT_KEY),
+            path: field(SOURCE_PATH_KEY),
+        }
+    }
+}
+
+/// Whether a processed output still matches its note
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Staleness {
+    /// The note changed since processing, or the output predates source tracking
+    pub stale: bool,
+    /// Lines added or removed since processing, when the processed version is known
+    pub lines_changed: Option<usize>,
+}
+
+/// SHA-256 of note content, hex encoded
+pub fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Output key for a note: its workspace-relative path without the .md extension,
+/// e.g. `projects/standup`. Notes outside the workspace have none.
+pub fn processed_key(workspace_path: &Path, note_path: &Path) -> Result<String, StorageError> {
+    storage::note_key(workspace_path, note_path)
+}
+
+/// Output key of a file inside .chronicle/processed/, e.g. `projects/standup`
+pub fn output_key(workspace_path: &Path, output_path: &Path) -> Option<String> {
+    let relative = output_path
+        .strip_prefix(workspace_path.join(".chronicle").join(PROCESSED_DIR))
+        .ok()?;
+    Some(
+        relative
+            .with_extension("")
+            .components()
+            .filter_map(|c| c.as_os_str().to_str())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+fn output_path(workspace_path: &Path, dir: &str, key: &str, extension: &str) -> PathBuf {
+    workspace_path
+        .join(".chronicle")
+        .join(dir)
+        .join(format!("{}.{}", key, extension))
+}
+
+/// .chronicle/processed/{relative path}.json for a note
+pub fn processed_json_path(
+    workspace_path: &Path,
+    note_path: &Path,
+) -> Result<PathBuf, StorageError> {
+    let key = processed_key(workspace_path, note_path)?;
+    Ok(output_path(workspace_path, PROCESSED_DIR, &key, "json"))
+}
+
+/// .chronicle/processed/{relative path}.md for a note
+pub fn processed_markdown_path(
+    workspace_path: &Path,
+    note_path: &Path,
+) -> Result<PathBuf, StorageError> {
+    let key = processed_key(workspace_path, note_path)?;
+    Ok(output_path(workspace_path, PROCESSED_DIR, &key, "md"))
+}
+
+fn snapshot_path(workspace_path: &Path, note_path: &Path) -> Result<PathBuf, StorageError> {
+    let key = processed_key(workspace_path, note_path)?;
+    Ok(output_path(workspace_path, SNAPSHOTS_DIR, &key, "md"))
+}
+
+/// Every processed JSON output in the workspace as (key, path), sorted by key
+pub fn list_processed_outputs(workspace_path: &Path) -> Vec<(String, PathBuf)> {
+    changed_files(workspace_path, AgentScope::Processed)
+        .into_iter()
+        .map(|f| workspace_path.join(f))
+        .filter_map(|path| Some((output_key(workspace_path, &path)?, path)))
+        .collect()
+}
+
+/// Files kept for an output key: processed JSON (first), processed markdown and snapshot
+pub fn output_files(workspace_path: &Path, key: &str) -> [PathBuf; 3] {
+    [
+        output_path(workspace_path, PROCESSED_DIR, key, "json"),
+        output_path(workspace_path, PROCESSED_DIR, key, "md"),
+        output_path(workspace_path, SNAPSHOTS_DIR, key, "md"),
+    ]
+}
+
+/// Move a key's processed outputs and snapshot to another key.
+/// Existing files at the destination are left alone.
+pub(crate) fn move_outputs(
+    workspace_path: &Path,
+    from: &str,
+    to: &str,
+) -> Result<(), StorageError> {
+    let targets = output_files(workspace_path, to);
+    for (source, target) in output_files(workspace_path, from).iter().zip(&targets) {
+        if !source.exists() || target.exists() {
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            storage::ensure_dir(parent)?;
+        }
+        std::fs::rename(source, target)
+            .map_err(|e| StorageError::WriteFailed(target.display().to_string(), e))?;
+    }
+    Ok(())
+}
+
+/// Source recorded in a note's processed JSON (empty when not processed)
+pub fn read_processed_source(workspace_path: &Path, note_path: &Path) -> ProcessedSource {
+    processed_json_path(workspace_path, note_path)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|c| serde_json::from_str::<Value>(&c).ok())
+        .map(|json| ProcessedSource::from_json(&json))
+        .unwrap_or_default()
+}
+
+/// Find the note a processed output belongs to: its recorded path or manifest entry
+/// when that still exists, then the note at the output's mirrored path. Outputs from
+/// before path keying fall back to the note with the same file name, when exactly
+/// one note has it; like `migrate_processed_outputs`, ambiguous names resolve to none.
+pub fn resolve_source_note(
+    workspace_path: &Path,
+    key: &str,
+    source: &ProcessedSource,
+    manifest: &ProcessedManifest,
+) -> Option<PathBuf> {
+    let candidates = [
+        source.path.clone(),
+        manifest.note_for_output(key).map(String::from),
+        Some(format!("{}.md", key)),
+    ];
+    for path in candidates.into_iter().flatten() {
+        let full = workspace_path.join(path);
+        if full.is_file() {
+            return Some(full);
+        }
+    }
+    if key.contains('/') {
+        return None;
+    }
+
+    let file_name = format!("{}.md", key);
+    let mut matches = changed_files(workspace_path, AgentScope::Notes)
+        .into_iter()
+        .map(|f| workspace_path.join(f))
+        .filter(|p| p.file_name().and_then(|n| n.to_str()) == Some(file_name.as_str()));
+    let note = matches.next()?;
+    matches.next().is_none().then_some(note)
+}
+
+/// Note content as it was when last processed: the snapshot taken at processing
+/// time, falling back to the note at the recorded commit
+fn processed_content(
+    workspace_path: &Path,
+    note_path: &Path,
+    source: &ProcessedSource,
+) -> Option<String> {
+    let snapshot = snapshot_path(workspace_path, note_path).ok()?;
+    if let Ok(content) = std::fs::read_to_string(snapshot) {
+        if source.hash.as_deref() == Some(content_hash(&content).as_str()) {
+            return Some(content);
+        }
+    }
+
+    let commit = source.commit.as_deref()?;
+    let relative = note_path.strip_prefix(workspace_path).ok()?;
+    crate::git::read_file_at_commit(workspace_path, commit, relative)
+        .ok()
+        .flatten()
+}
+
+/// Compare a note's current content with the version its processed output came from
+pub fn staleness(
+    workspace_path: &Path,
+    note_path: &Path,
+    content: &str,
+    source: &ProcessedSource,
+) -> Staleness {
+    let Some(hash) = &source.hash else {
+        return Staleness {
+            stale: true,
+            lines_changed: None,
+        };
+    };
+    if *hash == content_hash(content) {
+        return Staleness::default();
+    }
+
+    Staleness {
+        stale: true,
+        lines_changed: processed_content(workspace_path, note_path, source)
+            .map(|previous| changed_lines(&previous, content)),
+    }
+}
+
+/// Number of lines added or removed between two versions, ignoring moves
+pub fn changed_lines(old: &str, new: &str) -> usize {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    for line in old.lines() {
+        *counts.entry(line.trim_end()).or_default() += 1;
+    }
+    for line in new.lines() {
+        *counts.entry(line.trim_end()).or_default() -= 1;
+    }
+    counts.values().map(|c| c.unsigned_abs()).sum()
+}
+
+/// Decide whether a note should be processed, comparing its current content
+/// with the version its processed output was produced from
+pub fn process_decision(
+    workspace_path: &Path,
+    note_path: &Path,
+    content: &str,
+    min_changed_lines: usize,
+) -> ProcessDecision {
+    let source = read_processed_source(workspace_path, note_path);
+    let staleness = staleness(workspace_path, note_path, content, &source);
+    if !staleness.stale {
+        return ProcessDecision::Unchanged;
+    }
+
+    // Without the processed version there is nothing to diff against
+    let Some(changed) = staleness.lines_changed else {
+        return ProcessDecision::Process {
+            changed_lines: None,
+        };
+    };
+
+    if changed < min_changed_lines {
+        ProcessDecision::BelowThreshold {
+            changed_lines: changed,
+        }
+    } else {
+        ProcessDecision::Process {
+            changed_lines: Some(changed),
+        }
+    }
+}
+
+/// Stamp the processed JSON with the hash, commit and path of the content it was
+/// produced from, and keep a snapshot of that content for later diffs
+pub fn record_processed_source(
+    workspace_path: &Path,
+    note_path: &Path,
+    content: &str,
+) -> Result<(), StorageError> {
+    let json_path = processed_json_path(workspace_path, note_path)?;
+    let mut json: Value = serde_json::from_str(&storage::read_file(&json_path)?)?;
+
+    let Some(object) = json.as_object_mut() else {
+        return Err(StorageError::ParseError(
+            json_path.display().to_string(),
+            "expected a JSON object".to_string(),
+        ));
+    };
+    object.insert(
+        SOURCE_HASH_KEY.to_string(),
+        Value::String(content_hash(content)),
+    );
+    match crate::git::head_commit_id(workspace_path) {
+        Ok(Some(commit)) => {
+            object.insert(SOURCE_COMMIT_KEY.to_string(), Value::String(commit));
+        }
+        Ok(None) => {
+            object.remove(SOURCE_COMMIT_KEY);
+        }
+        Err(e) => tracing::warn!("Failed to read HEAD for {}: {}", note_path.display(), e),
+    }
+    let relative = storage::relative_note_path(workspace_path, note_path);
+    if let Some(relative) = &relative {
+        object.insert(SOURCE_PATH_KEY.to_string(), Value::String(relative.clone()));
+    }
+
+    storage::write_file_atomic(&json_path, &serde_json::to_string_pretty(&json)?)?;
+    storage::write_file_atomic(&snapshot_path(workspace_path, note_path)?, content)?;
+
+    if let Some(relative) = relative {
+        let mut manifest = ProcessedManifest::load(workspace_path);
+        manifest.record(&relative, &processed_key(workspace_path, note_path)?);
+        manifest.save(workspace_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_processed_key_mirrors_relative_path() {
+        let ws = Path::new("/ws");
+        assert_eq!(
+            processed_key(ws, Path::new("/ws/standup.md")).unwrap(),
+            "standup"
+        );
+        assert_eq!(
+            processed_key(ws, Path::new("/ws/projects/a/standup.md")).unwrap(),
+            "projects/a/standup"
+        );
+        assert_eq!(
+            processed_key(ws, Path::new("projects/standup.md")).unwrap(),
+            "projects/standup"
+        );
+        assert_eq!(
+            processed_key(ws, Path::new("../escape.md")).unwrap(),
+            "escape"
+        );
+        assert!(processed_key(ws, Path::new("/elsewhere/standup.md")).is_err());
+        assert_eq!(
+            output_key(
+                ws,
+                Path::new("/ws/.chronicle/processed/projects/standup.json")
+            ),
+            Some("projects/standup".to_string())
+        );
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        assert_eq!(changed_lines("a\nb\nc", "a\nb\nc"), 0);
+        assert_eq!(changed_lines("a\nb\nc", "c\nb\na"), 0);
+        assert_eq!(changed_lines("a\nb", "a\nb\nc\nd"), 2);
+        assert_eq!(changed_lines("a\nb", "a\nx"), 2);
+    }
+
+    #[test]
+    fn test_process_decision_uses_hash_and_threshold() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let note = ws.join("meeting.md");
+        let original = "# Meeting\n\n- one\n- two\n";
+
+        // Never processed
+        assert_eq!(
+            process_decision(ws, &note, original, 3),
+            ProcessDecision::Process {
+                changed_lines: None
+            }
+        );
+
+        let json_path = processed_json_path(ws, &note).unwrap();
+        std::fs::create_dir_all(json_path.parent().unwrap()).unwrap();
+        std::fs::write(&json_path, r#"{"tldr": "A meeting"}"#).unwrap();
+        record_processed_source(ws, &note, original).unwrap();
+
+        let json: Value =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["tldr"], "A meeting");
+        assert_eq!(json[SOURCE_HASH_KEY], content_hash(original));
+
+        assert_eq!(
+            process_decision(ws, &note, original, 3),
+            ProcessDecision::Unchanged
+        );
+
+        let small_edit = "# Meeting\n\n- one\n- two\n- three\n";
+        assert_eq!(
+            process_decision(ws, &note, small_edit, 3),
+            ProcessDecision::BelowThreshold { changed_lines: 1 }
+        );
+
+        let big_edit = "# Meeting\n\n- one\n- two\n- three\n- four\n- five\n";
+        assert_eq!(
+            process_decision(ws, &note, big_edit, 3),
+            ProcessDecision::Process {
+                changed_lines: Some(3)
+            }
+        );
+    }
+
+    #[test]
+    fn test_staleness_falls_back_to_commit() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        crate::git::init_or_open_repo(ws).unwrap();
+
+        std::fs::create_dir_all(ws.join("projects")).unwrap();
+        let note = ws.join("projects/standup.md");
+        let original = "# Standup\n\n- one\n";
+        std::fs::write(&note, original).unwrap();
+        crate::git::commit_files(
+            ws,
+            &[Path::new("projects/standup.md")],
+            crate::git::CommitType::Session,
+            "Standup",
+            "1m",
+        )
+        .unwrap();
+
+        let json_path = processed_json_path(ws, &note).unwrap();
+        std::fs::create_dir_all(json_path.parent().unwrap()).unwrap();
+        std::fs::write(&json_path, "{}").unwrap();
+        record_processed_source(ws, &note, original).unwrap();
+
+        assert!(ws
+            .join(".chronicle/processed/projects/standup.json")
+            .exists());
+        let source = read_processed_source(ws, &note);
+        assert!(source.commit.is_some());
+        assert_eq!(source.path.as_deref(), Some("projects/standup.md"));
+        assert_eq!(
+            resolve_source_note(
+                ws,
+                "projects/standup",
+                &source,
+                &ProcessedManifest::default()
+            ),
+            Some(note.clone())
+        );
+        assert_eq!(
+            staleness(ws, &note, original, &source),
+            Staleness::default()
+        );
+
+        // Lose the snapshot: the diff comes from the recorded commit instead
+        std::fs::remove_dir_all(ws.join(".chronicle/snapshots")).unwrap();
+        let edited = "# Standup\n\n- one\n- two\n";
+        assert_eq!(
+            staleness(ws, &note, edited, &source),
+            Staleness {
+                stale: true,
+                lines_changed: Some(1)
+            }
+        );
+
+        // A legacy key resolves by file name only while the name is unique
+        let legacy = |ws: &Path| {
+            resolve_source_note(
+                ws,
+                "standup",
+                &ProcessedSource::default(),
+                &ProcessedManifest::default(),
+            )
+        };
+        assert_eq!(legacy(ws), Some(note.clone()));
+        std::fs::create_dir_all(ws.join("team")).unwrap();
+        std::fs::write(ws.join("team/standup.md"), "# Team standup\n").unwrap();
+        assert_eq!(legacy(ws), None);
+
+        // Outputs from before source tracking can't be verified
+        assert!(staleness(ws, &note, edited, &ProcessedSource::default()).stale);
+    }
+}
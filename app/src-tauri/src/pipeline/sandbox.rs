@@ -0,0 +1,667 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use super::WorkspaceLocks;
+use crate::storage::{self, FilesConfig, WorkspaceConfig};
+
+/// Tools for agents and commands that only read the workspace
+pub const READ_ONLY_TOOLS: &[&str] = &["Read", "Glob", "Grep"];
+
+/// Tools that may be granted. Anything that runs programs or reaches the network is
+/// left out: what it writes couldn't be checked or rolled back.
+pub const KNOWN_TOOLS: &[&str] = &["Read", "Write", "Edit", "MultiEdit", "Glob", "Grep", "LS"];
+
+/// Tools that modify files
+const WRITE_TOOLS: &[&str] = &["Write", "Edit", "MultiEdit"];
+
+/// Denied on every run, even when the workspace's own Claude settings allow them
+pub const DENIED_TOOLS: &[&str] = &["Bash", "WebFetch", "WebSearch", "NotebookEdit", "Task"];
+
+/// Files background agents may write when they don't declare any: the indexes
+pub const DEFAULT_AGENT_WRITES: &[&str] = &[
+    ".chronicle/tags.json",
+    ".chronicle/actions.json",
+    ".chronicle/links.json",
+    ".chronicle/context.md",
+    ".chronicle/entities/",
+    ".chronicle/digests/",
+];
+
+/// Bookkeeping the app itself keeps writing while an agent runs (session journals,
+/// note metadata, state.json). Not checked, so it is never rolled back.
+const APP_MANAGED: &[&str] = &[
+    ".chronicle/meta/",
+    ".chronicle/sessions/",
+    ".chronicle/state.json",
+    ".chronicle/note-ids.json",
+];
+
+/// Files git can't restore that are larger than this are checked by size and
+/// modification time only, and can't be restored if an agent changes them
+const MAX_SNAPSHOT_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Most file content a snapshot keeps in all; files past it are only stamped
+const MAX_SNAPSHOT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// What an agent or command may do: the tools it gets and the workspace-relative
+/// paths it may write. A path ending in `/` covers everything under it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentPermissions {
+    pub tools: Vec<String>,
+    pub writes: Vec<String>,
+}
+
+impl AgentPermissions {
+    pub fn new(tools: Vec<String>, writes: Vec<String>) -> Self {
+        Self { tools, writes }
+    }
+
+    /// Read tools only, nothing may be written
+    pub fn read_only() -> Self {
+        Self::new(to_strings(READ_ONLY_TOOLS), Vec::new())
+    }
+
+    pub fn can_write(&self) -> bool {
+        self.tools.iter().any(|t| WRITE_TOOLS.contains(&t.as_str()))
+    }
+
+    /// Whether a workspace-relative path may be written
+    pub fn allows_write(&self, relative: &Path) -> bool {
+        self.can_write() && matches_any(&self.writes, relative)
+    }
+
+    /// Allowed tools in the comma-separated form expected by `--allowedTools`
+    pub fn tools_arg(&self) -> String {
+        self.tools.join(",")
+    }
+
+    /// Denied tools for `--disallowedTools`: the dangerous ones, and the write
+    /// tools when none were granted
+    pub fn denied_tools_arg(&self) -> String {
+        let mut denied = to_strings(DENIED_TOOLS);
+        denied.extend(
+            WRITE_TOOLS
+                .iter()
+                .filter(|t| !self.tools.iter().any(|granted| granted == *t))
+                .map(|t| t.to_string()),
+        );
+        denied.join(",")
+    }
+
+    /// The first unknown tool, if any
+    pub fn unknown_tool(&self) -> Option<&str> {
+        self.tools
+            .iter()
+            .map(String::as_str)
+            .find(|t| !KNOWN_TOOLS.contains(t))
+    }
+
+    /// The first write path that isn't a plain workspace-relative path, if any
+    pub fn invalid_write(&self) -> Option<&str> {
+        self.writes
+            .iter()
+            .map(String::as_str)
+            .find(|w| !is_valid_write_path(w))
+    }
+}
+
+/// Permissions declared in a command file's front matter:
+///
+/// ```yaml
+/// ---
+/// tools: [Read, Glob, Grep, Write]
+/// writes: [.chronicle/digests/]
+/// ---
+/// ```
+///
+/// Commands that declare nothing are read-only.
+#[derive(Debug, Default, Deserialize)]
+struct CommandFrontMatter {
+    tools: Option<Vec<String>>,
+    #[serde(default)]
+    writes: Vec<String>,
+}
+
+/// Split a command file into its permissions and its prompt
+pub fn command_permissions(content: &str) -> Result<(AgentPermissions, &str), String> {
+    let (block, body) = storage::split_front_matter(content);
+    let declared: CommandFrontMatter = match block.filter(|b| !b.trim().is_empty()) {
+        Some(block) => serde_yaml::from_str(block)
+            .map_err(|e| format!("Invalid command front matter: {}", e))?,
+        None => CommandFrontMatter::default(),
+    };
+
+    let permissions = AgentPermissions::new(
+        declared
+            .tools
+            .unwrap_or_else(|| to_strings(READ_ONLY_TOOLS)),
+        declared.writes,
+    );
+    if let Some(tool) = permissions.unknown_tool() {
+        return Err(format!("Command uses an unsupported tool: {}", tool));
+    }
+    if let Some(path) = permissions.invalid_write() {
+        return Err(format!("Command declares an invalid write path: {}", path));
+    }
+    Ok((permissions, body))
+}
+
+/// A workspace-relative file or folder (trailing `/`) outside `.git`
+pub fn is_valid_write_path(path: &str) -> bool {
+    let mut components = Path::new(path).components().peekable();
+    components.peek().is_some()
+        && components.enumerate().all(|(i, c)| match c {
+            Component::Normal(name) => i > 0 || name != ".git",
+            _ => false,
+        })
+}
+
+fn matches_any(patterns: &[impl AsRef<str>], relative: &Path) -> bool {
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.as_ref();
+        match pattern.strip_suffix('/') {
+            Some(dir) => relative.starts_with(dir),
+            None => relative == Path::new(pattern),
+        }
+    })
+}
+
+fn to_strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+/// How a file changed outside an agent's allowlist
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WriteKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// A write an agent wasn't allowed to make
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteViolation {
+    /// Workspace-relative path
+    pub path: String,
+    pub kind: WriteKind,
+    /// False when the change couldn't be undone (e.g. a large file was modified)
+    pub rolled_back: bool,
+}
+
+/// Size and modification time of a file, to tell whether it changed
+#[derive(Debug, PartialEq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    fn of(metadata: &std::fs::Metadata) -> Self {
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum SnapshotFile {
+    /// Same as in the git index: only its metadata and blob are recorded, and it
+    /// is restored from the blob
+    Tracked { stamp: FileStamp, blob: git2::Oid },
+    /// Not in git or changed since; kept to be restored
+    Content(Vec<u8>),
+    /// Not in git and too large to keep, or past the snapshot's total; only
+    /// changes are detected
+    Stamped(FileStamp),
+}
+
+/// Index blobs of the tracked files whose working copy matches the index
+fn clean_tracked_blobs(root: &Path) -> HashMap<PathBuf, git2::Oid> {
+    let Ok(repo) = git2::Repository::open(root) else {
+        return HashMap::new();
+    };
+    let (Ok(index), Ok(statuses)) = (
+        repo.index(),
+        repo.statuses(Some(
+            git2::StatusOptions::new()
+                .include_untracked(false)
+                .include_ignored(false),
+        )),
+    ) else {
+        return HashMap::new();
+    };
+
+    let changed = git2::Status::WT_MODIFIED
+        | git2::Status::WT_DELETED
+        | git2::Status::WT_TYPECHANGE
+        | git2::Status::WT_RENAMED
+        | git2::Status::CONFLICTED;
+    let dirty: HashSet<PathBuf> = statuses
+        .iter()
+        .filter(|entry| entry.status().intersects(changed))
+        .filter_map(|entry| entry.path().map(PathBuf::from))
+        .collect();
+
+    index
+        .iter()
+        .filter_map(|entry| {
+            let path = PathBuf::from(std::str::from_utf8(&entry.path).ok()?);
+            (!dirty.contains(&path)).then_some((path, entry.id))
+        })
+        .collect()
+}
+
+/// The workspace's files before an agent run, to find and undo writes outside
+/// its allowlist afterwards. Files git can restore are recorded by metadata and
+/// blob only; the rest are kept. Only `.git/config` and `.git/hooks/` are kept
+/// from `.git`, since those are what could make git run code. Folders the
+/// workspace skips (`files.skip_dirs`) aren't snapshotted.
+#[derive(Debug)]
+pub struct WorkspaceSnapshot {
+    root: PathBuf,
+    config: FilesConfig,
+    files: HashMap<PathBuf, SnapshotFile>,
+    dirs: HashSet<PathBuf>,
+}
+
+impl WorkspaceSnapshot {
+    /// Snapshot every file the agent may not write
+    pub fn capture(root: &Path, permissions: &AgentPermissions) -> Self {
+        Self::capture_within(root, permissions, MAX_SNAPSHOT_BYTES)
+    }
+
+    /// Snapshot keeping at most `max_bytes` of file content
+    fn capture_within(root: &Path, permissions: &AgentPermissions, max_bytes: u64) -> Self {
+        let config = WorkspaceConfig::load(root).files;
+        let mut tracked = clean_tracked_blobs(root);
+        let mut files = HashMap::new();
+        let mut dirs = HashSet::new();
+        let mut kept = 0u64;
+        for (relative, entry) in guarded_entries(root, permissions, &config) {
+            if entry.file_type().is_dir() {
+                dirs.insert(relative);
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let stamp = FileStamp::of(&metadata);
+            let file = if let Some(blob) = tracked.remove(&relative) {
+                SnapshotFile::Tracked { stamp, blob }
+            } else if stamp.len <= MAX_SNAPSHOT_FILE_BYTES && kept + stamp.len <= max_bytes {
+                match std::fs::read(entry.path()) {
+                    Ok(content) => {
+                        kept += content.len() as u64;
+                        SnapshotFile::Content(content)
+                    }
+                    Err(_) => continue,
+                }
+            } else {
+                SnapshotFile::Stamped(stamp)
+            };
+            files.insert(relative, file);
+        }
+        Self {
+            root: root.to_path_buf(),
+            config,
+            files,
+            dirs,
+        }
+    }
+
+    /// Changes since the snapshot to files the agent may not write. `excused` are
+    /// workspace-relative paths the app itself wrote meanwhile (the user editing).
+    pub fn violations(
+        &self,
+        permissions: &AgentPermissions,
+        excused: &HashSet<PathBuf>,
+    ) -> Vec<WriteViolation> {
+        let mut violations = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (relative, entry) in guarded_entries(&self.root, permissions, &self.config) {
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            seen.insert(relative.clone());
+            if excused.contains(&relative) {
+                continue;
+            }
+            let kind = match self.files.get(&relative) {
+                None => WriteKind::Created,
+                Some(before) if changed(before, entry.path()) => WriteKind::Modified,
+                Some(_) => continue,
+            };
+            violations.push(violation(&relative, kind));
+        }
+
+        for relative in self.files.keys() {
+            if !seen.contains(relative) && !excused.contains(relative) {
+                violations.push(violation(relative, WriteKind::Deleted));
+            }
+        }
+
+        violations.sort_by(|a, b| a.path.cmp(&b.path));
+        violations
+    }
+
+    /// Undo the violations: restore modified and deleted files, remove created ones
+    /// and any folders created for them. Marks what couldn't be undone.
+    pub fn roll_back(&self, violations: &mut [WriteViolation]) {
+        let repo = git2::Repository::open(&self.root).ok();
+        for violation in violations.iter_mut() {
+            let path = self.root.join(&violation.path);
+            let result = match (violation.kind, self.files.get(Path::new(&violation.path))) {
+                (WriteKind::Created, _) => std::fs::remove_file(&path).map_err(|e| e.to_string()),
+                (_, Some(SnapshotFile::Content(content))) => {
+                    storage::write_bytes_atomic(&path, content).map_err(|e| e.to_string())
+                }
+                (_, Some(SnapshotFile::Tracked { blob, .. })) => repo
+                    .as_ref()
+                    .ok_or_else(|| "git repository not found".to_string())
+                    .and_then(|repo| repo.find_blob(*blob).map_err(|e| e.to_string()))
+                    .and_then(|blob| {
+                        storage::write_bytes_atomic(&path, blob.content())
+                            .map_err(|e| e.to_string())
+                    }),
+                _ => Err("not kept in the snapshot".to_string()),
+            };
+            match result {
+                Ok(()) => violation.rolled_back = true,
+                Err(e) => tracing::warn!("Failed to roll back {}: {}", violation.path, e),
+            }
+        }
+
+        // Folders that didn't exist before, deepest first so nested ones go first
+        let mut created: Vec<&Path> = violations
+            .iter()
+            .filter(|v| v.kind == WriteKind::Created)
+            .flat_map(|v| Path::new(&v.path).ancestors().skip(1))
+            .filter(|dir| !dir.as_os_str().is_empty() && !self.dirs.contains(*dir))
+            .collect();
+        created.sort_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+        created.dedup();
+        for dir in created {
+            // Only empty folders; anything left in them was allowed
+            let _ = std::fs::remove_dir(self.root.join(dir));
+        }
+    }
+}
+
+fn violation(relative: &Path, kind: WriteKind) -> WriteViolation {
+    WriteViolation {
+        path: relative.to_string_lossy().replace('\\', "/"),
+        kind,
+        rolled_back: false,
+    }
+}
+
+fn changed(before: &SnapshotFile, path: &Path) -> bool {
+    match before {
+        SnapshotFile::Content(content) => std::fs::read(path).map_or(true, |now| now != *content),
+        // Touched but rewritten with the same content doesn't count
+        SnapshotFile::Tracked { stamp, blob } => {
+            std::fs::metadata(path).map_or(true, |m| FileStamp::of(&m) != *stamp)
+                && git2::Oid::hash_file(git2::ObjectType::Blob, path).ok() != Some(*blob)
+        }
+        SnapshotFile::Stamped(stamp) => {
+            std::fs::metadata(path).map_or(true, |m| FileStamp::of(&m) != *stamp)
+        }
+    }
+}
+
+/// Files and folders (workspace-relative) that an agent with `permissions` may not
+/// write and that aren't the app's own bookkeeping or in a skipped folder
+fn guarded_entries<'a>(
+    root: &'a Path,
+    permissions: &'a AgentPermissions,
+    config: &'a FilesConfig,
+) -> impl Iterator<Item = (PathBuf, walkdir::DirEntry)> + 'a {
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(move |e| {
+            let Ok(relative) = e.path().strip_prefix(root) else {
+                return false;
+            };
+            if e.file_type().is_dir() && config.skips(&e.file_name().to_string_lossy()) {
+                return false;
+            }
+            !relative.starts_with(".git")
+                || relative == Path::new(".git")
+                || relative == Path::new(".git/config")
+                || relative.starts_with(".git/hooks")
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() || e.file_type().is_dir())
+        .filter_map(move |e| {
+            let relative = e.path().strip_prefix(root).ok()?.to_path_buf();
+            let skip = relative.as_os_str().is_empty()
+                || relative == Path::new(".git")
+                || matches_any(APP_MANAGED, &relative)
+                || (e.file_type().is_file() && permissions.allows_write(&relative));
+            (!skip).then_some((relative, e))
+        })
+}
+
+/// Managed state that runs one agent at a time, so each run's writes can be told
+/// apart from another's
+pub struct AgentSandbox {
+    running: WorkspaceLocks,
+}
+
+impl AgentSandbox {
+    pub fn new() -> Self {
+        Self {
+            running: WorkspaceLocks::default(),
+        }
+    }
+
+    pub async fn exclusive(&self, workspace_id: &str) -> tokio::sync::OwnedMutexGuard<()> {
+        self.running.lock(workspace_id).await
+    }
+}
+
+impl Default for AgentSandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::DEFAULT_ALLOWED_TOOLS;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_writes_outside_allowlist_are_rolled_back() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        write(ws, "notes/a.md", "# A");
+        write(ws, "notes/b.md", "# B");
+        write(ws, ".chronicle/tags.json", "{}");
+        write(ws, ".git/hooks/pre-commit.sample", "sample");
+
+        let permissions = AgentPermissions::new(
+            to_strings(DEFAULT_ALLOWED_TOOLS),
+            vec![".chronicle/tags.json".to_string()],
+        );
+        let snapshot = WorkspaceSnapshot::capture(ws, &permissions);
+
+        // The agent writes its index, and also edits, deletes and creates files
+        write(ws, ".chronicle/tags.json", r#"{"a": 1}"#);
+        write(ws, "notes/a.md", "# Hijacked");
+        std::fs::remove_file(ws.join("notes/b.md")).unwrap();
+        write(ws, "evil/deep/payload.md", "x");
+        write(ws, ".git/hooks/pre-commit", "#!/bin/sh");
+        // Meanwhile the app records a session
+        write(ws, ".chronicle/sessions/run.json", "{}");
+
+        let mut violations = snapshot.violations(&permissions, &HashSet::new());
+        let found: Vec<_> = violations
+            .iter()
+            .map(|v| (v.path.as_str(), v.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (".git/hooks/pre-commit", WriteKind::Created),
+                ("evil/deep/payload.md", WriteKind::Created),
+                ("notes/a.md", WriteKind::Modified),
+                ("notes/b.md", WriteKind::Deleted),
+            ]
+        );
+
+        snapshot.roll_back(&mut violations);
+        assert!(violations.iter().all(|v| v.rolled_back));
+        assert_eq!(
+            std::fs::read_to_string(ws.join("notes/a.md")).unwrap(),
+            "# A"
+        );
+        assert_eq!(
+            std::fs::read_to_string(ws.join("notes/b.md")).unwrap(),
+            "# B"
+        );
+        assert!(!ws.join("evil").exists());
+        assert!(!ws.join(".git/hooks/pre-commit").exists());
+        assert_eq!(
+            std::fs::read_to_string(ws.join(".chronicle/tags.json")).unwrap(),
+            r#"{"a": 1}"#
+        );
+        assert!(ws.join(".chronicle/sessions/run.json").exists());
+    }
+
+    #[test]
+    fn test_tracked_files_restore_from_git() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        write(ws, "notes/a.md", "# A");
+        write(ws, "notes/b.md", "# B");
+        crate::git::init_repo_with_commit(ws, "Initial commit").unwrap();
+        write(ws, "notes/b.md", "# B, not committed yet");
+        write(ws, "notes/c.md", "# C, untracked");
+
+        let permissions = AgentPermissions::read_only();
+        let snapshot = WorkspaceSnapshot::capture(ws, &permissions);
+        // Only files git can't restore are kept
+        assert!(matches!(
+            snapshot.files[Path::new("notes/a.md")],
+            SnapshotFile::Tracked { .. }
+        ));
+        assert!(matches!(
+            snapshot.files[Path::new("notes/b.md")],
+            SnapshotFile::Content(_)
+        ));
+        assert!(matches!(
+            snapshot.files[Path::new("notes/c.md")],
+            SnapshotFile::Content(_)
+        ));
+
+        write(ws, "notes/a.md", "# Hijacked");
+        write(ws, "notes/b.md", "# Hijacked");
+        std::fs::remove_file(ws.join("notes/c.md")).unwrap();
+
+        let mut violations = snapshot.violations(&permissions, &HashSet::new());
+        assert_eq!(violations.len(), 3);
+        snapshot.roll_back(&mut violations);
+        assert!(violations.iter().all(|v| v.rolled_back));
+        let read = |path: &str| std::fs::read_to_string(ws.join(path)).unwrap();
+        assert_eq!(read("notes/a.md"), "# A");
+        assert_eq!(read("notes/b.md"), "# B, not committed yet");
+        assert_eq!(read("notes/c.md"), "# C, untracked");
+
+        // Rewriting a tracked file with the same content isn't a change
+        let snapshot = WorkspaceSnapshot::capture(ws, &permissions);
+        write(ws, "notes/a.md", "# Touched");
+        write(ws, "notes/a.md", "# A");
+        assert!(snapshot
+            .violations(&permissions, &HashSet::new())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_skipped_folders_and_snapshot_total() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        write(ws, "notes/a.md", "# A");
+        write(ws, "notes/b.md", "# B");
+        write(ws, "node_modules/pkg/index.js", "module.exports = 1");
+
+        let permissions = AgentPermissions::read_only();
+        let snapshot = WorkspaceSnapshot::capture_within(ws, &permissions, 3);
+        assert!(!snapshot.files.keys().any(|p| p.starts_with("node_modules")));
+        let kept = snapshot
+            .files
+            .values()
+            .filter(|f| matches!(f, SnapshotFile::Content(_)))
+            .count();
+        assert_eq!(kept, 1);
+
+        // Past the total, changes are still found though they can't be undone
+        write(ws, "notes/a.md", "# AA");
+        write(ws, "notes/b.md", "# BB");
+        write(ws, "node_modules/pkg/index.js", "module.exports = 2");
+        let mut violations = snapshot.violations(&permissions, &HashSet::new());
+        assert_eq!(violations.len(), 2);
+        snapshot.roll_back(&mut violations);
+        assert_eq!(violations.iter().filter(|v| v.rolled_back).count(), 1);
+    }
+
+    #[test]
+    fn test_read_only_and_excused_writes() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        write(ws, "a.md", "# A");
+        write(ws, "b.md", "# B");
+
+        // Declaring writes doesn't help without a write tool
+        let permissions =
+            AgentPermissions::new(to_strings(READ_ONLY_TOOLS), vec!["a.md".to_string()]);
+        let snapshot = WorkspaceSnapshot::capture(ws, &permissions);
+        write(ws, "a.md", "# Changed");
+        write(ws, "b.md", "# Edited by the user");
+
+        let excused = HashSet::from([PathBuf::from("b.md")]);
+        let violations = snapshot.violations(&permissions, &excused);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].path, "a.md");
+        assert!(permissions.denied_tools_arg().contains("Write"));
+    }
+
+    #[test]
+    fn test_command_permissions() {
+        let (permissions, body) = command_permissions("# Summary\n\nRead notes.\n").unwrap();
+        assert_eq!(permissions, AgentPermissions::read_only());
+        assert_eq!(body, "# Summary\n\nRead notes.\n");
+
+        let content = "---\ntools: [Read, Write]\nwrites: [.chronicle/digests/]\n---\n# Review\n";
+        let (permissions, body) = command_permissions(content).unwrap();
+        assert!(permissions.allows_write(Path::new(".chronicle/digests/week.md")));
+        assert!(!permissions.allows_write(Path::new(".chronicle/tags.json")));
+        assert_eq!(body, "# Review\n");
+
+        assert!(command_permissions("---\ntools: [Bash]\n---\nx").is_err());
+        for bad in [
+            "../outside.md",
+            "/etc/passwd",
+            ".git/hooks/",
+            "notes/../../x",
+        ] {
+            let content = format!("---\ntools: [Write]\nwrites: [\"{}\"]\n---\nx", bad);
+            assert!(command_permissions(&content).is_err(), "{}", bad);
+        }
+    }
+}
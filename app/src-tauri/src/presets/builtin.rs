@@ -0,0 +1,293 @@
+const ENGINEERING_MANAGER: &[(&str, &str)] = &[
+    (
+        "preset.toml",
+        r#"name = "Engineering manager"
+description = "1:1s, team rituals, hiring loops and incident follow-ups"
+folders = ["one-on-ones", "team", "projects", "hiring", "incidents"]
+
+[[context]]
+heading = "Team"
+hint = "Format: **Name** — Role, tenure, growth goals, how they like feedback"
+
+[[context]]
+heading = "Stakeholders"
+hint = "Format: **Name** — Team, what they need from us"
+
+[[context]]
+heading = "Active Projects"
+hint = "Format: **Project Name** — Status, owner, next milestone"
+
+[[context]]
+heading = "Team Rituals"
+hint = "Format: **Meeting Name** — Cadence, attendees, format"
+
+[[context]]
+heading = "Preferences"
+hint = "How you want Chronicle to process your notes"
+"#,
+    ),
+    (
+        "templates/team-one-on-one.md",
+        "---\ndate: {{date_iso}}\nattendees: []\ntags: [meeting:1on1]\n---\n# 1:1 with [Name] — {{date}}\n\n## How are things?\n\n## Their topics\n\n## My topics\n\n## Growth & feedback\n\n## Action Items\n",
+    ),
+    (
+        "templates/interview-debrief.md",
+        "---\ndate: {{date_iso}}\nattendees: []\ntags: [meeting:debrief, theme:hiring]\n---\n# Debrief: [Candidate] for [Role]\n\n## Signals\n\n## Concerns\n\n## Decision\n\n## Action Items\n",
+    ),
+    (
+        "templates/incident-review.md",
+        "---\ndate: {{date_iso}}\nattendees: []\ntags: [meeting:incident-review]\n---\n# Incident Review: [Incident]\n\n## Timeline\n\n## Impact\n\n## Root Cause\n\n## Decisions\n\n## Action Items\n",
+    ),
+    (
+        "prompts/digest.md",
+        r##"You are Chronicle's digest generator for an engineering manager. Your job is to summarize a period of notes about a team.
+
+Read .chronicle/context.md for the team, stakeholders and projects.
+Based on the requested range, find all processed notes in .chronicle/processed/ (including subfolders) within that timeframe.
+Also read .chronicle/actions.json for action item status and .chronicle/tags.json for topic mapping.
+Both refer to notes by ID; .chronicle/note-ids.json maps each note ID to its current path.
+
+Generate a digest covering:
+
+## Summary
+A short overview of the period for the team.
+
+## People
+For each report mentioned: what came up in 1:1s, wins, concerns and growth topics.
+
+## Projects
+Progress, risks and decisions per project.
+
+## Hiring
+Candidates discussed and decisions made.
+
+## Incidents
+Incidents reviewed and their follow-ups.
+
+## Action Items
+- **New**: Created during this period
+- **Completed**: Resolved during this period
+- **Overdue**: Open items older than {{stale_after_days}} days
+
+Write the digest as markdown to the specified output path.
+"##,
+    ),
+    (
+        "commands/team-health.md",
+        r#"---
+tools: [Read, Glob, Grep, Write]
+writes: [.chronicle/digests/]
+---
+# Team Health
+
+Review how each person on the team is doing.
+
+Read .chronicle/context.md for the team.
+Read the 1:1 notes in one-on-ones/ from the last {{days}} days (default: 30) and their processed versions in .chronicle/processed/.
+
+For each person:
+1. **Mood and workload** — as it came up in 1:1s
+2. **Recurring concerns** — raised more than once
+3. **Growth** — goals discussed and progress
+4. **Follow-ups I owe them** — open actions from .chronicle/actions.json
+
+Write the review to .chronicle/digests/team-health-{{date}}.md
+"#,
+    ),
+];
+
+const CONSULTING: &[(&str, &str)] = &[
+    (
+        "preset.toml",
+        r#"name = "Consulting"
+description = "Client meetings, workshops and deliverables across engagements"
+folders = ["clients", "engagements", "workshops", "proposals"]
+
+[[context]]
+heading = "Clients"
+hint = "Format: **Client** — Industry, main contacts, engagement status"
+
+[[context]]
+heading = "Contacts"
+hint = "Format: **Name** — Client, role, what they care about"
+
+[[context]]
+heading = "Engagements"
+hint = "Format: **Engagement** — Client, scope, deliverables, deadline"
+
+[[context]]
+heading = "Terminology"
+hint = "Client-specific terms, acronyms, shorthand. Format: **TERM** — Definition"
+
+[[context]]
+heading = "Preferences"
+hint = "How you want Chronicle to process your notes"
+"#,
+    ),
+    (
+        "templates/client-meeting.md",
+        "---\ndate: {{date_iso}}\nattendees: []\nproject: \ntags: [meeting:client]\n---\n# [Client] — {{date}}\n\n## Agenda\n\n## Notes\n\n## Decisions\n\n## Action Items\n\n## Follow-up\n",
+    ),
+    (
+        "templates/workshop.md",
+        "---\ndate: {{date_iso}}\nattendees: []\nproject: \ntags: [meeting:workshop]\n---\n# Workshop: [Topic]\n\n## Goals\n\n## Exercises\n\n## Outcomes\n\n## Action Items\n",
+    ),
+    (
+        "templates/status-report.md",
+        "---\ndate: {{date_iso}}\nproject: \ntags: [report:status]\n---\n# Status Report — {{date}}\n\n## Done\n\n## Next\n\n## Risks\n\n## Needs from the client\n",
+    ),
+    (
+        "prompts/digest.md",
+        r##"You are Chronicle's digest generator for a consultant. Your job is to summarize a period of notes across client engagements.
+
+Read .chronicle/context.md for clients, contacts and engagements.
+Based on the requested range, find all processed notes in .chronicle/processed/ (including subfolders) within that timeframe.
+Also read .chronicle/actions.json for action item status and .chronicle/tags.json for topic mapping.
+Both refer to notes by ID; .chronicle/note-ids.json maps each note ID to its current path.
+
+Generate a digest with one section per client:
+
+## [Client]
+- **Meetings** — one line each, ordered by date
+- **Decisions** — with source attribution
+- **Deliverables** — progress and upcoming deadlines
+- **Risks** — anything that threatens scope, timeline or the relationship
+
+Then:
+
+## Action Items
+- **Ours**: open items we owe clients
+- **Theirs**: open items clients owe us
+- **Overdue**: open items older than {{stale_after_days}} days
+
+Write the digest as markdown to the specified output path.
+"##,
+    ),
+    (
+        "commands/client-update.md",
+        r#"---
+tools: [Read, Glob, Grep, Write]
+writes: [.chronicle/digests/]
+---
+# Client Update
+
+Draft a status update for {{client}}.
+
+Read .chronicle/context.md for the engagement with {{client}}.
+Find the notes about {{client}} from the last {{days}} days (default: 14) and read their processed versions in .chronicle/processed/.
+
+Draft an update the client can read in two minutes:
+1. **Progress** — what was done since the last update
+2. **Decisions** — what was agreed
+3. **Next steps** — with owners and dates
+4. **Open questions** — what we need from them
+
+Write the draft to .chronicle/digests/update-{{client}}-{{date}}.md
+"#,
+    ),
+];
+
+const RESEARCH: &[(&str, &str)] = &[
+    (
+        "preset.toml",
+        r#"name = "Research"
+description = "Literature notes, experiments, interviews and findings"
+folders = ["literature", "experiments", "interviews", "findings"]
+
+[[context]]
+heading = "Research Questions"
+hint = "Format: **Question** — Why it matters, current hypothesis"
+
+[[context]]
+heading = "Sources"
+hint = "Format: **Author (Year)** — Title, key claim"
+
+[[context]]
+heading = "Collaborators"
+hint = "Format: **Name** — Affiliation, area, how we work together"
+
+[[context]]
+heading = "Terminology"
+hint = "Field-specific terms and definitions. Format: **TERM** — Definition"
+
+[[context]]
+heading = "Preferences"
+hint = "How you want Chronicle to process your notes"
+"#,
+    ),
+    (
+        "templates/literature-note.md",
+        "---\ndate: {{date_iso}}\ntags: [source:paper]\n---\n# [Author (Year)] — [Title]\n\n## Claim\n\n## Method\n\n## Evidence\n\n## Questions\n\n## Links to my work\n",
+    ),
+    (
+        "templates/experiment.md",
+        "---\ndate: {{date_iso}}\ntags: [experiment]\n---\n# Experiment: [Name]\n\n## Hypothesis\n\n## Setup\n\n## Results\n\n## Interpretation\n\n## Next steps\n",
+    ),
+    (
+        "templates/interview.md",
+        "---\ndate: {{date_iso}}\nattendees: []\ntags: [meeting:interview]\n---\n# Interview with [Participant]\n\n## Context\n\n## Notes\n\n## Quotes\n\n## Observations\n",
+    ),
+    (
+        "prompts/digest.md",
+        r##"You are Chronicle's digest generator for a researcher. Your job is to summarize a period of research notes.
+
+Read .chronicle/context.md for the research questions, sources and collaborators.
+Based on the requested range, find all processed notes in .chronicle/processed/ (including subfolders) within that timeframe.
+Also read .chronicle/actions.json for action item status and .chronicle/tags.json for topic mapping.
+Both refer to notes by ID; .chronicle/note-ids.json maps each note ID to its current path.
+
+Generate a digest covering:
+
+## Summary
+How the research moved forward in this period.
+
+## Findings
+New findings, each with the notes that support it.
+
+## Sources Read
+Literature notes from the period, one line each.
+
+## Experiments
+Experiments run and their results.
+
+## Open Questions
+Questions raised and not yet answered, grouped by research question.
+
+## Next Steps
+- **New**: Actions created during this period
+- **Overdue**: Open items older than {{stale_after_days}} days
+
+Write the digest as markdown to the specified output path.
+"##,
+    ),
+    (
+        "commands/synthesize-findings.md",
+        r#"---
+tools: [Read, Glob, Grep, Write]
+writes: [.chronicle/digests/]
+---
+# Synthesize Findings
+
+Bring together what the notes say about {{question}}.
+
+Read .chronicle/context.md for the research questions and sources.
+Search literature/, experiments/, interviews/ and findings/ for notes about {{question}} and read their processed versions in .chronicle/processed/.
+
+Produce a synthesis:
+1. **What we know** — claims with the notes and sources behind them
+2. **Where sources disagree**
+3. **Gaps** — what no note answers yet
+4. **Suggested next experiments or reading**
+
+Write the synthesis to .chronicle/digests/synthesis-{{date}}.md
+"#,
+    ),
+];
+
+/// Built-in presets by ID, each as preset.toml and its files, laid out like a
+/// shared preset directory
+pub(super) const BUILTIN_PRESETS: &[(&str, &[(&str, &str)])] = &[
+    ("engineering-manager", ENGINEERING_MANAGER),
+    ("consulting", CONSULTING),
+    ("research", RESEARCH),
+];
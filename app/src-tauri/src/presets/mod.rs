@@ -0,0 +1,4 @@
+mod builtin;
+mod preset;
+
+pub use preset::*;
@@ -0,0 +1,433 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use super::builtin::BUILTIN_PRESETS;
+use crate::storage::{self, generate_slug, get_app_data_dir, StorageError};
+
+/// A preset's description of itself, at the root of its directory
+pub const PRESET_MANIFEST: &str = "preset.toml";
+
+/// Extension of a preset packed into a single file to share it
+pub const PRESET_ARCHIVE_EXTENSION: &str = "chronicle-preset";
+
+/// Version of the archive format this build reads and writes
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Folders of .chronicle/ a preset brings files for
+const PRESET_DIRS: &[&str] = &["templates", "prompts", "commands"];
+
+/// Installed presets, in the app data directory
+const PRESETS_DIR: &str = "presets";
+
+/// A section of .chronicle/context.md
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextSection {
+    pub heading: String,
+    /// What belongs in the section, shown as a comment under the heading
+    #[serde(default)]
+    pub hint: String,
+}
+
+/// preset.toml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Folders created in the new workspace
+    #[serde(default)]
+    pub folders: Vec<String>,
+    /// Sections of context.md, in place of the default ones
+    #[serde(default)]
+    pub context: Vec<ContextSection>,
+}
+
+/// A workspace preset: its manifest and the .chronicle/ files it brings, keyed by
+/// paths like "templates/one-on-one.md". Prompts replace the default ones;
+/// templates and commands are added to the defaults.
+#[derive(Debug, Clone)]
+pub struct Preset {
+    pub id: String,
+    pub manifest: PresetManifest,
+    pub files: BTreeMap<String, String>,
+    pub builtin: bool,
+}
+
+/// A preset packed into one file
+#[derive(Serialize, Deserialize)]
+struct PresetArchive {
+    version: u32,
+    files: BTreeMap<String, String>,
+}
+
+/// Preset details for the workspace creation wizard
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub folders: Vec<String>,
+    pub templates: Vec<String>,
+    pub prompts: Vec<String>,
+    pub commands: Vec<String>,
+    pub builtin: bool,
+}
+
+fn invalid(message: impl Into<String>) -> StorageError {
+    StorageError::ParseError(PRESET_MANIFEST.to_string(), message.into())
+}
+
+/// Whether `path` is relative, stays inside the workspace and has no hidden parts
+fn is_plain_relative(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path).components().all(
+            |c| matches!(c, Component::Normal(part) if !part.to_string_lossy().starts_with('.')),
+        )
+}
+
+/// Whether `path` is a markdown file directly inside one of `PRESET_DIRS`
+fn is_preset_file(path: &str) -> bool {
+    let Some((dir, name)) = path.split_once('/') else {
+        return false;
+    };
+    PRESET_DIRS.contains(&dir)
+        && name.len() > ".md".len()
+        && name.ends_with(".md")
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\'])
+}
+
+/// ID of a preset read from a directory or archive with this name
+fn preset_id(path: &Path) -> String {
+    let name = if path.is_dir() {
+        path.file_name()
+    } else {
+        path.file_stem()
+    };
+    generate_slug(&name.unwrap_or_default().to_string_lossy())
+}
+
+impl Preset {
+    /// Build a preset from preset.toml and its files, checking that every path
+    /// stays inside the folders a preset may write
+    pub fn from_files(
+        id: &str,
+        mut files: BTreeMap<String, String>,
+        builtin: bool,
+    ) -> Result<Self, StorageError> {
+        let manifest = files
+            .remove(PRESET_MANIFEST)
+            .ok_or_else(|| invalid("file is missing"))?;
+        let manifest: PresetManifest =
+            toml::from_str(&manifest).map_err(|e| invalid(e.to_string()))?;
+
+        if id.is_empty() || manifest.name.trim().is_empty() {
+            return Err(invalid("the preset needs a name"));
+        }
+        if let Some(folder) = manifest.folders.iter().find(|f| !is_plain_relative(f)) {
+            return Err(invalid(format!(
+                "folders must be paths inside the workspace, not {:?}",
+                folder
+            )));
+        }
+        if manifest.context.iter().any(|s| s.heading.trim().is_empty()) {
+            return Err(invalid("context sections need a heading"));
+        }
+        if let Some(path) = files.keys().find(|p| !is_preset_file(p)) {
+            return Err(StorageError::InvalidPath(format!(
+                "Not a preset template, prompt or command: {}",
+                path
+            )));
+        }
+
+        Ok(Self {
+            id: id.to_string(),
+            manifest,
+            files,
+            builtin,
+        })
+    }
+
+    /// Read a preset directory: preset.toml and the markdown files in its
+    /// templates/, prompts/ and commands/ folders. Anything else is ignored.
+    pub fn from_dir(dir: &Path) -> Result<Self, StorageError> {
+        let mut files = BTreeMap::new();
+        files.insert(
+            PRESET_MANIFEST.to_string(),
+            storage::read_file(&dir.join(PRESET_MANIFEST))?,
+        );
+        for kind in PRESET_DIRS {
+            let Ok(entries) = std::fs::read_dir(dir.join(kind)) else {
+                continue;
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let key = format!("{}/{}", kind, entry.file_name().to_string_lossy());
+                if entry.path().is_file() && is_preset_file(&key) {
+                    files.insert(key, storage::read_file(&entry.path())?);
+                }
+            }
+        }
+        Self::from_files(&preset_id(dir), files, false)
+    }
+
+    /// Read a preset archive (see `to_archive`)
+    pub fn from_archive(path: &Path) -> Result<Self, StorageError> {
+        let archive: PresetArchive = serde_json::from_str(&storage::read_file(path)?)?;
+        if archive.version != ARCHIVE_VERSION {
+            return Err(StorageError::ParseError(
+                path.display().to_string(),
+                format!("unsupported preset version {}", archive.version),
+            ));
+        }
+        Self::from_files(&preset_id(path), archive.files, false)
+    }
+
+    /// Read a preset shared as a directory or an archive
+    pub fn read(path: &Path) -> Result<Self, StorageError> {
+        if path.is_dir() {
+            Self::from_dir(path)
+        } else {
+            Self::from_archive(path)
+        }
+    }
+
+    /// The preset packed into one file: JSON holding preset.toml and every file,
+    /// by path
+    pub fn to_archive(&self) -> Result<String, StorageError> {
+        let manifest = toml::to_string(&self.manifest)
+            .map_err(|e| StorageError::SerializeError(e.to_string()))?;
+        let mut files = self.files.clone();
+        files.insert(PRESET_MANIFEST.to_string(), manifest);
+        Ok(serde_json::to_string_pretty(&PresetArchive {
+            version: ARCHIVE_VERSION,
+            files,
+        })?)
+    }
+
+    /// Names of the files the preset brings for one .chronicle/ folder
+    fn names(&self, kind: &str) -> Vec<String> {
+        self.files
+            .keys()
+            .filter_map(|path| path.strip_prefix(kind)?.strip_prefix('/'))
+            .map(|name| name.trim_end_matches(".md").to_string())
+            .collect()
+    }
+
+    pub fn info(&self) -> PresetInfo {
+        PresetInfo {
+            id: self.id.clone(),
+            name: self.manifest.name.clone(),
+            description: self.manifest.description.clone(),
+            folders: self.manifest.folders.clone(),
+            templates: self.names("templates"),
+            prompts: self.names("prompts"),
+            commands: self.names("commands"),
+            builtin: self.builtin,
+        }
+    }
+
+    /// The preset's context.md sections, or None to keep the default ones
+    pub fn context_sections(&self) -> Option<String> {
+        if self.manifest.context.is_empty() {
+            return None;
+        }
+        let sections = self.manifest.context.iter().map(|s| {
+            if s.hint.is_empty() {
+                format!("## {}\n\n", s.heading.trim())
+            } else {
+                format!("## {}\n<!-- {} -->\n\n", s.heading.trim(), s.hint.trim())
+            }
+        });
+        Some(sections.collect())
+    }
+
+    /// Create the preset's folders in a workspace and write its files into
+    /// .chronicle/. Folders get a .gitkeep so the initial commit keeps them.
+    pub fn scaffold(&self, workspace_path: &Path) -> Result<(), StorageError> {
+        for folder in &self.manifest.folders {
+            let dir = workspace_path.join(folder);
+            storage::ensure_dir(&dir)?;
+            storage::write_file(&dir.join(".gitkeep"), "")?;
+        }
+        let chronicle_dir = workspace_path.join(".chronicle");
+        for (path, content) in &self.files {
+            storage::write_file(&chronicle_dir.join(path), content)?;
+        }
+        Ok(())
+    }
+}
+
+/// The presets Chronicle ships with
+pub fn builtin_presets() -> Vec<Preset> {
+    BUILTIN_PRESETS
+        .iter()
+        .map(|(id, files)| {
+            let files = files
+                .iter()
+                .map(|(path, content)| (path.to_string(), content.to_string()))
+                .collect();
+            Preset::from_files(id, files, true).expect("built-in presets are valid")
+        })
+        .collect()
+}
+
+fn presets_dir() -> Result<PathBuf, StorageError> {
+    get_app_data_dir()
+        .map(|dir| dir.join(PRESETS_DIR))
+        .ok_or_else(|| {
+            StorageError::InvalidPath("Could not determine app data directory".to_string())
+        })
+}
+
+/// Presets in `dir`, as directories or archives. Invalid ones are skipped.
+fn installed_presets(dir: &Path) -> Vec<Preset> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_dir() || p.extension().and_then(|e| e.to_str()) == Some(PRESET_ARCHIVE_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| match Preset::read(path) {
+            Ok(preset) => Some(preset),
+            Err(e) => {
+                tracing::warn!("Skipping preset {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// The built-in presets, then the installed ones. A preset named like one
+/// listed before it is left out.
+pub fn list_presets() -> Vec<Preset> {
+    let mut presets = builtin_presets();
+    if let Ok(dir) = presets_dir() {
+        for preset in installed_presets(&dir) {
+            if !presets.iter().any(|p| p.id == preset.id) {
+                presets.push(preset);
+            }
+        }
+    }
+    presets
+}
+
+pub fn find_preset(id: &str) -> Result<Preset, StorageError> {
+    list_presets()
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| StorageError::NotFound(format!("preset {}", id)))
+}
+
+/// Install a preset shared as a directory or archive. It is kept as an archive,
+/// replacing an installed preset of the same name.
+pub fn install_preset(source: &Path) -> Result<Preset, StorageError> {
+    let preset = Preset::read(source)?;
+    if builtin_presets().iter().any(|p| p.id == preset.id) {
+        return Err(StorageError::InvalidPath(format!(
+            "A built-in preset is already named {}",
+            preset.id
+        )));
+    }
+    let dir = presets_dir()?;
+    storage::ensure_dir(&dir)?;
+    let path = dir.join(format!("{}.{}", preset.id, PRESET_ARCHIVE_EXTENSION));
+    storage::write_file_atomic(&path, &preset.to_archive()?)?;
+    Ok(preset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_builtin_presets_are_valid() {
+        let presets = builtin_presets();
+        let ids: Vec<&str> = presets.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["engineering-manager", "consulting", "research"]);
+        for preset in &presets {
+            let info = preset.info();
+            assert!(
+                !info.folders.is_empty() && !info.templates.is_empty(),
+                "{}",
+                preset.id
+            );
+            assert!(preset.context_sections().is_some(), "{}", preset.id);
+        }
+    }
+
+    #[test]
+    fn test_preset_directory_round_trips_through_archive() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("Field Notes");
+        write(
+            &source.join(PRESET_MANIFEST),
+            "name = \"Field notes\"\nfolders = [\"sites\", \"interviews/2026\"]\n\n[[context]]\nheading = \"Sites\"\nhint = \"Where and when\"\n",
+        );
+        write(&source.join("templates/visit.md"), "# Visit {{date}}\n");
+        write(&source.join("prompts/digest.md"), "Summarize the visits.\n");
+        write(&source.join("README.md"), "Not part of the preset\n");
+
+        let preset = Preset::read(&source).unwrap();
+        assert_eq!(preset.id, "field-notes");
+        assert_eq!(preset.info().templates, vec!["visit"]);
+        assert_eq!(preset.info().prompts, vec!["digest"]);
+
+        let archive = dir.path().join("field-notes.chronicle-preset");
+        std::fs::write(&archive, preset.to_archive().unwrap()).unwrap();
+        let unpacked = Preset::read(&archive).unwrap();
+        assert_eq!(unpacked.id, preset.id);
+        assert_eq!(unpacked.manifest, preset.manifest);
+        assert_eq!(unpacked.files, preset.files);
+
+        let workspace = dir.path().join("workspace");
+        unpacked.scaffold(&workspace).unwrap();
+        assert!(workspace.join("interviews/2026/.gitkeep").exists());
+        assert_eq!(
+            std::fs::read_to_string(workspace.join(".chronicle/templates/visit.md")).unwrap(),
+            "# Visit {{date}}\n"
+        );
+        assert_eq!(
+            unpacked.context_sections().unwrap(),
+            "## Sites\n<!-- Where and when -->\n\n"
+        );
+    }
+
+    #[test]
+    fn test_presets_cannot_write_outside_their_folders() {
+        let manifest = |folders: &str| {
+            BTreeMap::from([(
+                PRESET_MANIFEST.to_string(),
+                format!("name = \"Bad\"\nfolders = {}\n", folders),
+            )])
+        };
+        assert!(Preset::from_files("bad", manifest("[\"notes\"]"), false).is_ok());
+        for folders in ["[\"../outside\"]", "[\"/etc\"]", "[\".chronicle\"]"] {
+            assert!(
+                Preset::from_files("bad", manifest(folders), false).is_err(),
+                "{}",
+                folders
+            );
+        }
+        for path in ["templates/../../x.md", "hooks/run.md", "prompts/notes.txt"] {
+            let mut files = manifest("[]");
+            files.insert(path.to_string(), String::new());
+            assert!(Preset::from_files("bad", files, false).is_err(), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod rules;
+mod service;
+
+pub use rules::*;
+pub use service::*;
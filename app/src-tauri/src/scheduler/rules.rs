@@ -0,0 +1,397 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use thiserror::Error;
+
+/// Schedule definition file inside .chronicle/
+pub const SCHEDULE_FILE: &str = "schedule.toml";
+
+/// Default schedule written to .chronicle/schedule.toml. Every job starts disabled.
+pub const DEFAULT_SCHEDULE: &str = r#"# Chronicle scheduled jobs.
+#
+# Each [[jobs]] entry runs an action either at a time of day or after you stop editing:
+#
+#   at                 - local time "HH:MM"; combine with days = ["mon", "fri", ...]
+#                        (every day when days is omitted)
+#   after_edit_minutes - run once notes have been idle this many minutes after an edit
+#
+# Actions:
+#   { type = "digest", range = "daily" | "weekly" | "monthly" }
+#   { type = "agents" }                                  - background agent pipeline
+#   { type = "command", command = "weekly-review.md" }   - a file in .chronicle/commands/
+#
+# Runs missed while Chronicle was closed are caught up once on the next launch.
+version = 1
+
+[[jobs]]
+name = "daily-digest"
+enabled = false
+at = "18:00"
+action = { type = "digest", range = "daily" }
+
+[[jobs]]
+name = "weekly-review"
+enabled = false
+at = "16:00"
+days = ["fri"]
+action = { type = "command", command = "weekly-review.md" }
+
+[[jobs]]
+name = "background-agents"
+enabled = false
+after_edit_minutes = 5
+action = { type = "agents" }
+"#;
+
+/// How late a time-of-day run can start before it counts as a catch-up
+const CATCH_UP_GRACE_MINUTES: i64 = 5;
+
+#[derive(Error, Debug)]
+pub enum ScheduleError {
+    #[error("Failed to read {0}: {1}")]
+    ReadFailed(String, #[source] std::io::Error),
+
+    #[error("Invalid schedule file: {0}")]
+    ParseError(String),
+
+    #[error("Unsupported schedule version: {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Duplicate job name: {0}")]
+    DuplicateJob(String),
+
+    #[error("Invalid job {0}: {1}")]
+    InvalidJob(String, String),
+}
+
+/// What a scheduled job does when it fires
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ScheduledAction {
+    Digest {
+        range: String,
+    },
+    Agents,
+    Command {
+        command: String,
+        #[serde(default)]
+        params: HashMap<String, String>,
+    },
+}
+
+impl ScheduledAction {
+    /// Short label used in the task list
+    pub fn label(&self) -> String {
+        match self {
+            ScheduledAction::Digest { range } => format!("{} digest", range),
+            ScheduledAction::Agents => "background agents".to_string(),
+            ScheduledAction::Command { command, .. } => {
+                format!("command {}", command.trim_end_matches(".md"))
+            }
+        }
+    }
+}
+
+/// A single job declared in schedule.toml
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledJob {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub action: ScheduledAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<Weekday>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_edit_minutes: Option<u32>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Why a job is due
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DueReason {
+    /// A time-of-day job reached its slot
+    Scheduled,
+    /// A time-of-day slot passed while Chronicle was closed
+    CatchUp,
+    /// Notes have been idle long enough after an edit
+    AfterEdit,
+}
+
+impl ScheduledJob {
+    fn time_of_day(&self) -> Option<NaiveTime> {
+        self.at
+            .as_deref()
+            .and_then(|at| NaiveTime::parse_from_str(at, "%H:%M").ok())
+    }
+
+    fn validate(&self) -> Result<(), ScheduleError> {
+        let invalid = |msg: &str| ScheduleError::InvalidJob(self.name.clone(), msg.to_string());
+
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(invalid("name must be letters, digits, '-' or '_'"));
+        }
+
+        match (&self.at, self.after_edit_minutes) {
+            (Some(_), Some(_)) => return Err(invalid("use either at or after_edit_minutes")),
+            (None, None) => return Err(invalid("needs at or after_edit_minutes")),
+            (Some(at), None) if self.time_of_day().is_none() => {
+                return Err(invalid(&format!("at must be HH:MM, got {:?}", at)))
+            }
+            (None, Some(0)) => return Err(invalid("after_edit_minutes must be at least 1")),
+            (None, Some(_)) if !self.days.is_empty() => {
+                return Err(invalid("days only applies to at"))
+            }
+            _ => {}
+        }
+
+        match &self.action {
+            ScheduledAction::Digest { range } => {
+                if !["daily", "weekly", "monthly"].contains(&range.as_str()) {
+                    return Err(invalid(&format!("unknown digest range {:?}", range)));
+                }
+            }
+            ScheduledAction::Command { command, .. } => {
+                if !command.ends_with(".md") || command.contains(['/', '\\']) {
+                    return Err(invalid("command must be a file name in .chronicle/commands/"));
+                }
+            }
+            ScheduledAction::Agents => {}
+        }
+
+        Ok(())
+    }
+
+    /// Most recent time-of-day slot at or before `now` (local time)
+    pub fn latest_slot(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let time = self.time_of_day()?;
+        (0..=7)
+            .map(|days_back| now.date() - Duration::days(days_back))
+            .filter(|date| self.days.is_empty() || self.days.contains(&date.weekday()))
+            .map(|date| date.and_time(time))
+            .find(|slot| *slot <= now)
+    }
+
+    /// Next time-of-day slot strictly after `now` (local time)
+    pub fn next_slot(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let time = self.time_of_day()?;
+        (0..=7)
+            .map(|days_ahead| now.date() + Duration::days(days_ahead))
+            .filter(|date| self.days.is_empty() || self.days.contains(&date.weekday()))
+            .map(|date| date.and_time(time))
+            .find(|slot| *slot > now)
+    }
+
+    /// Decide whether the job should run now.
+    ///
+    /// Time-of-day jobs run once per slot; a missed slot is caught up once, not once per
+    /// missed day. Edit-triggered jobs run once per burst of edits, after the idle delay.
+    /// `now`, `last_run` and `last_edit` are all local times.
+    pub fn due(
+        &self,
+        now: NaiveDateTime,
+        last_run: NaiveDateTime,
+        last_edit: Option<NaiveDateTime>,
+    ) -> Option<DueReason> {
+        if !self.enabled {
+            return None;
+        }
+
+        if let Some(minutes) = self.after_edit_minutes {
+            let edit = last_edit?;
+            let idle = now - edit >= Duration::minutes(minutes as i64);
+            return (idle && edit > last_run).then_some(DueReason::AfterEdit);
+        }
+
+        let slot = self.latest_slot(now)?;
+        if last_run >= slot {
+            return None;
+        }
+        if now - slot > Duration::minutes(CATCH_UP_GRACE_MINUTES) {
+            Some(DueReason::CatchUp)
+        } else {
+            Some(DueReason::Scheduled)
+        }
+    }
+}
+
+/// Scheduled jobs loaded from .chronicle/schedule.toml
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Schedule {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub jobs: Vec<ScheduledJob>,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+impl Schedule {
+    /// Parse and validate a schedule definition
+    pub fn parse(content: &str) -> Result<Self, ScheduleError> {
+        let schedule: Schedule =
+            toml::from_str(content).map_err(|e| ScheduleError::ParseError(e.to_string()))?;
+
+        if schedule.version != 1 {
+            return Err(ScheduleError::UnsupportedVersion(schedule.version));
+        }
+
+        let mut names = HashSet::new();
+        for job in &schedule.jobs {
+            job.validate()?;
+            if !names.insert(job.name.as_str()) {
+                return Err(ScheduleError::DuplicateJob(job.name.clone()));
+            }
+        }
+
+        Ok(schedule)
+    }
+
+    /// Load the workspace schedule; a workspace without one has no jobs
+    pub fn load(workspace_path: &Path) -> Result<Self, ScheduleError> {
+        let path = workspace_path.join(".chronicle").join(SCHEDULE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| ScheduleError::ReadFailed(path.display().to_string(), e))?;
+        Self::parse(&content)
+    }
+
+    pub fn job(&self, name: &str) -> Option<&ScheduledJob> {
+        self.jobs.iter().find(|j| j.name == name)
+    }
+}
+
+/// Convert a stored UTC timestamp to local wall-clock time for slot comparisons
+pub fn to_local_naive(ts: DateTime<Utc>) -> NaiveDateTime {
+    ts.with_timezone(&chrono::Local).naive_local()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    fn job(content: &str) -> ScheduledJob {
+        let schedule = Schedule::parse(&format!("[[jobs]]\n{}", content)).unwrap();
+        schedule.jobs.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_default_schedule_is_valid_and_disabled() {
+        let schedule = Schedule::parse(DEFAULT_SCHEDULE).unwrap();
+        assert_eq!(schedule.jobs.len(), 3);
+        assert!(schedule.jobs.iter().all(|j| !j.enabled));
+        assert_eq!(
+            schedule.job("weekly-review").unwrap().days,
+            vec![Weekday::Fri]
+        );
+    }
+
+    #[test]
+    fn test_daily_job_runs_once_per_slot() {
+        let j = job("name = \"d\"\nat = \"18:00\"\naction = { type = \"digest\", range = \"daily\" }");
+        let last_run = at(2026, 3, 2, 18, 0);
+
+        // Before today's slot: nothing to do
+        assert_eq!(j.due(at(2026, 3, 3, 17, 59), last_run, None), None);
+        // At the slot
+        assert_eq!(
+            j.due(at(2026, 3, 3, 18, 1), last_run, None),
+            Some(DueReason::Scheduled)
+        );
+        // Already ran for this slot
+        assert_eq!(j.due(at(2026, 3, 3, 18, 30), at(2026, 3, 3, 18, 1), None), None);
+    }
+
+    #[test]
+    fn test_missed_slots_caught_up_once() {
+        let j = job("name = \"d\"\nat = \"18:00\"\naction = { type = \"digest\", range = \"daily\" }");
+        // App closed for three days; opened the next morning
+        let now = at(2026, 3, 6, 9, 0);
+        assert_eq!(
+            j.due(now, at(2026, 3, 2, 18, 0), None),
+            Some(DueReason::CatchUp)
+        );
+        // After the catch-up run it waits for the next slot
+        assert_eq!(j.due(at(2026, 3, 6, 9, 5), now, None), None);
+    }
+
+    #[test]
+    fn test_weekly_job_respects_days() {
+        let j = job(
+            "name = \"w\"\nat = \"16:00\"\ndays = [\"fri\"]\naction = { type = \"command\", command = \"weekly-review.md\" }",
+        );
+        // 2026-03-06 is a Friday
+        let thursday = at(2026, 3, 5, 16, 30);
+        assert_eq!(j.latest_slot(thursday), Some(at(2026, 2, 27, 16, 0)));
+        assert_eq!(j.next_slot(thursday), Some(at(2026, 3, 6, 16, 0)));
+        assert_eq!(j.due(thursday, at(2026, 2, 27, 16, 0), None), None);
+        assert_eq!(
+            j.due(at(2026, 3, 6, 16, 0), at(2026, 2, 27, 16, 0), None),
+            Some(DueReason::Scheduled)
+        );
+    }
+
+    #[test]
+    fn test_after_edit_job_waits_for_idle() {
+        let j = job("name = \"a\"\nafter_edit_minutes = 5\naction = { type = \"agents\" }");
+        let last_run = at(2026, 3, 3, 9, 0);
+        let edit = at(2026, 3, 3, 10, 0);
+
+        assert_eq!(j.due(at(2026, 3, 3, 10, 3), last_run, Some(edit)), None);
+        assert_eq!(
+            j.due(at(2026, 3, 3, 10, 5), last_run, Some(edit)),
+            Some(DueReason::AfterEdit)
+        );
+        // No edits since the last run
+        assert_eq!(j.due(at(2026, 3, 3, 11, 0), at(2026, 3, 3, 10, 5), Some(edit)), None);
+        assert_eq!(j.due(at(2026, 3, 3, 11, 0), last_run, None), None);
+    }
+
+    #[test]
+    fn test_disabled_job_never_due() {
+        let j = job("name = \"d\"\nenabled = false\nat = \"00:00\"\naction = { type = \"agents\" }");
+        assert_eq!(j.due(at(2026, 3, 3, 12, 0), at(2020, 1, 1, 0, 0), None), None);
+    }
+
+    #[test]
+    fn test_invalid_jobs_rejected() {
+        let cases = [
+            "name = \"x\"\naction = { type = \"agents\" }",
+            "name = \"x\"\nat = \"25:00\"\naction = { type = \"agents\" }",
+            "name = \"x\"\nat = \"10:00\"\nafter_edit_minutes = 5\naction = { type = \"agents\" }",
+            "name = \"x\"\nat = \"10:00\"\naction = { type = \"digest\", range = \"hourly\" }",
+            "name = \"x\"\nat = \"10:00\"\naction = { type = \"command\", command = \"../evil.md\" }",
+        ];
+        for case in cases {
+            assert!(
+                Schedule::parse(&format!("[[jobs]]\n{}", case)).is_err(),
+                "accepted: {}",
+                case
+            );
+        }
+    }
+}
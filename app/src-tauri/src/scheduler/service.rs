@@ -0,0 +1,249 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{to_local_naive, DueReason, Schedule, ScheduledAction, ScheduledJob};
+use crate::commands::{
+    generate_digest, pipeline_failure, run_background_agents, run_custom_command,
+};
+use crate::pipeline::{changed_files, AgentScope};
+use crate::storage::{self, StorageError};
+use crate::tasks::{TaskList, TaskTrigger};
+use crate::SharedAppState;
+
+/// Last run per scheduled job, kept in .chronicle/
+pub const SCHEDULE_RUNS_FILE: &str = "schedule-runs.json";
+
+/// How often the scheduler checks for due jobs
+const TICK_SECONDS: u64 = 30;
+
+impl From<DueReason> for TaskTrigger {
+    fn from(reason: DueReason) -> Self {
+        match reason {
+            DueReason::Scheduled => TaskTrigger::Scheduled,
+            DueReason::CatchUp => TaskTrigger::CatchUp,
+            DueReason::AfterEdit => TaskTrigger::AfterEdit,
+        }
+    }
+}
+
+/// Read the last run time of every scheduled job
+pub fn read_schedule_runs(workspace_path: &Path) -> HashMap<String, DateTime<Utc>> {
+    storage::read_timestamps(&workspace_path.join(".chronicle").join(SCHEDULE_RUNS_FILE))
+}
+
+fn record_schedule_run(
+    workspace_path: &Path,
+    job: &str,
+    at: DateTime<Utc>,
+) -> Result<(), StorageError> {
+    storage::write_timestamp(
+        &workspace_path.join(".chronicle").join(SCHEDULE_RUNS_FILE),
+        job,
+        at,
+    )
+}
+
+/// Managed state that keeps a scheduler loop alive for each open, trusted workspace
+pub struct Scheduler {
+    inner: Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start checking a workspace's schedule, replacing its previous loop.
+    /// The first check runs immediately so missed runs are caught up on launch.
+    pub fn start(
+        &self,
+        workspace_id: &str,
+        workspace_path: &str,
+        app_handle: AppHandle,
+    ) -> Result<(), String> {
+        self.stop(workspace_id);
+
+        let id = workspace_id.to_string();
+        let workspace_path = workspace_path.to_string();
+        let handle = tauri::async_runtime::spawn(async move {
+            // Edits made while Chronicle was closed count towards after-edit jobs.
+            // Looked up once, the first time an after-edit job is enabled.
+            let mut launch_edit = None;
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+            loop {
+                interval.tick().await;
+                run_due_jobs(&app_handle, &id, &workspace_path, &mut launch_edit).await;
+            }
+        });
+
+        let mut guard = self.inner.lock().map_err(|e| e.to_string())?;
+        guard.insert(workspace_id.to_string(), handle);
+        tracing::info!("Started scheduler");
+
+        Ok(())
+    }
+
+    /// Stop a workspace's scheduler loop
+    pub fn stop(&self, workspace_id: &str) {
+        if let Ok(mut guard) = self.inner.lock() {
+            if let Some(handle) = guard.remove(workspace_id) {
+                handle.abort();
+                tracing::info!("Stopped scheduler");
+            }
+        }
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Modification time of the most recently edited note
+fn newest_note_mtime(workspace_path: &Path) -> Option<DateTime<Utc>> {
+    changed_files(workspace_path, AgentScope::Notes)
+        .iter()
+        .filter_map(|f| workspace_path.join(f).metadata().ok()?.modified().ok())
+        .max()
+        .map(DateTime::<Utc>::from)
+}
+
+/// Check the schedule once and run every due job, one at a time
+async fn run_due_jobs(
+    app_handle: &AppHandle,
+    workspace_id: &str,
+    workspace_path: &str,
+    launch_edit: &mut Option<Option<DateTime<Utc>>>,
+) {
+    let workspace = Path::new(workspace_path);
+    let schedule = match Schedule::load(workspace) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!("Skipping scheduled jobs: {}", e);
+            return;
+        }
+    };
+    if schedule.jobs.iter().all(|j| !j.enabled) {
+        return;
+    }
+    let waits_for_edits = schedule
+        .jobs
+        .iter()
+        .any(|j| j.enabled && j.after_edit_minutes.is_some());
+    if waits_for_edits && launch_edit.is_none() {
+        let path = workspace.to_path_buf();
+        *launch_edit = Some(
+            tokio::task::spawn_blocking(move || newest_note_mtime(&path))
+                .await
+                .unwrap_or_default(),
+        );
+    }
+
+    let session_edit = match app_handle.try_state::<SharedAppState>() {
+        Some(state) => state
+            .read()
+            .await
+            .workspaces
+            .get(workspace_id)
+            .and_then(|w| w.last_edited_at),
+        None => None,
+    };
+    let last_edit = session_edit.max(launch_edit.flatten()).map(to_local_naive);
+
+    let runs = read_schedule_runs(workspace);
+    let now = Utc::now();
+
+    for job in schedule.jobs.iter().filter(|j| j.enabled) {
+        let Some(last_run) = runs.get(&job.name) else {
+            // First time this job is enabled: start counting from now rather than
+            // treating every past slot as missed.
+            if let Err(e) = record_schedule_run(workspace, &job.name, now) {
+                tracing::warn!("Failed to record schedule baseline for {}: {}", job.name, e);
+            }
+            continue;
+        };
+
+        if let Some(reason) = job.due(to_local_naive(now), to_local_naive(*last_run), last_edit)
+        {
+            tracing::info!("Running scheduled job {} ({:?})", job.name, reason);
+            let _ = run_job(app_handle, workspace_path, job, reason.into()).await;
+        }
+    }
+}
+
+/// Run a scheduled job's action, tracking it in the task list.
+/// The run is recorded even when it fails so a broken job doesn't retry every tick.
+pub async fn run_job(
+    app_handle: &AppHandle,
+    workspace_path: &str,
+    job: &ScheduledJob,
+    trigger: TaskTrigger,
+) -> Result<(), String> {
+    let started_at = Utc::now();
+    let tasks = app_handle.state::<TaskList>();
+    let record = tasks.start(&job.name, &job.action.label(), workspace_path, trigger);
+    app_handle.emit("tasks:updated", record.clone()).ok();
+
+    let result = match &job.action {
+        ScheduledAction::Digest { range } => generate_digest(
+            app_handle.clone(),
+            workspace_path.to_string(),
+            range.clone(),
+            None,
+            None,
+            app_handle.state(),
+        )
+        .await
+        .and_then(|r| {
+            if r.success {
+                Ok(())
+            } else {
+                Err(r.error.unwrap_or_else(|| "Digest generation failed".to_string()))
+            }
+        }),
+        ScheduledAction::Agents => {
+            run_background_agents(
+                app_handle.clone(),
+                workspace_path.to_string(),
+                app_handle.state(),
+            )
+            .await
+            .and_then(|outcomes| match pipeline_failure(&outcomes) {
+                Some(failure) => Err(failure),
+                None => Ok(()),
+            })
+        }
+        ScheduledAction::Command { command, params } => run_custom_command(
+            app_handle.clone(),
+            workspace_path.to_string(),
+            command.clone(),
+            params.clone(),
+            app_handle.state(),
+        )
+        .await
+        .and_then(|r| {
+            if r.success {
+                Ok(())
+            } else {
+                Err(r.error.unwrap_or_else(|| "Command failed".to_string()))
+            }
+        }),
+    };
+
+    if let Err(e) = record_schedule_run(Path::new(workspace_path), &job.name, started_at) {
+        tracing::warn!("Failed to record run of {}: {}", job.name, e);
+    }
+
+    if let Some(done) = tasks.finish(record.id, result.clone()) {
+        app_handle.emit("tasks:updated", done).ok();
+    }
+
+    result
+}
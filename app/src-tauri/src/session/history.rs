@@ -0,0 +1,334 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use walkdir::WalkDir;
+
+use super::NoteTracker;
+use crate::notes::{self, NoteRegistry};
+use crate::storage::{self, MarkerCounts, NoteMeta, SessionMeta, StorageError};
+
+/// What changed in a note's content over a session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentChanges {
+    pub words_added: u32,
+    pub words_removed: u32,
+    /// Markers on added lines
+    pub markers: MarkerCounts,
+}
+
+/// Lines added and removed between two versions, ignoring moves
+fn line_changes<'a>(before: &'a str, after: &'a str) -> (Vec<&'a str>, Vec<&'a str>) {
+    let mut counts: HashMap<&str, isize> = HashMap::new();
+    for line in before.lines() {
+        *counts.entry(line.trim_end()).or_default() -= 1;
+    }
+    for line in after.lines() {
+        *counts.entry(line.trim_end()).or_default() += 1;
+    }
+
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    for (line, count) in counts {
+        let target = if count > 0 { &mut added } else { &mut removed };
+        target.extend(std::iter::repeat_n(line, count.unsigned_abs()));
+    }
+    (added, removed)
+}
+
+fn count_marker(markers: &mut MarkerCounts, line: &str) {
+    let line = line.trim_start();
+    let lower = line.to_ascii_lowercase();
+    if line.starts_with("> ") {
+        markers.highlights += 1;
+    } else if line.starts_with("! ") {
+        markers.decisions += 1;
+    } else if line.starts_with("? ") {
+        markers.questions += 1;
+    } else if line.starts_with("[] ") || line.starts_with("[ ] ") {
+        markers.actions += 1;
+    } else if lower.starts_with("[x] ") {
+        markers.completed_actions += 1;
+    } else if line
+        .strip_prefix('@')
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| c.is_alphanumeric() || c == '_')
+    {
+        markers.mentions += 1;
+    }
+}
+
+/// Words and markers added and removed between two versions of a note
+pub fn content_changes(before: &str, after: &str) -> ContentChanges {
+    let (added, removed) = line_changes(before, after);
+    let words = |lines: &[&str]| {
+        lines
+            .iter()
+            .map(|l| l.split_whitespace().count() as u32)
+            .sum()
+    };
+
+    let mut markers = MarkerCounts::default();
+    for line in &added {
+        count_marker(&mut markers, line);
+    }
+    ContentChanges {
+        words_added: words(&added),
+        words_removed: words(&removed),
+        markers,
+    }
+}
+
+/// Build the session record for a finished tracker. `content` is the note as it is
+/// now. The duration is active editing time, excluding idle and paused stretches.
+/// Returns None for sessions with no active time and no changes.
+pub fn session_record(
+    tracker: &NoteTracker,
+    ended_at: DateTime<Utc>,
+    idle_timeout: Duration,
+    content: Option<&str>,
+) -> Option<SessionMeta> {
+    let changes = match (tracker.initial_content.as_deref(), content) {
+        (Some(before), Some(after)) => content_changes(before, after),
+        // New note: everything in it was written this session
+        (None, Some(after)) => content_changes("", after),
+        _ => ContentChanges::default(),
+    };
+    let duration_minutes = tracker.active_minutes_at(ended_at, idle_timeout);
+    if duration_minutes == 0 && changes == ContentChanges::default() {
+        return None;
+    }
+
+    let annotation_count = changes.markers.total();
+    Some(SessionMeta {
+        started_at: tracker.opened_at,
+        ended_at: Some(ended_at),
+        duration_minutes,
+        annotation_count,
+        last_annotation_at: (annotation_count > 0).then_some(ended_at),
+        words_added: changes.words_added,
+        words_removed: changes.words_removed,
+        markers: changes.markers,
+    })
+}
+
+/// Append a session to a note's metadata history
+pub fn record_session(
+    workspace_path: &Path,
+    note_path: &Path,
+    session: SessionMeta,
+) -> Result<(), StorageError> {
+    let mut meta = storage::load_metadata(workspace_path, note_path)?
+        .unwrap_or_else(|| NoteMeta::new(note_path));
+    meta.record_session(session);
+    storage::save_metadata(workspace_path, note_path, &meta)?;
+
+    if let Some(relative) = storage::relative_note_path(workspace_path, note_path) {
+        notes::ensure_note_id(workspace_path, &relative)?;
+    }
+    Ok(())
+}
+
+/// Time spent on one note
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteTime {
+    pub path: String,
+    pub id: String,
+    pub minutes: u32,
+    pub sessions: u32,
+    pub words_added: u32,
+    pub words_removed: u32,
+    pub last_session_at: Option<DateTime<Utc>>,
+}
+
+/// Time spent on one day
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayTime {
+    pub date: NaiveDate,
+    pub minutes: u32,
+    pub sessions: u32,
+    pub notes: u32,
+}
+
+/// Time-spent statistics over recorded sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeStats {
+    pub total_minutes: u32,
+    pub sessions: u32,
+    /// Most time spent first
+    pub notes: Vec<NoteTime>,
+    /// Oldest first
+    pub days: Vec<DayTime>,
+}
+
+/// Every note's metadata in .chronicle/meta/, with the note's current path
+pub fn load_all_metadata(workspace_path: &Path) -> Vec<(String, NoteMeta)> {
+    let meta_dir = workspace_path.join(".chronicle").join(storage::META_DIR);
+    let registry = NoteRegistry::load(workspace_path);
+
+    WalkDir::new(&meta_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .filter_map(|e| {
+            let content = std::fs::read_to_string(e.path()).ok()?;
+            let meta: NoteMeta = serde_json::from_str(&content).ok()?;
+            let path = match registry.path_of(&meta.id) {
+                Some(path) => path.to_string(),
+                None => format!("{}.md", storage::note_key(&meta_dir, e.path()).ok()?),
+            };
+            Some((path, meta))
+        })
+        .collect()
+}
+
+/// Per-note and per-day time spent, for sessions started between `from` and `to`
+/// (inclusive, as dates in `tz`). Days are counted in `tz` by session start.
+pub fn time_stats<Tz: TimeZone>(
+    workspace_path: &Path,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    tz: &Tz,
+) -> TimeStats {
+    let mut stats = TimeStats::default();
+    let mut days: BTreeMap<NaiveDate, (DayTime, Vec<String>)> = BTreeMap::new();
+
+    for (path, meta) in load_all_metadata(workspace_path) {
+        let mut note = NoteTime {
+            path: path.clone(),
+            id: meta.id.clone(),
+            ..Default::default()
+        };
+
+        for session in &meta.sessions {
+            let date = session.started_at.with_timezone(tz).date_naive();
+            if from.is_some_and(|from| date < from) || to.is_some_and(|to| date > to) {
+                continue;
+            }
+
+            note.minutes += session.duration_minutes;
+            note.sessions += 1;
+            note.words_added += session.words_added;
+            note.words_removed += session.words_removed;
+            note.last_session_at = note.last_session_at.max(Some(session.started_at));
+
+            let (day, day_notes) = days.entry(date).or_insert_with(|| {
+                (
+                    DayTime {
+                        date,
+                        ..Default::default()
+                    },
+                    Vec::new(),
+                )
+            });
+            day.minutes += session.duration_minutes;
+            day.sessions += 1;
+            if !day_notes.contains(&path) {
+                day_notes.push(path.clone());
+                day.notes += 1;
+            }
+        }
+
+        if note.sessions > 0 {
+            stats.total_minutes += note.minutes;
+            stats.sessions += note.sessions;
+            stats.notes.push(note);
+        }
+    }
+
+    stats
+        .notes
+        .sort_by(|a, b| b.minutes.cmp(&a.minutes).then_with(|| a.path.cmp(&b.path)));
+    stats.days = days.into_values().map(|(day, _)| day).collect();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_content_changes() {
+        let before = "# Standup\n\nSome notes here\n";
+        let after = "# Standup\n\nSome notes\n> Key point\n[] Send the report\n@sarah owns this\n";
+
+        let changes = content_changes(before, after);
+        assert_eq!(changes.words_removed, 3);
+        assert_eq!(changes.words_added, 2 + 3 + 4 + 3);
+        assert_eq!(changes.markers.highlights, 1);
+        assert_eq!(changes.markers.actions, 1);
+        assert_eq!(changes.markers.mentions, 1);
+        assert_eq!(changes.markers.total(), 3);
+    }
+
+    #[test]
+    fn test_sessions_recorded_and_aggregated() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let note = ws.join("standup.md");
+        std::fs::write(&note, "# Standup\n").unwrap();
+
+        let timeout = Duration::minutes(5);
+        let day = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        for (start, minutes) in [
+            (day, 25),
+            (day + Duration::hours(3), 10),
+            (day + Duration::days(1), 5),
+        ] {
+            let mut tracker = NoteTracker::opened(
+                note.display().to_string(),
+                start,
+                Some("# Standup\n".to_string()),
+            );
+            for minute in 1..=minutes {
+                tracker.heartbeat(start + Duration::minutes(minute), timeout);
+            }
+            // Left open for an hour after the last edit
+            let session = session_record(
+                &tracker,
+                start + Duration::minutes(minutes + 60),
+                timeout,
+                Some("# Standup\n! Ship it\n"),
+            )
+            .unwrap();
+            record_session(ws, &note, session).unwrap();
+        }
+
+        let meta = storage::load_metadata(ws, &note).unwrap().unwrap();
+        assert_eq!(meta.sessions.len(), 3);
+        assert_eq!(meta.session.unwrap().markers.decisions, 1);
+
+        let stats = time_stats(ws, None, None, &Utc);
+        assert_eq!(stats.total_minutes, 40);
+        assert_eq!(stats.sessions, 3);
+        assert_eq!(stats.notes[0].path, "standup.md");
+        assert_eq!(stats.days.len(), 2);
+        assert_eq!(stats.days[0].minutes, 35);
+        assert_eq!(stats.days[0].sessions, 2);
+
+        // Date range filters by session start
+        let first_day = day.date_naive();
+        let stats = time_stats(ws, Some(first_day + Duration::days(1)), None, &Utc);
+        assert_eq!(stats.total_minutes, 5);
+    }
+
+    #[test]
+    fn test_idle_open_is_not_recorded() {
+        let opened_at = Utc::now();
+        let tracker = NoteTracker::opened(
+            "standup.md".to_string(),
+            opened_at,
+            Some("# Standup\n".to_string()),
+        );
+        // Opened overnight without a single edit
+        let ended_at = opened_at + Duration::hours(12);
+        let timeout = Duration::minutes(5);
+        assert!(session_record(&tracker, ended_at, timeout, Some("# Standup\n")).is_none());
+    }
+}
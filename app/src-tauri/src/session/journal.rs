@@ -0,0 +1,341 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::{record_session, session_record, NoteTracker};
+use crate::storage::{self, StorageError};
+
+/// In-progress session journals, kept in .chronicle/
+pub const SESSIONS_DIR: &str = "sessions";
+
+/// Snapshot of a tracked session, rewritten on every heartbeat so a crash loses at
+/// most one heartbeat interval of time and unsaved text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionJournal {
+    /// App run that wrote the journal; journals from other runs are orphaned
+    pub run_id: String,
+    pub pane_id: String,
+    pub tracker: NoteTracker,
+    /// Note content when tracking started
+    pub initial_content: Option<String>,
+    /// Editor content not yet saved to the note, if any
+    pub unsaved_content: Option<String>,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl SessionJournal {
+    pub fn new(
+        run_id: &str,
+        pane_id: &str,
+        tracker: &NoteTracker,
+        unsaved_content: Option<String>,
+    ) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            pane_id: pane_id.to_string(),
+            tracker: tracker.clone(),
+            initial_content: tracker.initial_content.clone(),
+            unsaved_content,
+            saved_at: Utc::now(),
+        }
+    }
+
+    /// Journal file name: one per run and pane
+    pub fn id(&self) -> String {
+        journal_id(&self.run_id, &self.pane_id)
+    }
+
+    /// The tracker with its initial content restored
+    fn restored_tracker(&self) -> NoteTracker {
+        let mut tracker = self.tracker.clone();
+        tracker.initial_content = self.initial_content.clone();
+        tracker
+    }
+}
+
+/// Journal ID from run and pane, limited to file-name-safe characters
+pub fn journal_id(run_id: &str, pane_id: &str) -> String {
+    let pane: String = pane_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}-{}", run_id, pane)
+}
+
+fn journal_path(workspace_path: &Path, id: &str) -> Result<PathBuf, StorageError> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(StorageError::InvalidPath(format!("session journal {}", id)));
+    }
+    Ok(workspace_path
+        .join(".chronicle")
+        .join(SESSIONS_DIR)
+        .join(format!("{}.json", id)))
+}
+
+pub fn write_journal(workspace_path: &Path, journal: &SessionJournal) -> Result<(), StorageError> {
+    let content = serde_json::to_string_pretty(journal)?;
+    storage::write_file_atomic(&journal_path(workspace_path, &journal.id())?, &content)
+}
+
+/// Remove a journal. Missing journals are ignored.
+pub fn remove_journal(workspace_path: &Path, id: &str) -> Result<(), StorageError> {
+    let path = journal_path(workspace_path, id)?;
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(StorageError::WriteFailed(path.display().to_string(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn read_journal(workspace_path: &Path, id: &str) -> Result<SessionJournal, StorageError> {
+    let path = journal_path(workspace_path, id)?;
+    let content = storage::read_file(&path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| StorageError::ParseError(path.display().to_string(), e.to_string()))
+}
+
+/// Journals left behind by earlier runs (the app quit or crashed mid-session)
+pub fn orphaned_journals(workspace_path: &Path, run_id: &str) -> Vec<SessionJournal> {
+    let dir = workspace_path.join(".chronicle").join(SESSIONS_DIR);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut journals: Vec<SessionJournal> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .filter_map(|e| {
+            let content = std::fs::read_to_string(e.path()).ok()?;
+            match serde_json::from_str::<SessionJournal>(&content) {
+                Ok(journal) => Some(journal),
+                Err(err) => {
+                    tracing::warn!(
+                        "Skipping unreadable session journal {}: {}",
+                        e.path().display(),
+                        err
+                    );
+                    None
+                }
+            }
+        })
+        .filter(|j| j.run_id != run_id)
+        .collect();
+    journals.sort_by_key(|j| j.tracker.opened_at);
+    journals
+}
+
+/// An orphaned session as offered to the user for recovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedSession {
+    pub id: String,
+    pub note_path: String,
+    pub opened_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
+    /// Active editing time up to the last heartbeat
+    pub duration_minutes: u32,
+    pub has_unsaved_content: bool,
+}
+
+impl OrphanedSession {
+    pub fn new(journal: &SessionJournal, idle_timeout: Duration) -> Self {
+        let tracker = &journal.tracker;
+        Self {
+            id: journal.id(),
+            note_path: tracker.note_path.clone(),
+            opened_at: tracker.opened_at,
+            last_activity_at: tracker.last_activity_at,
+            duration_minutes: tracker.active_minutes_at(tracker.last_activity_at, idle_timeout),
+            has_unsaved_content: journal.unsaved_content.is_some(),
+        }
+    }
+}
+
+/// Outcome of recovering an orphaned session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveredSession {
+    /// Note the session belongs to (where unsaved content went, if restored)
+    pub note_path: PathBuf,
+    pub duration_minutes: u32,
+    /// Unsaved content was written here
+    pub restored_to: Option<PathBuf>,
+    /// Commit recording the recovered session, if one was made
+    pub commit_id: Option<String>,
+}
+
+/// Where to write unsaved content: the note itself, unless it is gone or was
+/// changed after the journal was written, in which case a new file next to it
+fn restore_target(
+    workspace_path: &Path,
+    journal: &SessionJournal,
+    content: &str,
+) -> Result<PathBuf, StorageError> {
+    let note = Path::new(&journal.tracker.note_path);
+    if !note.is_file() || !note.starts_with(workspace_path) {
+        return Ok(storage::new_note_path(workspace_path, None, content, None));
+    }
+
+    let modified_since = std::fs::metadata(note)
+        .and_then(|m| m.modified())
+        .map(|t| DateTime::<Utc>::from(t) > journal.saved_at)
+        .unwrap_or(false);
+    if !modified_since {
+        return Ok(note.to_path_buf());
+    }
+
+    let stem = note.file_stem().and_then(|s| s.to_str()).unwrap_or("note");
+    storage::available_path(note, &note.with_file_name(format!("{}.recovered.md", stem)))
+}
+
+/// Recover an orphaned session: optionally write its unsaved content back, record
+/// the session with its active duration (up to the last heartbeat) in the note's
+/// history, and remove the journal
+pub fn recover_journal(
+    workspace_path: &Path,
+    journal: &SessionJournal,
+    restore_content: bool,
+    idle_timeout: Duration,
+) -> Result<RecoveredSession, StorageError> {
+    let mut tracker = journal.restored_tracker();
+
+    let restored_to = match journal.unsaved_content.as_deref() {
+        Some(content) if restore_content => {
+            let target = restore_target(workspace_path, journal, content)?;
+            storage::write_file_atomic(&target, content)?;
+            Some(target)
+        }
+        _ => None,
+    };
+    if let Some(target) = &restored_to {
+        tracker.note_path = target.display().to_string();
+    }
+
+    let note_path = PathBuf::from(&tracker.note_path);
+    let ended_at = tracker.last_activity_at;
+    let duration_minutes = tracker.active_minutes_at(ended_at, idle_timeout);
+    if note_path.is_file() {
+        let content = std::fs::read_to_string(&note_path).ok();
+        if let Some(session) = session_record(&tracker, ended_at, idle_timeout, content.as_deref())
+        {
+            record_session(workspace_path, &note_path, session)?;
+        }
+    }
+
+    remove_journal(workspace_path, &journal.id())?;
+    Ok(RecoveredSession {
+        note_path,
+        duration_minutes,
+        restored_to,
+        commit_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn crashed_session(ws: &Path, note: &Path) -> SessionJournal {
+        let timeout = Duration::minutes(5);
+        let start = Utc::now() - Duration::hours(2);
+        let mut tracker = NoteTracker::opened(
+            note.display().to_string(),
+            start,
+            Some("# Plan\n".to_string()),
+        );
+        for minute in 1..=20 {
+            tracker.heartbeat(start + Duration::minutes(minute), timeout);
+        }
+        let journal = SessionJournal::new(
+            "previous-run",
+            "main",
+            &tracker,
+            Some("# Plan\n\n[] Book the room\n".to_string()),
+        );
+        write_journal(ws, &journal).unwrap();
+        journal
+    }
+
+    #[test]
+    fn test_orphans_are_journals_from_other_runs() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let note = ws.join("plan.md");
+        std::fs::write(&note, "# Plan\n").unwrap();
+
+        crashed_session(ws, &note);
+        let current = NoteTracker::new(note.display().to_string());
+        write_journal(ws, &SessionJournal::new("this-run", "main", &current, None)).unwrap();
+
+        let orphans = orphaned_journals(ws, "this-run");
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].run_id, "previous-run");
+        let info = OrphanedSession::new(&orphans[0], Duration::minutes(5));
+        assert_eq!(info.duration_minutes, 20);
+        assert!(info.has_unsaved_content);
+    }
+
+    #[test]
+    fn test_recover_restores_content_and_records_session() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let note = ws.join("plan.md");
+        std::fs::write(&note, "# Plan\n").unwrap();
+        // The note was last saved before the crash
+        let earlier = std::time::SystemTime::now() - std::time::Duration::from_secs(3 * 3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&note)
+            .unwrap()
+            .set_modified(earlier)
+            .unwrap();
+
+        let journal = crashed_session(ws, &note);
+        let recovered = recover_journal(ws, &journal, true, Duration::minutes(5)).unwrap();
+
+        assert_eq!(recovered.restored_to.as_deref(), Some(note.as_path()));
+        assert_eq!(recovered.duration_minutes, 20);
+        assert!(std::fs::read_to_string(&note)
+            .unwrap()
+            .contains("Book the room"));
+
+        let meta = storage::load_metadata(ws, &note).unwrap().unwrap();
+        let session = meta.session.unwrap();
+        assert_eq!(session.duration_minutes, 20);
+        assert_eq!(session.markers.actions, 1);
+        assert!(orphaned_journals(ws, "this-run").is_empty());
+    }
+
+    #[test]
+    fn test_recover_keeps_newer_note_intact() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        let note = ws.join("plan.md");
+        std::fs::write(&note, "# Plan\n").unwrap();
+
+        let mut journal = crashed_session(ws, &note);
+        // Edited elsewhere after the journal was written
+        journal.saved_at = Utc::now() - Duration::hours(1);
+        std::fs::write(&note, "# Plan\n\nEdited elsewhere\n").unwrap();
+
+        let recovered = recover_journal(ws, &journal, true, Duration::minutes(5)).unwrap();
+        assert_eq!(recovered.restored_to, Some(ws.join("plan.recovered.md")));
+        assert_eq!(
+            std::fs::read_to_string(&note).unwrap(),
+            "# Plan\n\nEdited elsewhere\n"
+        );
+    }
+}
@@ -1,3 +1,9 @@
+mod history;
+mod journal;
+mod report;
 mod tracker;
 
-pub use tracker::{TrackerInfo, TrackerManager};
+pub use history::*;
+pub use journal::*;
+pub use report::*;
+pub use tracker::*;
@@ -0,0 +1,396 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use super::load_all_metadata;
+use crate::git;
+use crate::notes::{self, NoteRegistry};
+use crate::storage::{self, StorageError};
+
+/// Row key for sessions that match no tag
+const UNTAGGED: &str = "(untagged)";
+
+/// How a time report groups sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportGroup {
+    Note,
+    Folder,
+    Tag,
+    Day,
+}
+
+/// Export format for time reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// Time spent in one group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportRow {
+    /// Note path, folder, tag or date (YYYY-MM-DD)
+    pub key: String,
+    pub minutes: u32,
+    pub sessions: u32,
+    pub notes: u32,
+    pub first_session_at: DateTime<Utc>,
+    pub last_session_at: DateTime<Utc>,
+}
+
+/// Time spent per group over a date range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeReport {
+    pub group_by: ReportGroup,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Only sessions on notes with a tag starting with this (e.g. `project:`)
+    pub tag: Option<String>,
+    /// Each session counted once, even when it appears under several tags
+    pub total_minutes: u32,
+    pub sessions: u32,
+    /// Most time first; by date for day reports
+    pub rows: Vec<ReportRow>,
+}
+
+/// One session on one note, from metadata or a session commit
+#[derive(Debug, Clone)]
+struct ReportSession {
+    path: String,
+    started_at: DateTime<Utc>,
+    minutes: u32,
+}
+
+/// Sessions from note metadata, plus session commits from before a note's
+/// metadata history began (notes edited before sessions were recorded there)
+fn collect_sessions(workspace_path: &Path) -> Vec<ReportSession> {
+    let mut sessions = Vec::new();
+    let mut history_starts: HashMap<String, DateTime<Utc>> = HashMap::new();
+
+    for (path, meta) in load_all_metadata(workspace_path) {
+        for session in &meta.sessions {
+            sessions.push(ReportSession {
+                path: path.clone(),
+                started_at: session.started_at,
+                minutes: session.duration_minutes,
+            });
+        }
+        if let Some(first) = meta.sessions.iter().map(|s| s.started_at).min() {
+            history_starts.insert(path, first);
+        }
+    }
+
+    let commits = git::session_commits(workspace_path).unwrap_or_else(|e| {
+        tracing::warn!("Failed to read session commits: {}", e);
+        Vec::new()
+    });
+    for commit in commits {
+        if history_starts
+            .get(&commit.note_path)
+            .is_some_and(|start| commit.committed_at >= *start)
+        {
+            continue;
+        }
+        sessions.push(ReportSession {
+            started_at: commit.committed_at - Duration::minutes(commit.duration_minutes as i64),
+            path: commit.note_path,
+            minutes: commit.duration_minutes,
+        });
+    }
+    sessions
+}
+
+/// Tags per note path: the tags index plus each note's own front matter
+fn note_tags(workspace_path: &Path) -> HashMap<String, BTreeSet<String>> {
+    let mut tags: HashMap<String, BTreeSet<String>> = HashMap::new();
+
+    let index = std::fs::read_to_string(workspace_path.join(".chronicle").join("tags.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str::<Value>(&c).ok());
+    if let Some(mut index) = index {
+        notes::resolve_note_ids(&mut index, &NoteRegistry::load(workspace_path));
+        if let Some(by_note) = index.get("byNote").and_then(|v| v.as_object()) {
+            for (path, note_tags) in by_note {
+                let entry = tags.entry(path.clone()).or_default();
+                for tag in note_tags.as_array().into_iter().flatten() {
+                    if let Some(tag) = tag.as_str() {
+                        entry.insert(tag.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    for (path, entry) in tags.iter_mut() {
+        add_front_matter_tags(&workspace_path.join(path), entry);
+    }
+    tags
+}
+
+/// Front matter tags as-is, the project as `project:` and attendees as `person:`
+fn add_front_matter_tags(note: &Path, tags: &mut BTreeSet<String>) {
+    let Ok(content) = std::fs::read_to_string(note) else {
+        return;
+    };
+    let Ok((front_matter, _)) = storage::parse_front_matter(&content) else {
+        return;
+    };
+    tags.extend(front_matter.tags);
+    if let Some(project) = front_matter.project {
+        tags.insert(format!("project:{}", storage::generate_slug(&project)));
+    }
+    for attendee in front_matter.attendees {
+        tags.insert(format!("person:{}", storage::generate_slug(&attendee)));
+    }
+}
+
+fn folder_of(path: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((folder, _)) => folder.to_string(),
+        None => ".".to_string(),
+    }
+}
+
+/// Aggregate recorded sessions started between `from` and `to` (inclusive, as
+/// dates in `tz`) by note, folder, tag or day. With `tag`, only sessions on notes
+/// carrying a tag with that prefix count, and tag reports only show those tags.
+pub fn time_report<Tz: TimeZone>(
+    workspace_path: &Path,
+    group_by: ReportGroup,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    tag: Option<&str>,
+    tz: &Tz,
+) -> TimeReport {
+    let mut tags = note_tags(workspace_path);
+    let mut rows: BTreeMap<String, (ReportRow, BTreeSet<String>)> = BTreeMap::new();
+    let mut total_minutes = 0;
+    let mut session_count = 0;
+
+    for session in collect_sessions(workspace_path) {
+        let date = session.started_at.with_timezone(tz).date_naive();
+        if from.is_some_and(|from| date < from) || to.is_some_and(|to| date > to) {
+            continue;
+        }
+
+        // Notes without an index entry still carry front matter tags
+        let session_tags = tags.entry(session.path.clone()).or_insert_with(|| {
+            let mut own = BTreeSet::new();
+            add_front_matter_tags(&workspace_path.join(&session.path), &mut own);
+            own
+        });
+        let matching: Vec<String> = session_tags
+            .iter()
+            .filter(|t| tag.is_none_or(|prefix| t.starts_with(prefix)))
+            .cloned()
+            .collect();
+        if tag.is_some() && matching.is_empty() {
+            continue;
+        }
+
+        let keys = match group_by {
+            ReportGroup::Note => vec![session.path.clone()],
+            ReportGroup::Folder => vec![folder_of(&session.path)],
+            ReportGroup::Day => vec![date.format("%Y-%m-%d").to_string()],
+            ReportGroup::Tag if matching.is_empty() => vec![UNTAGGED.to_string()],
+            ReportGroup::Tag => matching,
+        };
+
+        total_minutes += session.minutes;
+        session_count += 1;
+        for key in keys {
+            let (row, row_notes) = rows.entry(key.clone()).or_insert_with(|| {
+                (
+                    ReportRow {
+                        key,
+                        minutes: 0,
+                        sessions: 0,
+                        notes: 0,
+                        first_session_at: session.started_at,
+                        last_session_at: session.started_at,
+                    },
+                    BTreeSet::new(),
+                )
+            });
+            row.minutes += session.minutes;
+            row.sessions += 1;
+            row.first_session_at = row.first_session_at.min(session.started_at);
+            row.last_session_at = row.last_session_at.max(session.started_at);
+            if row_notes.insert(session.path.clone()) {
+                row.notes += 1;
+            }
+        }
+    }
+
+    let mut rows: Vec<ReportRow> = rows.into_values().map(|(row, _)| row).collect();
+    if group_by != ReportGroup::Day {
+        rows.sort_by(|a, b| b.minutes.cmp(&a.minutes).then_with(|| a.key.cmp(&b.key)));
+    }
+    TimeReport {
+        group_by,
+        from,
+        to,
+        tag: tag.map(str::to_string),
+        total_minutes,
+        sessions: session_count,
+        rows,
+    }
+}
+
+/// Render a report as CSV, one row per group with hours for billing
+pub fn report_csv(report: &TimeReport) -> Result<String, StorageError> {
+    let csv_error = |e: csv::Error| StorageError::SerializeError(e.to_string());
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record([
+            "group",
+            "key",
+            "minutes",
+            "hours",
+            "sessions",
+            "notes",
+            "first_session",
+            "last_session",
+        ])
+        .map_err(csv_error)?;
+
+    let group = serde_json::to_value(report.group_by)?;
+    let group = group.as_str().unwrap_or_default();
+    for row in &report.rows {
+        writer
+            .write_record([
+                group,
+                &row.key,
+                &row.minutes.to_string(),
+                &format!("{:.2}", row.minutes as f64 / 60.0),
+                &row.sessions.to_string(),
+                &row.notes.to_string(),
+                &row.first_session_at.to_rfc3339(),
+                &row.last_session_at.to_rfc3339(),
+            ])
+            .map_err(csv_error)?;
+    }
+
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| StorageError::SerializeError(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| StorageError::SerializeError(e.to_string()))
+}
+
+/// Render a report in an export format
+pub fn export_report(report: &TimeReport, format: ReportFormat) -> Result<String, StorageError> {
+    match format {
+        ReportFormat::Csv => report_csv(report),
+        ReportFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{record_session, session_record, NoteTracker};
+    use tempfile::tempdir;
+
+    fn record(ws: &Path, relative: &str, start: DateTime<Utc>, minutes: i64) {
+        let note = ws.join(relative);
+        let timeout = Duration::minutes(5);
+        let mut tracker = NoteTracker::opened(note.display().to_string(), start, None);
+        for minute in 1..=minutes {
+            tracker.heartbeat(start + Duration::minutes(minute), timeout);
+        }
+        let content = std::fs::read_to_string(&note).ok();
+        let ended_at = start + Duration::minutes(minutes);
+        let session = session_record(&tracker, ended_at, timeout, content.as_deref()).unwrap();
+        record_session(ws, &note, session).unwrap();
+    }
+
+    #[test]
+    fn test_report_groups_by_folder_and_tag() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join("clients")).unwrap();
+        std::fs::write(
+            ws.join("clients/acme-kickoff.md"),
+            "---\nproject: Acme\nattendees: [Sarah]\n---\n# Kickoff\n",
+        )
+        .unwrap();
+        std::fs::write(ws.join("clients/acme-review.md"), "# Review\n").unwrap();
+        std::fs::write(ws.join("journal.md"), "# Journal\n").unwrap();
+        std::fs::create_dir_all(ws.join(".chronicle")).unwrap();
+        std::fs::write(
+            ws.join(".chronicle/tags.json"),
+            r#"{"byNote": {"clients/acme-review.md": ["project:acme", "topic:design"]}}"#,
+        )
+        .unwrap();
+
+        let day = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        record(ws, "clients/acme-kickoff.md", day, 45);
+        record(ws, "clients/acme-review.md", day + Duration::days(1), 30);
+        record(ws, "journal.md", day + Duration::days(2), 10);
+
+        let report = time_report(ws, ReportGroup::Folder, None, None, None, &Utc);
+        assert_eq!(report.total_minutes, 85);
+        assert_eq!(report.rows[0].key, "clients");
+        assert_eq!(report.rows[0].minutes, 75);
+        assert_eq!(report.rows[0].notes, 2);
+
+        let report = time_report(ws, ReportGroup::Tag, None, None, Some("project:"), &Utc);
+        assert_eq!(report.rows.len(), 1);
+        assert_eq!(report.rows[0].key, "project:acme");
+        assert_eq!(report.rows[0].minutes, 75);
+        assert_eq!(report.sessions, 2);
+
+        let report = time_report(ws, ReportGroup::Tag, None, None, Some("person:"), &Utc);
+        assert_eq!(report.rows[0].key, "person:sarah");
+        assert_eq!(report.total_minutes, 45);
+
+        // Date range
+        let first = day.date_naive();
+        let report = time_report(ws, ReportGroup::Day, Some(first), Some(first), None, &Utc);
+        assert_eq!(report.rows.len(), 1);
+        assert_eq!(report.rows[0].key, "2026-03-02");
+        assert_eq!(report.total_minutes, 45);
+    }
+
+    #[test]
+    fn test_report_includes_commit_history_and_exports_csv() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        git::init_or_open_repo(ws).unwrap();
+        std::fs::write(ws.join("standup.md"), "# Standup, daily\n").unwrap();
+        git::commit_files(
+            ws,
+            &[Path::new("standup.md")],
+            git::CommitType::Session,
+            "Standup, daily",
+            "20m",
+        )
+        .unwrap();
+
+        let report = time_report(ws, ReportGroup::Note, None, None, None, &Utc);
+        assert_eq!(report.rows.len(), 1);
+        assert_eq!(report.rows[0].minutes, 20);
+
+        let csv = export_report(&report, ReportFormat::Csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("group,key,minutes,hours,sessions,notes,first_session,last_session")
+        );
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("note,standup.md,20,0.33,1,1,"));
+
+        let json: Value =
+            serde_json::from_str(&export_report(&report, ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["groupBy"], "note");
+        assert_eq!(json["totalMinutes"], 20);
+    }
+}
@@ -1,47 +1,133 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::{Mutex, MutexGuard};
 
-/// Simple note tracking - just tracks when a note was opened
+/// Pane used when the frontend doesn't name one
+pub const DEFAULT_PANE: &str = "main";
+
+/// Gaps between activity longer than this count as idle, not editing time
+pub const DEFAULT_IDLE_TIMEOUT_SECS: i64 = 5 * 60;
+
+/// Activity-based note tracking: time only accrues between heartbeats that are
+/// closer together than the idle timeout, and never while paused
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoteTracker {
     /// Path to the note file
     pub note_path: String,
     /// When the note was opened
     pub opened_at: DateTime<Utc>,
+    /// Last heartbeat (or open/resume)
+    pub last_activity_at: DateTime<Utc>,
+    /// Active time accumulated up to `last_activity_at`
+    pub active_seconds: i64,
+    pub paused: bool,
+    /// Note content when it was opened, to measure what the session changed
+    #[serde(skip)]
+    pub initial_content: Option<String>,
 }
 
 impl NoteTracker {
     /// Create a new tracker for a note
     pub fn new(note_path: String) -> Self {
+        let initial_content = std::fs::read_to_string(&note_path).ok();
+        Self::opened(note_path, Utc::now(), initial_content)
+    }
+
+    /// Create a tracker for a note opened at a given time
+    pub fn opened(note_path: String, at: DateTime<Utc>, initial_content: Option<String>) -> Self {
         Self {
             note_path,
-            opened_at: Utc::now(),
+            opened_at: at,
+            last_activity_at: at,
+            active_seconds: 0,
+            paused: false,
+            initial_content,
+        }
+    }
+
+    /// Active time from the last activity up to `now`, if it isn't idle
+    fn pending_seconds(&self, now: DateTime<Utc>, idle_timeout: Duration) -> i64 {
+        let gap = now - self.last_activity_at;
+        if self.paused || gap > idle_timeout || gap < Duration::zero() {
+            0
+        } else {
+            gap.num_seconds()
+        }
+    }
+
+    /// Record editor activity. Time since the previous activity counts unless the
+    /// note sat idle for longer than the timeout.
+    pub fn heartbeat(&mut self, now: DateTime<Utc>, idle_timeout: Duration) {
+        if self.paused {
+            return;
+        }
+        self.active_seconds += self.pending_seconds(now, idle_timeout);
+        self.last_activity_at = now;
+    }
+
+    /// Stop accruing time (window hidden, pane in background)
+    pub fn pause(&mut self, now: DateTime<Utc>, idle_timeout: Duration) {
+        self.heartbeat(now, idle_timeout);
+        self.paused = true;
+    }
+
+    /// Accrue time again from `now`
+    pub fn resume(&mut self, now: DateTime<Utc>) {
+        if self.paused {
+            self.paused = false;
+            self.last_activity_at = now;
         }
     }
 
-    /// Get current duration in minutes since file was opened
-    pub fn duration_minutes(&self) -> u32 {
-        (Utc::now() - self.opened_at).num_minutes().max(0) as u32
+    /// Whether the note has had no activity for longer than the timeout
+    pub fn is_idle(&self, now: DateTime<Utc>, idle_timeout: Duration) -> bool {
+        !self.paused && now - self.last_activity_at > idle_timeout
+    }
+
+    /// Active editing time up to `now`, in seconds
+    pub fn active_seconds_at(&self, now: DateTime<Utc>, idle_timeout: Duration) -> i64 {
+        self.active_seconds + self.pending_seconds(now, idle_timeout)
+    }
+
+    /// Active editing time up to `now`, in whole minutes
+    pub fn active_minutes_at(&self, now: DateTime<Utc>, idle_timeout: Duration) -> u32 {
+        (self.active_seconds_at(now, idle_timeout) / 60).max(0) as u32
     }
 }
 
-/// Global tracker manager
+/// Global tracker manager: one tracker per editor pane
 pub struct TrackerManager {
-    /// Current note tracker (if any)
-    current: Mutex<Option<NoteTracker>>,
+    trackers: Mutex<HashMap<String, NoteTracker>>,
+    idle_timeout: Duration,
+    /// Identifies this app run in session journals
+    run_id: String,
 }
 
 impl TrackerManager {
     pub fn new() -> Self {
+        Self::with_idle_timeout(Duration::seconds(DEFAULT_IDLE_TIMEOUT_SECS))
+    }
+
+    pub fn with_idle_timeout(idle_timeout: Duration) -> Self {
         Self {
-            current: Mutex::new(None),
+            trackers: Mutex::new(HashMap::new()),
+            idle_timeout,
+            run_id: uuid::Uuid::new_v4().to_string(),
         }
     }
 
+    pub fn idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
     /// Acquire the lock with poison recovery
-    fn lock_current(&self) -> MutexGuard<'_, Option<NoteTracker>> {
-        match self.current.lock() {
+    fn lock_trackers(&self) -> MutexGuard<'_, HashMap<String, NoteTracker>> {
+        match self.trackers.lock() {
             Ok(guard) => guard,
             Err(poisoned) => {
                 tracing::warn!("Recovering from poisoned session lock");
@@ -50,37 +136,84 @@ impl TrackerManager {
         }
     }
 
-    /// Start tracking a note
-    pub fn start_tracking(&self, note_path: &str) {
-        let mut current = self.lock_current();
-        *current = Some(NoteTracker::new(note_path.to_string()));
-        tracing::debug!("Started tracking note");
+    /// Start tracking a note in a pane, replacing whatever the pane tracked before
+    pub fn start_tracking(&self, pane: &str, note_path: &str) {
+        let mut trackers = self.lock_trackers();
+        trackers.insert(pane.to_string(), NoteTracker::new(note_path.to_string()));
+        tracing::debug!("Started tracking note in pane {}", pane);
     }
 
-    /// Stop tracking and return the tracker data (for commit)
-    pub fn stop_tracking(&self) -> Option<NoteTracker> {
-        let mut current = self.lock_current();
-        let tracker = current.take();
+    /// Stop tracking a pane and return the tracker data (for commit)
+    pub fn stop_tracking(&self, pane: &str) -> Option<NoteTracker> {
+        let mut trackers = self.lock_trackers();
+        let tracker = trackers.remove(pane);
         if let Some(ref t) = tracker {
-            tracing::debug!("Stopped tracking note ({}m)", t.duration_minutes());
+            tracing::debug!(
+                "Stopped tracking note in pane {} ({}m active)",
+                pane,
+                t.active_minutes_at(Utc::now(), self.idle_timeout)
+            );
         }
         tracker
     }
 
-    /// Get current tracker info
-    pub fn get_info(&self) -> Option<TrackerInfo> {
-        let current = self.lock_current();
-        current.as_ref().map(|t| TrackerInfo {
-            note_path: t.note_path.clone(),
-            duration_minutes: t.duration_minutes(),
-            opened_at: t.opened_at,
-        })
+    /// Record activity in a pane. Returns false when the pane isn't tracking.
+    pub fn heartbeat(&self, pane: &str) -> bool {
+        let mut trackers = self.lock_trackers();
+        match trackers.get_mut(pane) {
+            Some(tracker) => {
+                tracker.heartbeat(Utc::now(), self.idle_timeout);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Copy of a pane's tracker, for journaling
+    pub fn snapshot(&self, pane: &str) -> Option<NoteTracker> {
+        self.lock_trackers().get(pane).cloned()
+    }
+
+    /// Pause or resume a pane, or every pane when `pane` is None
+    pub fn set_paused(&self, pane: Option<&str>, paused: bool) {
+        let now = Utc::now();
+        let mut trackers = self.lock_trackers();
+        for (name, tracker) in trackers.iter_mut() {
+            if pane.is_some_and(|p| p != name) {
+                continue;
+            }
+            if paused {
+                tracker.pause(now, self.idle_timeout);
+            } else {
+                tracker.resume(now);
+            }
+        }
+    }
+
+    /// Get tracker info for a pane
+    pub fn get_info(&self, pane: &str) -> Option<TrackerInfo> {
+        let trackers = self.lock_trackers();
+        trackers
+            .get(pane)
+            .map(|t| TrackerInfo::new(pane, t, Utc::now(), self.idle_timeout))
+    }
+
+    /// Tracker info for every pane, sorted by pane
+    pub fn list(&self) -> Vec<TrackerInfo> {
+        let now = Utc::now();
+        let trackers = self.lock_trackers();
+        let mut infos: Vec<TrackerInfo> = trackers
+            .iter()
+            .map(|(pane, t)| TrackerInfo::new(pane, t, now, self.idle_timeout))
+            .collect();
+        infos.sort_by(|a, b| a.pane_id.cmp(&b.pane_id));
+        infos
     }
 
-    /// Check if currently tracking
+    /// Check if a pane is tracking
     #[allow(dead_code)] // Used in tests
-    pub fn is_tracking(&self) -> bool {
-        self.lock_current().is_some()
+    pub fn is_tracking(&self, pane: &str) -> bool {
+        self.lock_trackers().contains_key(pane)
     }
 }
 
@@ -93,9 +226,33 @@ impl Default for TrackerManager {
 /// Serializable tracker info for frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrackerInfo {
+    pub pane_id: String,
     pub note_path: String,
+    /// Active editing time, not wall clock
     pub duration_minutes: u32,
     pub opened_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
+    pub paused: bool,
+    pub idle: bool,
+}
+
+impl TrackerInfo {
+    pub fn new(
+        pane: &str,
+        tracker: &NoteTracker,
+        now: DateTime<Utc>,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            pane_id: pane.to_string(),
+            note_path: tracker.note_path.clone(),
+            duration_minutes: tracker.active_minutes_at(now, idle_timeout),
+            opened_at: tracker.opened_at,
+            last_activity_at: tracker.last_activity_at,
+            paused: tracker.paused,
+            idle: tracker.is_idle(now, idle_timeout),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -105,7 +262,45 @@ mod tests {
     #[test]
     fn test_note_tracker() {
         let tracker = NoteTracker::new("test.md".to_string());
-        assert_eq!(tracker.duration_minutes(), 0);
+        let timeout = Duration::seconds(DEFAULT_IDLE_TIMEOUT_SECS);
+        assert_eq!(tracker.active_minutes_at(Utc::now(), timeout), 0);
+    }
+
+    #[test]
+    fn test_idle_time_is_not_counted() {
+        let timeout = Duration::minutes(5);
+        let start = Utc::now();
+        let mut tracker = NoteTracker::opened("test.md".to_string(), start, None);
+
+        // Ten minutes of steady editing
+        for minute in 1..=10 {
+            tracker.heartbeat(start + Duration::minutes(minute), timeout);
+        }
+        assert_eq!(
+            tracker.active_minutes_at(start + Duration::minutes(10), timeout),
+            10
+        );
+
+        // Left open overnight: the idle stretch adds nothing
+        let morning = start + Duration::hours(15);
+        assert!(tracker.is_idle(morning, timeout));
+        assert_eq!(tracker.active_minutes_at(morning, timeout), 10);
+        tracker.heartbeat(morning, timeout);
+        tracker.heartbeat(morning + Duration::minutes(2), timeout);
+        assert_eq!(
+            tracker.active_minutes_at(morning + Duration::minutes(2), timeout),
+            12
+        );
+
+        // Paused time doesn't count either
+        tracker.pause(morning + Duration::minutes(3), timeout);
+        tracker.heartbeat(morning + Duration::minutes(4), timeout);
+        tracker.resume(morning + Duration::minutes(30));
+        tracker.heartbeat(morning + Duration::minutes(31), timeout);
+        assert_eq!(
+            tracker.active_minutes_at(morning + Duration::minutes(31), timeout),
+            14
+        );
     }
 
     #[test]
@@ -113,20 +308,37 @@ mod tests {
         let manager = TrackerManager::new();
 
         // Initially not tracking
-        assert!(!manager.is_tracking());
-        assert!(manager.get_info().is_none());
+        assert!(!manager.is_tracking(DEFAULT_PANE));
+        assert!(manager.get_info(DEFAULT_PANE).is_none());
 
         // Start tracking
-        manager.start_tracking("test.md");
-        assert!(manager.is_tracking());
+        manager.start_tracking(DEFAULT_PANE, "test.md");
+        assert!(manager.is_tracking(DEFAULT_PANE));
 
-        let info = manager.get_info().unwrap();
+        let info = manager.get_info(DEFAULT_PANE).unwrap();
         assert_eq!(info.note_path, "test.md");
         assert_eq!(info.duration_minutes, 0);
 
         // Stop tracking
-        let tracker = manager.stop_tracking().unwrap();
+        let tracker = manager.stop_tracking(DEFAULT_PANE).unwrap();
         assert_eq!(tracker.note_path, "test.md");
-        assert!(!manager.is_tracking());
+        assert!(!manager.is_tracking(DEFAULT_PANE));
+    }
+
+    #[test]
+    fn test_panes_track_independently() {
+        let manager = TrackerManager::new();
+        manager.start_tracking("left", "a.md");
+        manager.start_tracking("right", "b.md");
+        assert_eq!(manager.list().len(), 2);
+
+        manager.set_paused(Some("left"), true);
+        assert!(manager.get_info("left").unwrap().paused);
+        assert!(!manager.get_info("right").unwrap().paused);
+        assert!(manager.heartbeat("right"));
+        assert!(!manager.heartbeat("missing"));
+
+        assert_eq!(manager.stop_tracking("left").unwrap().note_path, "a.md");
+        assert!(manager.is_tracking("right"));
     }
 }
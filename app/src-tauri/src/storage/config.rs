@@ -0,0 +1,335 @@
+use crate::pipeline::{AgentPermissions, DEFAULT_ALLOWED_TOOLS, DEFAULT_MAX_TURNS};
+use crate::storage::StorageError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Workspace settings file inside .chronicle/
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Version of the settings file this build reads and writes
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Placeholders prompts and commands can use for settings
+pub const SETTING_PLACEHOLDERS: &[&str] = &["stale_after_days"];
+
+/// Comment heading .chronicle/config.toml, describing every setting
+const CONFIG_HEADER: &str = r#"# Chronicle workspace settings. Changes apply as soon as the file is saved.
+#
+# [files]
+#   max_file_size_mb  - largest note that can be opened or saved
+#   max_attachment_size_mb
+#                     - largest attachment that can be imported
+#   skip_dirs         - folders left out of the file tree, note count and search
+#                       (hidden folders always are)
+# [search]
+#   max_results       - matches a search returns
+# [agents]
+#   process_max_turns - turn limit for processing a single note
+#   max_turns         - turn limit for digests, custom commands and pipeline
+#                       agents that don't set their own
+#   allowed_tools     - tools for runs that don't declare their own; any of
+#                       Read, Write, Edit, MultiEdit, Glob, Grep, LS
+# [digests]
+#   weekly_days       - days a weekly digest looks back
+#   monthly_days      - days a monthly digest looks back
+# [actions]
+#   stale_after_days  - open actions older than this are stale; prompts refer
+#                       to it as {{stale_after_days}}
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilesConfig {
+    pub max_file_size_mb: u64,
+    pub max_attachment_size_mb: u64,
+    pub skip_dirs: Vec<String>,
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size_mb: 50,
+            max_attachment_size_mb: 50,
+            skip_dirs: vec!["node_modules".to_string()],
+        }
+    }
+}
+
+impl FilesConfig {
+    /// Largest note that can be opened or saved, in bytes
+    pub fn max_file_size(&self) -> u64 {
+        self.max_file_size_mb.saturating_mul(1024 * 1024)
+    }
+
+    /// Largest attachment that can be imported, in bytes
+    pub fn max_attachment_size(&self) -> u64 {
+        self.max_attachment_size_mb.saturating_mul(1024 * 1024)
+    }
+
+    /// Whether a folder with this name is left out of listings and search
+    pub fn skips(&self, dir_name: &str) -> bool {
+        self.skip_dirs.iter().any(|d| d == dir_name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    pub max_results: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self { max_results: 50 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentsConfig {
+    pub process_max_turns: u32,
+    pub max_turns: u32,
+    pub allowed_tools: Vec<String>,
+}
+
+impl Default for AgentsConfig {
+    fn default() -> Self {
+        Self {
+            process_max_turns: 10,
+            max_turns: DEFAULT_MAX_TURNS,
+            allowed_tools: DEFAULT_ALLOWED_TOOLS
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl AgentsConfig {
+    /// The configured tools, allowed to write exactly `writes`
+    pub fn permissions(&self, writes: Vec<String>) -> AgentPermissions {
+        AgentPermissions::new(self.allowed_tools.clone(), writes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestsConfig {
+    pub weekly_days: u32,
+    pub monthly_days: u32,
+}
+
+impl Default for DigestsConfig {
+    fn default() -> Self {
+        Self {
+            weekly_days: 7,
+            monthly_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionsConfig {
+    pub stale_after_days: u32,
+}
+
+impl Default for ActionsConfig {
+    fn default() -> Self {
+        Self {
+            stale_after_days: 7,
+        }
+    }
+}
+
+/// Per-workspace settings, loaded from .chronicle/config.toml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkspaceConfig {
+    pub version: u32,
+    pub files: FilesConfig,
+    pub search: SearchConfig,
+    pub agents: AgentsConfig,
+    pub digests: DigestsConfig,
+    pub actions: ActionsConfig,
+}
+
+impl Default for WorkspaceConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            files: FilesConfig::default(),
+            search: SearchConfig::default(),
+            agents: AgentsConfig::default(),
+            digests: DigestsConfig::default(),
+            actions: ActionsConfig::default(),
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> StorageError {
+    StorageError::ParseError(CONFIG_FILE.to_string(), message.into())
+}
+
+impl WorkspaceConfig {
+    /// Parse and validate workspace settings
+    pub fn parse(content: &str) -> Result<Self, StorageError> {
+        let config: WorkspaceConfig =
+            toml::from_str(content).map_err(|e| invalid(e.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the version and that every limit is usable
+    pub fn validate(&self) -> Result<(), StorageError> {
+        if self.version != CONFIG_VERSION {
+            return Err(invalid(format!("unsupported version {}", self.version)));
+        }
+        if self.files.max_file_size_mb == 0 || self.files.max_attachment_size_mb == 0 {
+            return Err(invalid("file size limits must be at least 1MB"));
+        }
+        if let Some(dir) = self
+            .files
+            .skip_dirs
+            .iter()
+            .find(|d| d.is_empty() || d.contains(['/', '\\']) || d.as_str() == "..")
+        {
+            return Err(invalid(format!(
+                "files.skip_dirs must be folder names, not {:?}",
+                dir
+            )));
+        }
+        if self.search.max_results == 0 {
+            return Err(invalid("search.max_results must be at least 1"));
+        }
+        if self.agents.process_max_turns == 0 || self.agents.max_turns == 0 {
+            return Err(invalid("agent turn limits must be at least 1"));
+        }
+        if let Some(tool) = self.agents.permissions(Vec::new()).unknown_tool() {
+            return Err(invalid(format!(
+                "agents.allowed_tools: unsupported tool {}",
+                tool
+            )));
+        }
+        if self.digests.weekly_days == 0 || self.digests.monthly_days == 0 {
+            return Err(invalid("digest ranges must be at least 1 day"));
+        }
+        if self.actions.stale_after_days == 0 {
+            return Err(invalid("actions.stale_after_days must be at least 1"));
+        }
+        Ok(())
+    }
+
+    /// Read the workspace settings, defaults when the file doesn't exist
+    pub fn read(workspace_path: &Path) -> Result<Self, StorageError> {
+        let path = workspace_path.join(".chronicle").join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| StorageError::ReadFailed(path.display().to_string(), e))?;
+        Self::parse(&content)
+    }
+
+    /// Load the workspace settings. Falls back to the defaults when the file is
+    /// invalid; `get_config` reports why.
+    pub fn load(workspace_path: &Path) -> Self {
+        Self::read(workspace_path).unwrap_or_else(|e| {
+            tracing::warn!("Using default settings: {}", e);
+            Self::default()
+        })
+    }
+
+    /// The settings file's content: the settings under an explanatory header
+    pub fn to_toml(&self) -> Result<String, StorageError> {
+        let body =
+            toml::to_string(self).map_err(|e| StorageError::SerializeError(e.to_string()))?;
+        Ok(format!("{}{}", CONFIG_HEADER, body))
+    }
+
+    /// Validate the settings and write them to .chronicle/config.toml
+    pub fn save(&self, workspace_path: &Path) -> Result<(), StorageError> {
+        self.validate()?;
+        let path = workspace_path.join(".chronicle").join(CONFIG_FILE);
+        super::write_file_atomic(&path, &self.to_toml()?)
+    }
+
+    /// Fill in the settings prompts can refer to (`SETTING_PLACEHOLDERS`)
+    pub fn expand_prompt(&self, prompt: &str) -> String {
+        prompt.replace(
+            "{{stale_after_days}}",
+            &self.actions.stale_after_days.to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_partial_config_keeps_defaults() {
+        let config = WorkspaceConfig::parse(
+            "version = 1\n[search]\nmax_results = 20\n[files]\nskip_dirs = [\"vendor\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.search.max_results, 20);
+        assert!(config.files.skips("vendor") && !config.files.skips("node_modules"));
+        assert_eq!(config.agents, AgentsConfig::default());
+        assert_eq!(config.files.max_file_size(), 50 * 1024 * 1024);
+        assert_eq!(config.files.max_attachment_size(), 50 * 1024 * 1024);
+
+        let huge =
+            WorkspaceConfig::parse(&format!("[files]\nmax_file_size_mb = {}", i64::MAX)).unwrap();
+        assert_eq!(huge.files.max_file_size(), u64::MAX);
+
+        let written = WorkspaceConfig::default().to_toml().unwrap();
+        assert_eq!(
+            WorkspaceConfig::parse(&written).unwrap(),
+            WorkspaceConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        for content in [
+            "version = 2",
+            "[search]\nmax_results = 0",
+            "[files]\nmax_attachment_size_mb = 0",
+            "[agents]\nallowed_tools = [\"Read\", \"Bash\"]",
+            "[files]\nskip_dirs = [\"a/b\"]",
+            "[actions]\nstale_after_days = \"soon\"",
+        ] {
+            assert!(
+                matches!(
+                    WorkspaceConfig::parse(content),
+                    Err(StorageError::ParseError(_, _))
+                ),
+                "{}",
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_falls_back_to_defaults() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(WorkspaceConfig::load(root), WorkspaceConfig::default());
+
+        std::fs::create_dir_all(root.join(".chronicle")).unwrap();
+        std::fs::write(root.join(".chronicle").join(CONFIG_FILE), "version = 9").unwrap();
+        assert!(WorkspaceConfig::read(root).is_err());
+        assert_eq!(WorkspaceConfig::load(root), WorkspaceConfig::default());
+
+        let mut config = WorkspaceConfig::default();
+        config.actions.stale_after_days = 14;
+        config.save(root).unwrap();
+        assert_eq!(WorkspaceConfig::read(root).unwrap(), config);
+        assert_eq!(
+            config.expand_prompt("older than {{stale_after_days}} days"),
+            "older than 14 days"
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 use thiserror::Error;
+use uuid::Uuid;
 
 #[derive(Error, Debug)]
 pub enum StorageError {
@@ -40,32 +42,175 @@ pub fn write_file(path: &Path, content: &str) -> Result<(), StorageError> {
     fs::write(path, content).map_err(|e| StorageError::WriteFailed(path.display().to_string(), e))
 }
 
+/// Write a file atomically: the content goes to a uniquely named temp file in the
+/// same directory, which is synced to disk and renamed over the target. Readers see
+/// either the old or the new content, never a partial write. The target's
+/// permissions are kept.
 pub fn write_file_atomic(path: &Path, content: &str) -> Result<(), StorageError> {
+    write_atomic(path, content.as_bytes(), false)
+}
+
+/// `write_file_atomic`, keeping the previous content in `<name>.bak`
+pub fn write_file_atomic_with_backup(path: &Path, content: &str) -> Result<(), StorageError> {
+    write_atomic(path, content.as_bytes(), true)
+}
+
+/// `write_file_atomic` for content that isn't UTF-8
+pub fn write_bytes_atomic(path: &Path, content: &[u8]) -> Result<(), StorageError> {
+    write_atomic(path, content, false)
+}
+
+fn write_atomic(path: &Path, content: &[u8], backup: bool) -> Result<(), StorageError> {
+    let write_error = |e| StorageError::WriteFailed(path.display().to_string(), e);
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| StorageError::InvalidPath(path.display().to_string()))?
+        .to_string_lossy();
+
     // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| StorageError::WriteFailed(parent.display().to_string(), e))?;
+    fs::create_dir_all(parent)
+        .map_err(|e| StorageError::WriteFailed(parent.display().to_string(), e))?;
+
+    // Unique per write, so concurrent writers and same-stem files never share it
+    let temp_path = parent.join(format!(".{}.{}.tmp", file_name, Uuid::new_v4().simple()));
+    let result = write_temp(&temp_path, path, content)
+        .and_then(|()| {
+            if backup && path.is_file() {
+                rotate_backup(path, &file_name)
+            } else {
+                Ok(())
+            }
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+    if let Err(e) = result {
+        // Best effort cleanup of temp file
+        let _ = fs::remove_file(&temp_path);
+        return Err(write_error(e));
     }
 
-    // Write to temp file first, then rename for atomic operation
-    let temp_path = path.with_extension("tmp");
-    fs::write(&temp_path, content)
-        .map_err(|e| StorageError::WriteFailed(temp_path.display().to_string(), e))?;
-
-    match fs::rename(&temp_path, path) {
-        Ok(()) => Ok(()),
-        Err(e) => {
-            // Best effort cleanup of temp file
-            let _ = fs::remove_file(&temp_path);
-            Err(StorageError::WriteFailed(path.display().to_string(), e))
-        }
+    sync_dir(parent);
+    Ok(())
+}
+
+/// Write and sync the temp file, with the target's permissions when it exists
+fn write_temp(temp_path: &Path, target: &Path, content: &[u8]) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp_path)?;
+    file.write_all(content)?;
+    if let Ok(metadata) = fs::metadata(target) {
+        file.set_permissions(metadata.permissions())?;
+    }
+    file.sync_all()
+}
+
+/// Replace `<name>.bak` with the file's current content
+fn rotate_backup(path: &Path, file_name: &str) -> std::io::Result<()> {
+    let backup_path = path.with_file_name(format!("{}.bak", file_name));
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.bak.tmp",
+        file_name,
+        Uuid::new_v4().simple()
+    ));
+    // A hard link is instant; copy where links aren't supported
+    if fs::hard_link(path, &temp_path).is_err() {
+        fs::copy(path, &temp_path)?;
+    }
+    fs::rename(&temp_path, &backup_path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+/// Sync a directory so a rename in it survives a crash. Best effort, Unix only.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Err(e) = fs::File::open(dir).and_then(|d| d.sync_all()) {
+        tracing::debug!("Failed to sync {}: {}", dir.display(), e);
     }
+    #[cfg(not(unix))]
+    let _ = dir;
 }
 
 pub fn file_exists(path: &Path) -> bool {
     path.exists()
 }
 
+/// Whether two existing paths are the same file, such as names differing only in
+/// case on a case-insensitive filesystem
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (fs::metadata(a), fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
 pub fn ensure_dir(path: &Path) -> Result<(), StorageError> {
     fs::create_dir_all(path).map_err(|e| StorageError::WriteFailed(path.display().to_string(), e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_file_atomic_leaves_no_temp_files() {
+        let dir = tempdir().unwrap();
+        let md = dir.path().join("notes.md");
+        let txt = dir.path().join("notes.txt");
+
+        write_file_atomic(&md, "markdown").unwrap();
+        write_file_atomic(&txt, "text").unwrap();
+        write_file_atomic(&md, "markdown, again").unwrap();
+
+        assert_eq!(fs::read_to_string(&md).unwrap(), "markdown, again");
+        assert_eq!(fs::read_to_string(&txt).unwrap(), "text");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_write_file_atomic_with_backup() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("actions.json");
+
+        write_file_atomic_with_backup(&path, "[1]").unwrap();
+        assert!(!dir.path().join("actions.json.bak").exists());
+
+        write_file_atomic_with_backup(&path, "[2]").unwrap();
+        write_file_atomic_with_backup(&path, "[3]").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[3]");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("actions.json.bak")).unwrap(),
+            "[2]"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_atomic_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".mcp.json");
+        write_file_atomic(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        write_file_atomic(&path, "{\"a\": 1}").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
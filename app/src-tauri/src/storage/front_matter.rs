@@ -0,0 +1,156 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+use super::StorageError;
+
+const DELIMITER: &str = "---";
+
+/// YAML front matter at the top of a note, between `---` lines.
+/// Unknown keys are kept in `extra` so rewriting the block doesn't drop them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontMatter {
+    /// Stable note ID, when the note carries its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "string_or_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub attendees: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "string_or_list",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl FrontMatter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Accept both `tags: [a, b]` and `tags: a, b`
+fn string_or_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        List(Vec<String>),
+        String(String),
+    }
+
+    Ok(match Option::<StringOrList>::deserialize(deserializer)? {
+        Some(StringOrList::List(items)) => items,
+        Some(StringOrList::String(s)) => s
+            .split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect(),
+        None => Vec::new(),
+    })
+}
+
+/// Split a note into its raw front matter block and body.
+/// Returns None for the block when the note doesn't start with one.
+pub fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end_matches(['\n', '\r']);
+        if trimmed == DELIMITER || trimmed == "..." {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    // No closing delimiter: not front matter
+    (None, content)
+}
+
+/// Parse a note's front matter. Notes without a block get an empty one.
+pub fn parse_front_matter(content: &str) -> Result<(FrontMatter, &str), StorageError> {
+    let (block, body) = split_front_matter(content);
+    let Some(block) = block.filter(|b| !b.trim().is_empty()) else {
+        return Ok((FrontMatter::default(), body));
+    };
+
+    let front_matter = serde_yaml::from_str(block)
+        .map_err(|e| StorageError::ParseError("front matter".to_string(), e.to_string()))?;
+    Ok((front_matter, body))
+}
+
+/// Replace a note's front matter, leaving the body byte-for-byte intact.
+/// An empty front matter removes the block.
+pub fn set_front_matter(content: &str, front_matter: &FrontMatter) -> Result<String, StorageError> {
+    let (_, body) = split_front_matter(content);
+    if front_matter.is_empty() {
+        return Ok(body.to_string());
+    }
+
+    let yaml = serde_yaml::to_string(front_matter)
+        .map_err(|e| StorageError::SerializeError(e.to_string()))?;
+    Ok(format!("{}\n{}{}\n{}", DELIMITER, yaml, DELIMITER, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "---\ntitle: Weekly sync\ndate: 2026-02-22\nattendees: [Sarah, Tom]\ntags: planning, q3\nmood: good\n---\n# Weekly sync\n\nBody text\n";
+
+    #[test]
+    fn test_parse_front_matter() {
+        let (fm, body) = parse_front_matter(NOTE).unwrap();
+        assert_eq!(fm.title.as_deref(), Some("Weekly sync"));
+        assert_eq!(fm.date.as_deref(), Some("2026-02-22"));
+        assert_eq!(fm.attendees, vec!["Sarah", "Tom"]);
+        assert_eq!(fm.tags, vec!["planning", "q3"]);
+        assert!(fm.extra.contains_key("mood"));
+        assert_eq!(body, "# Weekly sync\n\nBody text\n");
+
+        // Plain notes and unterminated blocks have no front matter
+        let (fm, body) = parse_front_matter("# Plain\n").unwrap();
+        assert!(fm.is_empty());
+        assert_eq!(body, "# Plain\n");
+        assert_eq!(split_front_matter("---\nnot closed\n").0, None);
+    }
+
+    #[test]
+    fn test_set_front_matter_keeps_body_and_extra_keys() {
+        let (mut fm, body) = parse_front_matter(NOTE).unwrap();
+        fm.project = Some("chronicle".to_string());
+
+        let updated = set_front_matter(NOTE, &fm).unwrap();
+        let (reparsed, new_body) = parse_front_matter(&updated).unwrap();
+        assert_eq!(reparsed, fm);
+        assert_eq!(new_body, body);
+
+        // Adding front matter to a plain note, then removing it again
+        let plain = "# Plain\n\ntext";
+        let with = set_front_matter(plain, &fm).unwrap();
+        assert!(with.starts_with("---\n") && with.ends_with("---\n# Plain\n\ntext"));
+        assert_eq!(
+            set_front_matter(&with, &FrontMatter::default()).unwrap(),
+            plain
+        );
+    }
+}
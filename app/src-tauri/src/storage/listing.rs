@@ -0,0 +1,273 @@
+use crate::models::{FileNode, FileNodeType};
+use crate::storage::{FilesConfig, StorageError};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use super::workspace::{directory_node, file_node, is_hidden, is_markdown_file};
+
+/// How often the note scan reports progress, in directory entries
+const SCAN_PROGRESS_EVERY: usize = 500;
+
+/// Order of the entries in a directory listing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortMode {
+    /// Most recently modified first
+    #[default]
+    Mtime,
+    /// Alphabetical, folders first
+    Name,
+    /// Most recently created first
+    Created,
+}
+
+impl SortMode {
+    fn sort(self, nodes: &mut [FileNode]) {
+        match self {
+            SortMode::Mtime => nodes.sort_by_key(|n| std::cmp::Reverse(n.modified_at)),
+            SortMode::Created => nodes.sort_by_key(|n| std::cmp::Reverse(n.created_at)),
+            SortMode::Name => nodes.sort_by(|a, b| {
+                let is_file = |n: &FileNode| n.node_type != FileNodeType::Directory;
+                is_file(a)
+                    .cmp(&is_file(b))
+                    .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            }),
+        }
+    }
+}
+
+struct Listing {
+    modified: SystemTime,
+    nodes: Vec<FileNode>,
+}
+
+/// Managed state holding directories already listed. A listing is reused until the
+/// directory's own modification time changes (an entry was added, removed or
+/// renamed) or the watcher invalidates it (an entry was modified).
+#[derive(Default)]
+pub struct DirectoryCache {
+    listings: Mutex<HashMap<PathBuf, Listing>>,
+}
+
+impl DirectoryCache {
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, Listing>> {
+        match self.listings.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// The entries directly in a directory, unsorted, directories without children
+    fn entries(&self, dir: &Path, files: &FilesConfig) -> Result<Vec<FileNode>, StorageError> {
+        let modified = dir.metadata().and_then(|m| m.modified()).ok();
+        if let Some(modified) = modified {
+            if let Some(listing) = self.lock().get(dir).filter(|l| l.modified == modified) {
+                return Ok(listing.nodes.clone());
+            }
+        }
+
+        let nodes = read_entries(dir, files)?;
+        if let Some(modified) = modified {
+            self.lock().insert(
+                dir.to_path_buf(),
+                Listing {
+                    modified,
+                    nodes: nodes.clone(),
+                },
+            );
+        }
+        Ok(nodes)
+    }
+
+    /// Forget the listings a changed path shows up in: its own, if it is a
+    /// directory, and its parent's
+    pub fn invalidate(&self, path: &Path) {
+        let mut listings = self.lock();
+        listings.remove(path);
+        if let Some(parent) = path.parent() {
+            listings.remove(parent);
+        }
+    }
+
+    /// Forget every listing in a workspace, e.g. once it is closed or its settings
+    /// change
+    pub fn clear_under(&self, root: &Path) {
+        self.lock().retain(|dir, _| !dir.starts_with(root));
+    }
+}
+
+fn is_skipped(entry: &walkdir::DirEntry, files: &FilesConfig) -> bool {
+    is_hidden(entry)
+        || (entry.file_type().is_dir() && files.skips(&entry.file_name().to_string_lossy()))
+}
+
+fn read_entries(dir: &Path, files: &FilesConfig) -> Result<Vec<FileNode>, StorageError> {
+    let mut nodes = Vec::new();
+    for entry in WalkDir::new(dir)
+        .follow_links(false)
+        .min_depth(1)
+        .max_depth(1)
+        .into_iter()
+        .filter_entry(|e| !is_skipped(e, files))
+    {
+        let entry = entry.map_err(|e| {
+            StorageError::ReadFailed(
+                dir.display().to_string(),
+                e.into_io_error()
+                    .unwrap_or_else(|| std::io::Error::other("walkdir error")),
+            )
+        })?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.file_type().is_dir() {
+            nodes.push(directory_node(name, path, None));
+        } else if let Some(node) = file_node(name, path) {
+            nodes.push(node);
+        }
+    }
+    Ok(nodes)
+}
+
+/// List a directory's notes, attachments and folders, `depth` levels deep.
+/// Folders at the last level have no `children`; they are listed on demand.
+pub fn list_directory(
+    cache: &DirectoryCache,
+    dir: &Path,
+    depth: usize,
+    sort: SortMode,
+    files: &FilesConfig,
+) -> Result<Vec<FileNode>, StorageError> {
+    if !dir.is_dir() {
+        return Err(StorageError::NotFound(dir.display().to_string()));
+    }
+
+    let mut nodes = cache.entries(dir, files)?;
+    if depth > 1 {
+        for node in nodes
+            .iter_mut()
+            .filter(|n| n.node_type == FileNodeType::Directory)
+        {
+            let children =
+                list_directory(cache, Path::new(&node.path), depth - 1, sort, files)?;
+            node.children = Some(children);
+        }
+    }
+    sort.sort(&mut nodes);
+    Ok(nodes)
+}
+
+/// Count the notes in a workspace, calling `progress` with the entries looked at
+/// and the notes found so far every few hundred entries
+pub fn count_notes(
+    root: &Path,
+    files: &FilesConfig,
+    mut progress: impl FnMut(usize, usize),
+) -> usize {
+    let mut scanned = 0;
+    let mut notes = 0;
+    for entry in WalkDir::new(root)
+        .follow_links(false)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !is_skipped(e, files))
+        .filter_map(|e| e.ok())
+    {
+        scanned += 1;
+        if entry.file_type().is_file() && is_markdown_file(entry.path()) {
+            notes += 1;
+        }
+        if scanned % SCAN_PROGRESS_EVERY == 0 {
+            progress(scanned, notes);
+        }
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn names(nodes: &[FileNode]) -> Vec<&str> {
+        nodes.iter().map(|n| n.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_list_directory_by_depth_and_sort() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("team/retros")).unwrap();
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        std::fs::create_dir_all(root.join(".chronicle")).unwrap();
+        std::fs::write(root.join("b.md"), "# B").unwrap();
+        std::fs::write(root.join("a.md"), "# A").unwrap();
+        std::fs::write(root.join("notes.json"), "{}").unwrap();
+        std::fs::write(root.join("team/plan.md"), "# Plan").unwrap();
+        std::fs::write(root.join("team/board.png"), "png").unwrap();
+
+        let cache = DirectoryCache::default();
+        let files = FilesConfig::default();
+        let top = list_directory(&cache, root, 1, SortMode::Name, &files).unwrap();
+        assert_eq!(names(&top), vec!["empty", "team", "a.md", "b.md"]);
+        assert!(top.iter().all(|n| n.children.is_none()));
+
+        let tree = list_directory(&cache, root, 2, SortMode::Name, &files).unwrap();
+        let team = tree.iter().find(|n| n.name == "team").unwrap();
+        let children = team.children.as_ref().unwrap();
+        assert_eq!(names(children), vec!["retros", "board.png", "plan.md"]);
+        assert_eq!(children[1].node_type, FileNodeType::Attachment);
+        assert!(children[0].children.is_none());
+    }
+
+    #[test]
+    fn test_cached_listing_until_invalidated() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("a.md"), "# A").unwrap();
+
+        let cache = DirectoryCache::default();
+        let files = FilesConfig::default();
+        let before = list_directory(&cache, root, 1, SortMode::Mtime, &files).unwrap();
+        let modified = before[0].modified_at;
+
+        // A note's content changing leaves its folder untouched; the watcher
+        // invalidates the listing
+        std::fs::write(root.join("a.md"), "# A\n\nlonger").unwrap();
+        let file = std::fs::File::options()
+            .write(true)
+            .open(root.join("a.md"))
+            .unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(
+            list_directory(&cache, root, 1, SortMode::Mtime, &files).unwrap()[0].modified_at,
+            modified
+        );
+
+        cache.invalidate(&root.join("a.md"));
+        assert_ne!(
+            list_directory(&cache, root, 1, SortMode::Mtime, &files).unwrap()[0].modified_at,
+            modified
+        );
+    }
+
+    #[test]
+    fn test_count_notes_reports_progress() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for i in 0..SCAN_PROGRESS_EVERY {
+            std::fs::write(root.join(format!("{}.md", i)), "").unwrap();
+        }
+        std::fs::write(root.join("photo.png"), "").unwrap();
+
+        let mut reports = Vec::new();
+        let notes = count_notes(root, &FilesConfig::default(), |scanned, notes| reports.push((scanned, notes)));
+        assert_eq!(notes, SCAN_PROGRESS_EVERY);
+        assert_eq!(reports.len(), 1);
+    }
+}
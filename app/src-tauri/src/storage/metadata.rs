@@ -1,10 +1,46 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use uuid::Uuid;
+use walkdir::WalkDir;
 
 use super::StorageError;
 
-/// Session metadata stored in .meta/ JSON files
+/// Note metadata, mirroring the notes' folder structure, kept in .chronicle/
+pub const META_DIR: &str = "meta";
+
+/// Per-folder metadata directory used before metadata moved into .chronicle/
+const LEGACY_META_DIR: &str = ".meta";
+
+/// Semantic markers (see the editor's marker syntax) added during a session
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MarkerCounts {
+    /// `> ` lines
+    pub highlights: u32,
+    /// `! ` lines
+    pub decisions: u32,
+    /// `? ` lines
+    pub questions: u32,
+    /// `[] ` lines
+    pub actions: u32,
+    /// `[x] ` lines
+    pub completed_actions: u32,
+    /// `@name` lines
+    pub mentions: u32,
+}
+
+impl MarkerCounts {
+    pub fn total(&self) -> u32 {
+        self.highlights
+            + self.decisions
+            + self.questions
+            + self.actions
+            + self.completed_actions
+            + self.mentions
+    }
+}
+
+/// Session metadata stored in .chronicle/meta/ JSON files
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMeta {
     pub started_at: DateTime<Utc>,
@@ -12,24 +48,38 @@ pub struct SessionMeta {
     pub duration_minutes: u32,
     pub annotation_count: u32,
     pub last_annotation_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub words_added: u32,
+    #[serde(default)]
+    pub words_removed: u32,
+    #[serde(default)]
+    pub markers: MarkerCounts,
 }
 
-/// File metadata stored in .meta/ JSON files
+/// File metadata stored in .chronicle/meta/ JSON files
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMeta {
     pub name: String,
     pub raw_path: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Template the note was created from, for naming patterns that use it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
 }
 
 /// Full metadata file structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoteMeta {
+    /// Stable note ID (a UUID) that survives renames and moves
     pub id: String,
     pub version: u32,
     pub file: FileMeta,
+    /// Most recent session
     pub session: Option<SessionMeta>,
+    /// Every recorded session, oldest first
+    #[serde(default)]
+    pub sessions: Vec<SessionMeta>,
 }
 
 impl NoteMeta {
@@ -41,63 +91,107 @@ impl NoteMeta {
             .unwrap_or_else(|| "untitled".to_string());
 
         let now = Utc::now();
-        let id = format!(
-            "{}-{}",
-            name.trim_end_matches(".md"),
-            now.format("%Y%m%d%H%M%S")
-        );
 
         Self {
-            id,
+            id: new_note_id(),
             version: 1,
             file: FileMeta {
                 name,
                 raw_path: None,
                 created_at: now,
                 updated_at: now,
+                template: None,
             },
             session: None,
+            sessions: Vec::new(),
         }
     }
 
+    /// Replace an ID from before stable IDs (`{stem}-{timestamp}`) with a UUID.
+    /// Returns whether the ID changed.
+    pub fn upgrade_id(&mut self) -> bool {
+        if is_note_id(&self.id) {
+            return false;
+        }
+        self.id = new_note_id();
+        true
+    }
+
     /// Update file metadata
     pub fn update_file(&mut self) {
         self.file.updated_at = Utc::now();
     }
 
-    /// Set session metadata
-    #[allow(dead_code)] // Will be used when storing session data in M6
-    pub fn set_session(&mut self, session: SessionMeta) {
+    /// Append a session to the history and make it the most recent one
+    pub fn record_session(&mut self, session: SessionMeta) {
+        self.sessions.push(session.clone());
         self.session = Some(session);
     }
 }
 
-/// Get the metadata file path for a note
-pub fn get_meta_path(note_path: &Path) -> PathBuf {
-    let workspace = note_path.parent().unwrap_or(Path::new("."));
-    let filename = note_path
-        .file_stem()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| "untitled".to_string());
+/// Generate a new stable note ID
+pub fn new_note_id() -> String {
+    Uuid::new_v4().to_string()
+}
 
-    workspace.join(".meta").join(format!("{}.json", filename))
+/// Whether a string is a stable note ID
+pub fn is_note_id(value: &str) -> bool {
+    value.len() == 36 && Uuid::parse_str(value).is_ok()
 }
 
-/// Ensure .meta directory exists
-pub fn ensure_meta_dir(workspace_path: &Path) -> Result<PathBuf, StorageError> {
-    let meta_dir = workspace_path.join(".meta");
-    if !meta_dir.exists() {
-        std::fs::create_dir_all(&meta_dir).map_err(|e| {
-            StorageError::WriteFailed(meta_dir.display().to_string(), e)
-        })?;
-        tracing::debug!("Created .meta directory at {}", meta_dir.display());
-    }
-    Ok(meta_dir)
+/// Key for a note: its workspace-relative path without the .md extension,
+/// e.g. `projects/standup`. Notes outside the workspace have none: a bare file
+/// name would let same-named notes collide.
+pub fn note_key(workspace_path: &Path, note_path: &Path) -> Result<String, StorageError> {
+    let relative = match note_path.strip_prefix(workspace_path) {
+        Ok(relative) => relative,
+        Err(_) if note_path.is_relative() => note_path,
+        Err(_) => {
+            return Err(StorageError::InvalidPath(format!(
+                "{} is outside the workspace",
+                note_path.display()
+            )))
+        }
+    };
+
+    Ok(relative
+        .with_extension("")
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+/// Workspace-relative note path with `/` separators, if the note is in the workspace
+pub fn relative_note_path(workspace_path: &Path, note_path: &Path) -> Option<String> {
+    let relative = note_path.strip_prefix(workspace_path).ok()?;
+    let parts = relative
+        .components()
+        .map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
+}
+
+/// Get the metadata file path for a note: .chronicle/meta/{relative path}.json
+pub fn get_meta_path(workspace_path: &Path, note_path: &Path) -> Result<PathBuf, StorageError> {
+    Ok(workspace_path
+        .join(".chronicle")
+        .join(META_DIR)
+        .join(format!("{}.json", note_key(workspace_path, note_path)?)))
 }
 
 /// Load metadata for a note
-pub fn load_metadata(note_path: &Path) -> Result<Option<NoteMeta>, StorageError> {
-    let meta_path = get_meta_path(note_path);
+pub fn load_metadata(
+    workspace_path: &Path,
+    note_path: &Path,
+) -> Result<Option<NoteMeta>, StorageError> {
+    let meta_path = get_meta_path(workspace_path, note_path)?;
 
     if !meta_path.exists() {
         return Ok(None);
@@ -114,21 +208,79 @@ pub fn load_metadata(note_path: &Path) -> Result<Option<NoteMeta>, StorageError>
 }
 
 /// Save metadata for a note
-pub fn save_metadata(note_path: &Path, meta: &NoteMeta) -> Result<(), StorageError> {
-    let workspace = note_path.parent().unwrap_or(Path::new("."));
-    ensure_meta_dir(workspace)?;
-
-    let meta_path = get_meta_path(note_path);
+pub fn save_metadata(
+    workspace_path: &Path,
+    note_path: &Path,
+    meta: &NoteMeta,
+) -> Result<(), StorageError> {
+    let meta_path = get_meta_path(workspace_path, note_path)?;
 
     let content = serde_json::to_string_pretty(meta)
         .map_err(|e| StorageError::SerializeError(e.to_string()))?;
 
-    super::write_file(&meta_path, &content)?;
+    super::write_file_atomic(&meta_path, &content)?;
 
     tracing::debug!("Saved metadata for {} to {}", note_path.display(), meta_path.display());
     Ok(())
 }
 
+/// Move metadata from per-folder `.meta/{stem}.json` files into .chronicle/meta/ and
+/// remove the emptied `.meta` directories. Existing entries in .chronicle/meta/ win.
+/// Returns the number of files moved.
+pub fn migrate_legacy_metadata(workspace_path: &Path) -> Result<usize, StorageError> {
+    let legacy_dirs: Vec<PathBuf> = WalkDir::new(workspace_path)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || e.file_name() == LEGACY_META_DIR
+                || !e.file_name().to_string_lossy().starts_with('.')
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir() && e.file_name() == LEGACY_META_DIR)
+        .map(|e| e.into_path())
+        .collect();
+
+    let mut moved = 0;
+    for dir in legacy_dirs {
+        let Some(note_dir) = dir.parent() else {
+            continue;
+        };
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| StorageError::ReadFailed(dir.display().to_string(), e))?;
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let legacy = entry.path();
+            if legacy.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(stem) = legacy.file_stem() else {
+                continue;
+            };
+
+            let note = note_dir.join(stem).with_extension("md");
+            let target = get_meta_path(workspace_path, &note)?;
+            if target.exists() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                super::ensure_dir(parent)?;
+            }
+            std::fs::rename(&legacy, &target)
+                .map_err(|e| StorageError::WriteFailed(target.display().to_string(), e))?;
+            moved += 1;
+        }
+
+        // Only removes the directory once nothing is left in it
+        let _ = std::fs::remove_dir(&dir);
+    }
+
+    if moved > 0 {
+        tracing::info!("Moved {} metadata files into .chronicle/{}", moved, META_DIR);
+    }
+    Ok(moved)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,9 +288,35 @@ mod tests {
 
     #[test]
     fn test_meta_path() {
-        let note_path = Path::new("/workspace/2024-12-13-test.md");
-        let meta_path = get_meta_path(note_path);
-        assert_eq!(meta_path, Path::new("/workspace/.meta/2024-12-13-test.json"));
+        let workspace = Path::new("/workspace");
+        let meta_path =
+            get_meta_path(workspace, Path::new("/workspace/2024-12-13-test.md")).unwrap();
+        assert_eq!(
+            meta_path,
+            Path::new("/workspace/.chronicle/meta/2024-12-13-test.json")
+        );
+
+        let nested = get_meta_path(workspace, Path::new("/workspace/projects/standup.md")).unwrap();
+        assert_eq!(
+            nested,
+            Path::new("/workspace/.chronicle/meta/projects/standup.json")
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_metadata() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join(".meta")).unwrap();
+        std::fs::create_dir_all(ws.join("projects/.meta")).unwrap();
+        std::fs::write(ws.join(".meta/top.json"), "{}").unwrap();
+        std::fs::write(ws.join("projects/.meta/standup.json"), "{}").unwrap();
+
+        assert_eq!(migrate_legacy_metadata(ws).unwrap(), 2);
+        assert!(ws.join(".chronicle/meta/top.json").exists());
+        assert!(ws.join(".chronicle/meta/projects/standup.json").exists());
+        assert!(!ws.join(".meta").exists());
+        assert!(!ws.join("projects/.meta").exists());
     }
 
     #[test]
@@ -150,21 +328,27 @@ mod tests {
         std::fs::write(&note_path, "# Test Note").unwrap();
 
         let mut meta = NoteMeta::new(&note_path);
-        meta.set_session(SessionMeta {
+        meta.record_session(SessionMeta {
             started_at: Utc::now(),
             ended_at: Some(Utc::now()),
             duration_minutes: 15,
             annotation_count: 2,
             last_annotation_at: Some(Utc::now()),
+            words_added: 40,
+            words_removed: 3,
+            markers: MarkerCounts::default(),
         });
 
         // Save
-        save_metadata(&note_path, &meta).unwrap();
+        save_metadata(dir.path(), &note_path, &meta).unwrap();
 
         // Load
-        let loaded = load_metadata(&note_path).unwrap().unwrap();
+        let loaded = load_metadata(dir.path(), &note_path).unwrap().unwrap();
         assert_eq!(loaded.id, meta.id);
+        assert!(is_note_id(&loaded.id));
         assert!(loaded.session.is_some());
         assert_eq!(loaded.session.unwrap().duration_minutes, 15);
+        assert_eq!(loaded.sessions.len(), 1);
+        assert_eq!(loaded.sessions[0].words_added, 40);
     }
 }
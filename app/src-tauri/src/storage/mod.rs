@@ -1,11 +1,25 @@
+pub mod config;
 pub mod files;
+pub mod front_matter;
+pub mod listing;
 pub mod metadata;
 pub mod naming;
+pub mod recent;
+pub mod timestamps;
+pub mod transaction;
+pub mod trust;
 pub mod validate;
 pub mod workspace;
 
+pub use config::*;
 pub use files::*;
+pub use front_matter::*;
+pub use listing::*;
 pub use metadata::*;
 pub use naming::*;
+pub use recent::*;
+pub use timestamps::*;
+pub use transaction::*;
+pub use trust::*;
 pub use validate::*;
 pub use workspace::*;
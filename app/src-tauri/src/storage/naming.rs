@@ -1,10 +1,19 @@
-use crate::storage::StorageError;
-use chrono::Utc;
+use crate::storage::{load_metadata, same_file, StorageError};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
-/// Extract title from first H1 heading in markdown content
+/// Extract title from front matter, falling back to the first H1 heading
 pub fn extract_title(content: &str) -> Option<String> {
-    for line in content.lines() {
+    if let Ok((front_matter, _)) = super::parse_front_matter(content) {
+        if let Some(title) = front_matter.title.filter(|t| !t.trim().is_empty()) {
+            return Some(title.trim().to_string());
+        }
+    }
+
+    let (_, body) = super::split_front_matter(content);
+    for line in body.lines() {
         let trimmed = line.trim();
         if trimmed.starts_with("# ") {
             return Some(trimmed[2..].trim().to_string());
@@ -38,22 +47,231 @@ pub fn generate_slug(title: &str) -> String {
     result.trim_matches('-').to_string()
 }
 
-/// Generate a filename from title with date prefix
-pub fn generate_filename(title: &str) -> String {
-    let date = Utc::now().format("%Y-%m-%d");
-    let slug = generate_slug(title);
-    if slug.is_empty() {
-        format!("{}-untitled.md", date)
-    } else {
-        format!("{}-{}.md", date, slug)
+/// Naming scheme file inside .chronicle/
+pub const NAMING_FILE: &str = "naming.toml";
+
+/// Default naming scheme written to .chronicle/naming.toml
+pub const DEFAULT_NAMING: &str = r#"# How Chronicle names new notes.
+#
+#   pattern         - path of a new note inside the workspace, without ".md".
+#                     Folders in the pattern file new notes automatically.
+#                       {slug}       - the note's title, as a slug
+#                       {date}       - creation date (YYYY-MM-DD)
+#                       {date:%Y/%m} - creation date in any strftime format
+#                       {template}   - template the note was created from, if any
+#                     e.g. "{date:%Y/%m}/{slug}" or "{template}/{template}-{date}"
+#   timezone        - "local" or "utc", for dates
+#   transliterate   - spell accented and other non-ASCII letters in ASCII
+#   max_slug_length - longest slug, cut at a word boundary (0 for no limit)
+pattern = "{date}-{slug}"
+timezone = "local"
+transliterate = true
+max_slug_length = 60
+"#;
+
+/// Timezone for dates in note names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NamingTimezone {
+    #[default]
+    Local,
+    Utc,
+}
+
+/// Per-workspace note naming, loaded from .chronicle/naming.toml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingScheme {
+    pub pattern: String,
+    pub timezone: NamingTimezone,
+    pub transliterate: bool,
+    pub max_slug_length: usize,
+}
+
+impl Default for NamingScheme {
+    fn default() -> Self {
+        Self {
+            pattern: "{date}-{slug}".to_string(),
+            timezone: NamingTimezone::Local,
+            transliterate: true,
+            max_slug_length: 60,
+        }
+    }
+}
+
+/// A pattern placeholder: `{name}` or `{name:argument}`
+enum Placeholder<'a> {
+    Literal(&'a str),
+    Slug,
+    Template,
+    Date(&'a str),
+}
+
+/// Split a pattern into literals and placeholders
+fn parse_pattern(pattern: &str) -> Result<Vec<Placeholder<'_>>, String> {
+    let mut parts = Vec::new();
+    let mut rest = pattern;
+    while let Some(open) = rest.find('{') {
+        if open > 0 {
+            parts.push(Placeholder::Literal(&rest[..open]));
+        }
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| "unclosed '{' in pattern".to_string())?
+            + open;
+        let (name, argument) = match rest[open + 1..close].split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (&rest[open + 1..close], None),
+        };
+        parts.push(match (name, argument) {
+            ("slug", None) => Placeholder::Slug,
+            ("template", None) => Placeholder::Template,
+            ("date", format) => {
+                let format = format.unwrap_or("%Y-%m-%d");
+                if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                    return Err(format!("invalid date format '{}'", format));
+                }
+                Placeholder::Date(format)
+            }
+            _ => {
+                return Err(format!(
+                    "unknown placeholder '{{{}}}'",
+                    &rest[open + 1..close]
+                ))
+            }
+        });
+        rest = &rest[close + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Placeholder::Literal(rest));
+    }
+    Ok(parts)
+}
+
+/// Tidy one path segment of a rendered pattern: placeholders that rendered empty
+/// leave doubled or dangling dashes behind
+fn clean_segment(segment: &str) -> String {
+    let mut cleaned = String::new();
+    for c in segment.chars() {
+        if c == '-' && cleaned.ends_with('-') {
+            continue;
+        }
+        cleaned.push(c);
+    }
+    cleaned
+        .trim_matches(|c: char| c == '-' || c == '.' || c.is_whitespace())
+        .to_string()
+}
+
+/// Cut a slug to at most `max` characters, at a word boundary when there is one
+fn truncate_slug(slug: &str, max: usize) -> String {
+    if max == 0 || slug.chars().count() <= max {
+        return slug.to_string();
+    }
+    let cut: String = slug.chars().take(max).collect();
+    let at_boundary = slug.chars().nth(max) == Some('-');
+    let cut = match cut.rfind('-') {
+        Some(dash) if !at_boundary && dash > 0 => &cut[..dash],
+        _ => cut.as_str(),
+    };
+    cut.trim_matches('-').to_string()
+}
+
+impl NamingScheme {
+    /// Parse and validate a naming scheme
+    pub fn parse(content: &str) -> Result<Self, StorageError> {
+        let scheme: NamingScheme = toml::from_str(content)
+            .map_err(|e| StorageError::ParseError(NAMING_FILE.to_string(), e.to_string()))?;
+        if scheme.pattern.trim().is_empty() || scheme.pattern.starts_with('/') {
+            return Err(StorageError::ParseError(
+                NAMING_FILE.to_string(),
+                "pattern must be a relative path".to_string(),
+            ));
+        }
+        parse_pattern(&scheme.pattern)
+            .map_err(|e| StorageError::ParseError(NAMING_FILE.to_string(), e))?;
+        Ok(scheme)
+    }
+
+    /// Load the workspace naming scheme. Falls back to the default when the file is
+    /// missing or invalid: a broken scheme shouldn't stop notes from being saved.
+    pub fn load(workspace_path: &Path) -> Self {
+        let path = workspace_path.join(".chronicle").join(NAMING_FILE);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            tracing::warn!("Using default note naming: {}", e);
+            Self::default()
+        })
+    }
+
+    /// Slug for a title, transliterated and shortened as configured
+    pub fn slug(&self, title: &str) -> String {
+        let slug = if self.transliterate {
+            generate_slug(&deunicode::deunicode(title))
+        } else {
+            generate_slug(title)
+        };
+        truncate_slug(&slug, self.max_slug_length)
+    }
+
+    /// Workspace-relative path (with ".md") for a note with this title
+    pub fn note_path(
+        &self,
+        title: &str,
+        template: Option<&str>,
+        created: DateTime<Utc>,
+    ) -> PathBuf {
+        let parts = parse_pattern(&self.pattern).unwrap_or_default();
+        let slug = self.slug(title);
+        let template = template
+            .map(|t| generate_slug(t.strip_suffix(".md").unwrap_or(t)))
+            .unwrap_or_default();
+
+        let mut rendered = String::new();
+        for part in &parts {
+            match part {
+                Placeholder::Literal(text) => rendered.push_str(text),
+                Placeholder::Slug => rendered.push_str(&slug),
+                Placeholder::Template => rendered.push_str(&template),
+                Placeholder::Date(format) => {
+                    let date = match self.timezone {
+                        NamingTimezone::Local => created.with_timezone(&Local).format(format),
+                        NamingTimezone::Utc => created.format(format),
+                    };
+                    rendered.push_str(&date.to_string());
+                }
+            }
+        }
+
+        let mut segments: Vec<String> = rendered.split('/').map(clean_segment).collect();
+        let file_name = segments.pop().filter(|s| !s.is_empty());
+        let mut path: PathBuf = segments.into_iter().filter(|s| !s.is_empty()).collect();
+        path.push(format!(
+            "{}.md",
+            file_name.unwrap_or_else(|| "untitled".to_string())
+        ));
+        path
+    }
+
+    /// File name part of `note_path`
+    pub fn file_name(&self, title: &str, template: Option<&str>, created: DateTime<Utc>) -> String {
+        self.note_path(title, template, created)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "untitled.md".to_string())
     }
 }
 
-/// Suggest a new path for a file based on its H1 heading
-/// Returns None if no rename is needed
-pub fn suggest_path(current_path: &Path, content: &str) -> Option<PathBuf> {
+/// Suggest a new file name for a note after its title changed, keeping its folder
+/// and the date it was created. Returns None if no rename is needed.
+pub fn suggest_path(workspace_path: &Path, current_path: &Path, content: &str) -> Option<PathBuf> {
     let title = extract_title(content)?;
-    let new_filename = generate_filename(&title);
+    let meta = load_metadata(workspace_path, current_path).ok().flatten();
+    let created = meta.as_ref().map(|m| m.file.created_at).unwrap_or_else(Utc::now);
+    let template = meta.as_ref().and_then(|m| m.file.template.as_deref());
+    let new_filename = NamingScheme::load(workspace_path).file_name(&title, template, created);
 
     let current_filename = current_path.file_name()?.to_str()?;
     if current_filename == new_filename {
@@ -64,15 +282,29 @@ pub fn suggest_path(current_path: &Path, content: &str) -> Option<PathBuf> {
     Some(parent.join(new_filename))
 }
 
-/// Generate a unique path for a new file, adding numeric suffix if needed
-pub fn generate_unique_path(workspace: &Path, content: &str) -> PathBuf {
+/// Path for a new note named by the workspace naming scheme. In `folder` only the
+/// file name part of the pattern applies; otherwise the pattern's folders file the
+/// note. Adds a numeric suffix when the path is taken.
+pub fn new_note_path(
+    workspace_path: &Path,
+    folder: Option<&Path>,
+    content: &str,
+    template: Option<&str>,
+) -> PathBuf {
     let title = extract_title(content).unwrap_or_else(|| "untitled".to_string());
-    let base_filename = generate_filename(&title);
-    let base_path = workspace.join(&base_filename);
+    let relative = NamingScheme::load(workspace_path).note_path(&title, template, Utc::now());
+    let base_path = match (folder, relative.file_name()) {
+        (Some(folder), Some(file_name)) if folder != workspace_path => folder.join(file_name),
+        _ => workspace_path.join(relative),
+    };
+    unique_path(&base_path)
+}
 
+/// `base_path`, or `base_path` with a numeric suffix when it already exists
+fn unique_path(base_path: &Path) -> PathBuf {
     // If path doesn't exist, use it directly
     if !base_path.exists() {
-        return base_path;
+        return base_path.to_path_buf();
     }
 
     // Add numeric suffix until we find a unique name
@@ -80,10 +312,11 @@ pub fn generate_unique_path(workspace: &Path, content: &str) -> PathBuf {
         .file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or("note");
+    let parent = base_path.parent().unwrap_or(Path::new("."));
 
     let mut counter = 2;
     loop {
-        let candidate = workspace.join(format!("{}-{}.md", stem, counter));
+        let candidate = parent.join(format!("{}-{}.md", stem, counter));
         if !candidate.exists() {
             return candidate;
         }
@@ -91,19 +324,23 @@ pub fn generate_unique_path(workspace: &Path, content: &str) -> PathBuf {
         if counter > 100 {
             // Fallback: use timestamp
             let ts = Utc::now().format("%H%M%S");
-            return workspace.join(format!("{}-{}.md", stem, ts));
+            return parent.join(format!("{}-{}.md", stem, ts));
         }
     }
 }
 
-/// Rename a file, handling conflicts by adding a numeric suffix
-pub fn rename_file(old_path: &Path, new_path: &Path) -> Result<PathBuf, StorageError> {
-    if old_path == new_path {
-        return Ok(old_path.to_path_buf());
+/// Target for moving `old_path` to `new_path`: `new_path` itself, or `new_path` with
+/// a numeric suffix when another file already exists there
+pub fn available_path(old_path: &Path, new_path: &Path) -> Result<PathBuf, StorageError> {
+    if !new_path.exists() || new_path == old_path || same_file(old_path, new_path) {
+        return Ok(new_path.to_path_buf());
     }
 
-    // Handle conflict by adding suffix
-    let final_path = if new_path.exists() && new_path != old_path {
+    // Folders keep their full name and get no extension
+    let (stem, ext) = if old_path.is_dir() {
+        let name = new_path.file_name().and_then(|s| s.to_str()).unwrap_or("folder");
+        (name, String::new())
+    } else {
         let stem = new_path
             .file_stem()
             .and_then(|s| s.to_str())
@@ -112,35 +349,50 @@ pub fn rename_file(old_path: &Path, new_path: &Path) -> Result<PathBuf, StorageE
             .extension()
             .and_then(|s| s.to_str())
             .unwrap_or("md");
-        let parent = new_path.parent().unwrap_or(Path::new("."));
-
-        let mut counter = 1;
-        loop {
-            let candidate = parent.join(format!("{}-{}.{}", stem, counter, ext));
-            if !candidate.exists() {
-                break candidate;
-            }
-            counter += 1;
-            if counter > 100 {
-                return Err(StorageError::WriteFailed(
-                    new_path.display().to_string(),
-                    std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Too many conflicts"),
-                ));
-            }
-        }
-    } else {
-        new_path.to_path_buf()
+        (stem, format!(".{}", ext))
     };
+    let parent = new_path.parent().unwrap_or(Path::new("."));
 
-    std::fs::rename(old_path, &final_path)
-        .map_err(|e| StorageError::WriteFailed(final_path.display().to_string(), e))?;
-
-    Ok(final_path)
+    let mut counter = 1;
+    loop {
+        let candidate = parent.join(format!("{}-{}{}", stem, counter, ext));
+        if !candidate.exists() {
+            return Ok(candidate);
+        }
+        counter += 1;
+        if counter > 100 {
+            return Err(StorageError::WriteFailed(
+                new_path.display().to_string(),
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Too many conflicts"),
+            ));
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_available_path_keeps_the_same_file() {
+        let dir = tempdir().unwrap();
+        let standup = dir.path().join("standup.md");
+        std::fs::write(&standup, "# Standup").unwrap();
+        std::fs::write(dir.path().join("other.md"), "# Other").unwrap();
+
+        // Another name for the same file, as a case-only rename is on a
+        // case-insensitive filesystem
+        let alias = dir.path().join("Standup.md");
+        std::fs::hard_link(&standup, &alias).unwrap();
+        assert_eq!(available_path(&standup, &alias).unwrap(), alias);
+
+        assert_eq!(
+            available_path(&standup, &dir.path().join("other.md")).unwrap(),
+            dir.path().join("other-1.md")
+        );
+    }
 
     #[test]
     fn test_extract_title() {
@@ -148,6 +400,14 @@ mod tests {
         assert_eq!(extract_title("## Not H1\n# This is H1"), Some("This is H1".to_string()));
         assert_eq!(extract_title("No heading here"), None);
         assert_eq!(extract_title("#NoSpace"), None);
+        assert_eq!(
+            extract_title("---\ntitle: From Front Matter\n---\n# Heading"),
+            Some("From Front Matter".to_string())
+        );
+        assert_eq!(
+            extract_title("---\ndate: 2026-02-22\n---\n# Heading"),
+            Some("Heading".to_string())
+        );
     }
 
     #[test]
@@ -160,8 +420,53 @@ mod tests {
 
     #[test]
     fn test_generate_filename() {
-        let filename = generate_filename("My Note Title");
+        let scheme = NamingScheme::default();
+        let filename = scheme.file_name("My Note Title", None, Utc::now());
         assert!(filename.ends_with("-my-note-title.md"));
         assert!(filename.starts_with("20")); // Starts with year
+        assert_eq!(NamingScheme::parse(DEFAULT_NAMING).unwrap(), scheme);
+    }
+
+    #[test]
+    fn test_naming_scheme_patterns() {
+        let created = Utc.with_ymd_and_hms(2026, 3, 2, 23, 30, 0).unwrap();
+        let scheme = NamingScheme::parse(
+            "pattern = \"{date:%Y/%m}/{template}-{slug}\"\ntimezone = \"utc\"\nmax_slug_length = 20",
+        )
+        .unwrap();
+
+        assert_eq!(
+            scheme.note_path("Réunion d'équipe à Zürich", Some("meeting.md"), created),
+            PathBuf::from("2026/03/meeting-reunion-d-equipe-a.md")
+        );
+        // No template: the empty placeholder leaves no stray dash
+        assert_eq!(
+            scheme.note_path("Plan", None, created),
+            PathBuf::from("2026/03/plan.md")
+        );
+
+        assert!(NamingScheme::parse("pattern = \"{slug}-{nope}\"").is_err());
+        assert!(NamingScheme::parse("pattern = \"{date:%Q}\"").is_err());
+    }
+
+    #[test]
+    fn test_new_note_path_files_into_folders() {
+        let dir = tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::create_dir_all(ws.join(".chronicle")).unwrap();
+        std::fs::write(
+            ws.join(".chronicle").join(NAMING_FILE),
+            "pattern = \"{template}/{slug}\"",
+        )
+        .unwrap();
+
+        let path = new_note_path(ws, None, "# Weekly Sync", Some("meeting.md"));
+        assert_eq!(path, ws.join("meeting/weekly-sync.md"));
+
+        // An explicit folder keeps only the file name
+        std::fs::create_dir_all(ws.join("projects")).unwrap();
+        std::fs::write(ws.join("projects/weekly-sync.md"), "").unwrap();
+        let path = new_note_path(ws, Some(&ws.join("projects")), "# Weekly Sync", None);
+        assert_eq!(path, ws.join("projects/weekly-sync-2.md"));
     }
 }
@@ -0,0 +1,197 @@
+use crate::models::{RecentWorkspaces, Workspace};
+use crate::storage::{get_app_data_dir, StorageError};
+use std::path::Path;
+
+const RECENT_WORKSPACES_FILE: &str = "recent_workspaces.json";
+
+/// Unpinned workspaces kept in the list; pinned ones are always kept
+const MAX_RECENT_WORKSPACES: usize = 10;
+
+impl RecentWorkspaces {
+    /// Load the list from `data_dir`; a missing file is an empty list
+    pub fn load(data_dir: &Path) -> Result<Self, StorageError> {
+        let path = data_dir.join(RECENT_WORKSPACES_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = crate::storage::read_file(&path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&mut self, data_dir: &Path) -> Result<(), StorageError> {
+        crate::storage::ensure_dir(data_dir)?;
+        self.version = 1;
+        let content = serde_json::to_string_pretty(self)?;
+        crate::storage::write_file_atomic(&data_dir.join(RECENT_WORKSPACES_FILE), &content)
+    }
+
+    fn find_mut(&mut self, path: &str) -> Result<&mut Workspace, StorageError> {
+        self.workspaces
+            .iter_mut()
+            .find(|w| w.path == path)
+            .ok_or_else(|| StorageError::NotFound(path.to_string()))
+    }
+
+    /// Pinned workspaces first, then the rest by when they were opened, dropping
+    /// unpinned ones past the limit
+    fn tidy(&mut self) {
+        self.workspaces.sort_by_key(|w| !w.pinned);
+        let mut unpinned = 0;
+        self.workspaces.retain(|w| {
+            unpinned += usize::from(!w.pinned);
+            w.pinned || unpinned <= MAX_RECENT_WORKSPACES
+        });
+    }
+
+    /// Move a workspace that was just opened to the top, keeping its pin and
+    /// display name
+    pub fn touch(&mut self, mut workspace: Workspace) {
+        if let Some(index) = self
+            .workspaces
+            .iter()
+            .position(|w| w.path == workspace.path)
+        {
+            let previous = self.workspaces.remove(index);
+            workspace.pinned = previous.pinned;
+            workspace.display_name = previous.display_name;
+        }
+        self.workspaces.insert(0, workspace);
+        self.tidy();
+    }
+
+    pub fn set_pinned(&mut self, path: &str, pinned: bool) -> Result<(), StorageError> {
+        self.find_mut(path)?.pinned = pinned;
+        self.tidy();
+        Ok(())
+    }
+
+    pub fn remove(&mut self, path: &str) -> Result<(), StorageError> {
+        self.find_mut(path)?;
+        self.workspaces.retain(|w| w.path != path);
+        Ok(())
+    }
+
+    /// Show a workspace under another name; a blank name shows its folder's again
+    pub fn rename(&mut self, path: &str, display_name: Option<&str>) -> Result<(), StorageError> {
+        self.find_mut(path)?.display_name = display_name
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::to_string);
+        Ok(())
+    }
+
+    /// Point an entry at the folder a workspace was moved to. An entry already
+    /// listed for that folder is replaced.
+    pub fn relocate(&mut self, from: &str, to: &str, name: &str) -> Result<(), StorageError> {
+        self.find_mut(from)?;
+        if from == to {
+            return Ok(());
+        }
+        self.workspaces.retain(|w| w.path != to);
+        let workspace = self.find_mut(from)?;
+        workspace.path = to.to_string();
+        workspace.name = name.to_string();
+        Ok(())
+    }
+}
+
+/// Get list of recently opened workspaces
+pub fn get_recent_workspaces() -> Result<RecentWorkspaces, StorageError> {
+    let Some(data_dir) = get_app_data_dir() else {
+        return Ok(RecentWorkspaces::default());
+    };
+    RecentWorkspaces::load(&data_dir)
+}
+
+/// Change the recent list and save it, returning the updated list. A list that
+/// can't be read is left alone rather than replaced.
+pub fn update_recent_workspaces(
+    change: impl FnOnce(&mut RecentWorkspaces) -> Result<(), StorageError>,
+) -> Result<RecentWorkspaces, StorageError> {
+    let Some(data_dir) = get_app_data_dir() else {
+        return Err(StorageError::InvalidPath(
+            "Could not determine app data directory".to_string(),
+        ));
+    };
+    let mut recent = RecentWorkspaces::load(&data_dir)?;
+    change(&mut recent)?;
+    recent.save(&data_dir)?;
+    Ok(recent)
+}
+
+/// Save a workspace to the recent list
+pub fn save_recent_workspace(workspace: &Workspace) -> Result<(), StorageError> {
+    update_recent_workspaces(|recent| {
+        recent.touch(workspace.clone());
+        Ok(())
+    })
+    .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tempfile::tempdir;
+
+    fn workspace(path: &str) -> Workspace {
+        Workspace {
+            path: path.to_string(),
+            name: path.trim_start_matches('/').to_string(),
+            last_opened: Utc::now(),
+            pinned: false,
+            display_name: None,
+        }
+    }
+
+    fn paths(recent: &RecentWorkspaces) -> Vec<&str> {
+        recent.workspaces.iter().map(|w| w.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_pinned_workspaces_stay_listed() {
+        let mut recent = RecentWorkspaces::default();
+        recent.touch(workspace("/work"));
+        recent.set_pinned("/work", true).unwrap();
+        recent.rename("/work", Some(" Work notes ")).unwrap();
+        for i in 0..MAX_RECENT_WORKSPACES + 2 {
+            recent.touch(workspace(&format!("/other-{}", i)));
+        }
+
+        assert_eq!(recent.workspaces.len(), MAX_RECENT_WORKSPACES + 1);
+        assert_eq!(recent.workspaces[0].path, "/work");
+        assert_eq!(recent.workspaces[1].path, "/other-11");
+
+        // Reopening keeps the pin and the name the user gave it
+        recent.touch(workspace("/work"));
+        assert!(recent.workspaces[0].pinned);
+        assert_eq!(
+            recent.workspaces[0].display_name.as_deref(),
+            Some("Work notes")
+        );
+
+        assert!(recent.set_pinned("/unknown", true).is_err());
+    }
+
+    #[test]
+    fn test_remove_and_relocate() {
+        let dir = tempdir().unwrap();
+        let mut recent = RecentWorkspaces::default();
+        recent.touch(workspace("/old"));
+        recent.touch(workspace("/new"));
+        recent.touch(workspace("/scratch"));
+        recent.set_pinned("/old", true).unwrap();
+
+        recent.remove("/scratch").unwrap();
+        recent.relocate("/new", "/new", "new").unwrap();
+        assert_eq!(paths(&recent), vec!["/old", "/new"]);
+        recent.relocate("/old", "/new", "new").unwrap();
+        assert_eq!(paths(&recent), vec!["/new"]);
+        assert!(recent.workspaces[0].pinned);
+
+        recent.save(dir.path()).unwrap();
+        let loaded = RecentWorkspaces::load(dir.path()).unwrap();
+        assert_eq!(paths(&loaded), vec!["/new"]);
+        assert_eq!(loaded.workspaces[0].name, "new");
+    }
+}
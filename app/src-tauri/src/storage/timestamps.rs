@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::StorageError;
+
+/// Read a JSON object of `{ "key": "RFC 3339 timestamp" }` entries.
+/// Missing files and entries that aren't timestamps are ignored.
+pub fn read_timestamps(path: &Path) -> HashMap<String, DateTime<Utc>> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return HashMap::new();
+    };
+    let Ok(Value::Object(map)) = serde_json::from_str::<Value>(&content) else {
+        return HashMap::new();
+    };
+
+    map.into_iter()
+        .filter_map(|(key, ts)| {
+            let ts = DateTime::parse_from_rfc3339(ts.as_str()?).ok()?;
+            Some((key, ts.with_timezone(&Utc)))
+        })
+        .collect()
+}
+
+/// Set one entry in a timestamp file, preserving all other entries
+pub fn write_timestamp(path: &Path, key: &str, at: DateTime<Utc>) -> Result<(), StorageError> {
+    let mut map = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<Map<String, Value>>(&c).ok())
+        .unwrap_or_default();
+
+    map.insert(key.to_string(), Value::String(at.to_rfc3339()));
+
+    let content = serde_json::to_string_pretty(&map)?;
+    super::write_file_atomic(path, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_and_read_timestamps() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("runs.json");
+        std::fs::write(&path, r#"{"digest": "not a date"}"#).unwrap();
+
+        let at = Utc::now();
+        write_timestamp(&path, "tagger", at).unwrap();
+
+        let runs = read_timestamps(&path);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs["tagger"].timestamp(), at.timestamp());
+
+        // Unparseable entries are preserved on disk
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(raw.contains("not a date"));
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        let dir = tempdir().unwrap();
+        assert!(read_timestamps(&dir.path().join("missing.json")).is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::StorageError;
+
+/// How to undo one step of a transaction
+enum Undo {
+    /// Move a file or directory back to where it came from
+    Rename { from: PathBuf, to: PathBuf },
+    /// Put back a file's previous content, or remove it if it didn't exist
+    Restore {
+        path: PathBuf,
+        previous: Option<Vec<u8>>,
+    },
+}
+
+/// A group of file operations that either all apply or are all undone.
+/// Dropping the transaction without calling `commit` rolls it back.
+pub struct FileTransaction {
+    undo: Vec<Undo>,
+    committed: bool,
+}
+
+impl FileTransaction {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            committed: false,
+        }
+    }
+
+    /// Move a file or directory, creating the target's parent directories.
+    /// Fails rather than overwriting an existing target other than `from` itself.
+    pub fn rename(&mut self, from: &Path, to: &Path) -> Result<(), StorageError> {
+        if to.exists() && !super::same_file(from, to) {
+            return Err(StorageError::WriteFailed(
+                to.display().to_string(),
+                std::io::Error::new(std::io::ErrorKind::AlreadyExists, "Target already exists"),
+            ));
+        }
+        if let Some(parent) = to.parent() {
+            super::ensure_dir(parent)?;
+        }
+
+        fs::rename(from, to).map_err(|e| StorageError::WriteFailed(to.display().to_string(), e))?;
+        self.undo.push(Undo::Rename {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    /// Write a file, keeping its previous content for rollback
+    pub fn write(&mut self, path: &Path, content: &str) -> Result<(), StorageError> {
+        let previous = fs::read(path).ok();
+        super::write_file_atomic(path, content)?;
+        self.undo.push(Undo::Restore {
+            path: path.to_path_buf(),
+            previous,
+        });
+        Ok(())
+    }
+
+    /// Remove a file, keeping its content for rollback. Missing files are ignored.
+    pub fn remove(&mut self, path: &Path) -> Result<(), StorageError> {
+        let Ok(previous) = fs::read(path) else {
+            return Ok(());
+        };
+
+        fs::remove_file(path)
+            .map_err(|e| StorageError::WriteFailed(path.display().to_string(), e))?;
+        self.undo.push(Undo::Restore {
+            path: path.to_path_buf(),
+            previous: Some(previous),
+        });
+        Ok(())
+    }
+
+    /// Every path written, removed or moved so far, in order
+    pub fn touched_paths(&self) -> Vec<PathBuf> {
+        self.undo
+            .iter()
+            .flat_map(|undo| match undo {
+                Undo::Rename { from, to } => vec![from.clone(), to.clone()],
+                Undo::Restore { path, .. } => vec![path.clone()],
+            })
+            .collect()
+    }
+
+    /// Keep every change made so far
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+
+    /// Undo every change, newest first. Best effort: failures are logged.
+    fn rollback(&mut self) {
+        while let Some(step) = self.undo.pop() {
+            let result = match &step {
+                Undo::Rename { from, to } => fs::rename(to, from),
+                Undo::Restore {
+                    path,
+                    previous: Some(content),
+                } => fs::write(path, content),
+                Undo::Restore {
+                    path,
+                    previous: None,
+                } => fs::remove_file(path),
+            };
+            if let Err(e) = result {
+                let path = match &step {
+                    Undo::Rename { from, .. } => from,
+                    Undo::Restore { path, .. } => path,
+                };
+                tracing::error!("Failed to roll back {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+impl Default for FileTransaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for FileTransaction {
+    fn drop(&mut self) {
+        if !self.committed && !self.undo.is_empty() {
+            tracing::warn!("Rolling back {} file operation(s)", self.undo.len());
+            self.rollback();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_drop_rolls_back() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("sub/b.md");
+        let index = dir.path().join("index.json");
+        let extra = dir.path().join("extra.json");
+        std::fs::write(&a, "note").unwrap();
+        std::fs::write(&index, "old").unwrap();
+        std::fs::write(&extra, "extra").unwrap();
+
+        {
+            let mut tx = FileTransaction::new();
+            tx.rename(&a, &b).unwrap();
+            tx.write(&index, "new").unwrap();
+            tx.write(&dir.path().join("created.json"), "{}").unwrap();
+            tx.remove(&extra).unwrap();
+            assert!(b.exists());
+        }
+
+        assert_eq!(std::fs::read_to_string(&a).unwrap(), "note");
+        assert!(!b.exists());
+        assert_eq!(std::fs::read_to_string(&index).unwrap(), "old");
+        assert!(!dir.path().join("created.json").exists());
+        assert_eq!(std::fs::read_to_string(&extra).unwrap(), "extra");
+    }
+
+    #[test]
+    fn test_commit_keeps_changes() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.md");
+        let b = dir.path().join("b.md");
+        std::fs::write(&a, "note").unwrap();
+
+        let mut tx = FileTransaction::new();
+        tx.rename(&a, &b).unwrap();
+        tx.commit();
+
+        assert!(b.exists());
+        assert!(!a.exists());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::storage::{get_app_data_dir, StorageError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Workspaces the user agreed to run agents in. Kept in the app data directory,
+/// never in the workspace, so a cloned workspace can't mark itself trusted.
+const TRUSTED_WORKSPACES_FILE: &str = "trusted_workspaces.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedWorkspace {
+    /// Canonical workspace path
+    pub path: PathBuf,
+    pub trusted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    pub version: u32,
+    pub workspaces: Vec<TrustedWorkspace>,
+}
+
+impl TrustStore {
+    /// Load the store from `data_dir`; a missing file means nothing is trusted
+    pub fn load(data_dir: &Path) -> Result<Self, StorageError> {
+        let path = data_dir.join(TRUSTED_WORKSPACES_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = crate::storage::read_file(&path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&mut self, data_dir: &Path) -> Result<(), StorageError> {
+        crate::storage::ensure_dir(data_dir)?;
+        self.version = 1;
+        let content = serde_json::to_string_pretty(self)?;
+        crate::storage::write_file_atomic(&data_dir.join(TRUSTED_WORKSPACES_FILE), &content)
+    }
+
+    pub fn is_trusted(&self, workspace: &Path) -> bool {
+        let Ok(path) = workspace.canonicalize() else {
+            return false;
+        };
+        self.workspaces.iter().any(|w| w.path == path)
+    }
+
+    /// Trust or distrust a workspace
+    pub fn set_trusted(&mut self, workspace: &Path, trusted: bool) -> Result<(), StorageError> {
+        let path = workspace
+            .canonicalize()
+            .map_err(|e| StorageError::InvalidPath(format!("{}: {}", workspace.display(), e)))?;
+        self.workspaces.retain(|w| w.path != path);
+        if trusted {
+            self.workspaces.push(TrustedWorkspace {
+                path,
+                trusted_at: Utc::now(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Whether the user trusts the workspace to run agents and commands
+pub fn is_workspace_trusted(workspace: &Path) -> bool {
+    get_app_data_dir()
+        .and_then(|dir| TrustStore::load(&dir).ok())
+        .is_some_and(|store| store.is_trusted(workspace))
+}
+
+/// Record the user's trust decision for a workspace
+pub fn set_workspace_trust(workspace: &Path, trusted: bool) -> Result<(), StorageError> {
+    let Some(data_dir) = get_app_data_dir() else {
+        return Err(StorageError::InvalidPath(
+            "Could not determine app data directory".to_string(),
+        ));
+    };
+    let mut store = TrustStore::load(&data_dir)?;
+    store.set_trusted(workspace, trusted)?;
+    store.save(&data_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_trust_is_per_canonical_workspace() {
+        let data = tempdir().unwrap();
+        let ws = tempdir().unwrap();
+        std::fs::create_dir(ws.path().join("notes")).unwrap();
+        let other = tempdir().unwrap();
+
+        let mut store = TrustStore::load(data.path()).unwrap();
+        assert!(!store.is_trusted(ws.path()));
+
+        store.set_trusted(ws.path(), true).unwrap();
+        store.save(data.path()).unwrap();
+
+        let mut store = TrustStore::load(data.path()).unwrap();
+        assert!(store.is_trusted(ws.path()));
+        assert!(store.is_trusted(&ws.path().join("notes/..")));
+        assert!(!store.is_trusted(other.path()));
+        assert!(!store.is_trusted(&ws.path().join("notes")));
+
+        store.set_trusted(ws.path(), false).unwrap();
+        assert!(!store.is_trusted(ws.path()));
+    }
+}
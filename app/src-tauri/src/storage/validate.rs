@@ -1,4 +1,7 @@
-use std::path::{Path, PathBuf};
+use std::ops::Deref;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{SharedAppState, WorkspaceSession};
 
 /// Validates that a target path is within the workspace boundary.
 /// Prevents path traversal attacks (../ escaping workspace).
@@ -12,20 +15,7 @@ pub fn validate_workspace_path(workspace: &Path, target: &Path) -> Result<PathBu
         workspace.join(target)
     };
 
-    // For existing files, canonicalize fully
-    // For new files, canonicalize parent directory
-    let canonical_target = if resolved.exists() {
-        resolved.canonicalize()
-            .map_err(|e| format!("Invalid target path: {}", e))?
-    } else {
-        let parent = resolved.parent()
-            .ok_or("Invalid path: no parent directory")?;
-        let parent_canonical = parent.canonicalize()
-            .map_err(|e| format!("Invalid parent path: {}", e))?;
-        let filename = resolved.file_name()
-            .ok_or("Invalid path: no filename")?;
-        parent_canonical.join(filename)
-    };
+    let canonical_target = canonicalize_lenient(&resolved)?;
 
     if !canonical_target.starts_with(&canonical_workspace) {
         return Err("Path is outside workspace boundary".to_string());
@@ -34,6 +24,171 @@ pub fn validate_workspace_path(workspace: &Path, target: &Path) -> Result<PathBu
     Ok(canonical_target)
 }
 
+/// Canonicalize a path that may not exist yet. For new files, the nearest existing
+/// ancestor is canonicalized and the rest appended, which may only be plain names
+/// (no `..`).
+fn canonicalize_lenient(resolved: &Path) -> Result<PathBuf, String> {
+    if resolved.exists() {
+        return resolved
+            .canonicalize()
+            .map_err(|e| format!("Invalid target path: {}", e));
+    }
+    let mut existing = resolved;
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        missing.push(existing.file_name().ok_or("Invalid path: no filename")?);
+        existing = existing.parent().ok_or("Invalid path: no parent directory")?;
+    }
+    let mut canonical = existing
+        .canonicalize()
+        .map_err(|e| format!("Invalid parent path: {}", e))?;
+    for name in missing.into_iter().rev() {
+        canonical.push(name);
+    }
+    Ok(canonical)
+}
+
+/// A path inside a workspace open in `SharedAppState`. Only built by validating
+/// against that workspace, so a command holding one can't be pointed outside it
+/// with `../`, absolute paths or symlinks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspacePath {
+    /// ID of the workspace session
+    id: String,
+    /// Workspace root as it was opened (what the frontend knows it by)
+    opened: PathBuf,
+    /// Canonical workspace root
+    root: PathBuf,
+    /// Canonical path, inside `root`
+    path: PathBuf,
+}
+
+impl WorkspacePath {
+    /// The active workspace's root
+    pub async fn workspace(state: &SharedAppState) -> Result<Self, String> {
+        let app_state = state.read().await;
+        let session = app_state
+            .active()
+            .ok_or_else(|| "No workspace open".to_string())?;
+        Self::root_of(session)
+    }
+
+    /// A path from the frontend: absolute inside any open workspace, or relative
+    /// to the active one
+    pub async fn resolve(state: &SharedAppState, target: &str) -> Result<Self, String> {
+        if Path::new(target).is_absolute() {
+            let canonical = canonicalize_lenient(Path::new(target))?;
+            let app_state = state.read().await;
+            if let Some(session) = app_state.workspace_containing(&canonical) {
+                return Self::root_of(session)?.join(target);
+            }
+        }
+        Self::workspace(state).await?.join(target)
+    }
+
+    /// A workspace the frontend names by ID or root path, which must be open
+    pub async fn check_workspace(state: &SharedAppState, claimed: &str) -> Result<Self, String> {
+        let app_state = state.read().await;
+        match app_state.find_workspace(claimed) {
+            Some(session) => Self::root_of(session),
+            None => Err("Path is not an open workspace".to_string()),
+        }
+    }
+
+    fn root_of(session: &WorkspaceSession) -> Result<Self, String> {
+        let root = Path::new(&session.path)
+            .canonicalize()
+            .map_err(|e| format!("Invalid workspace path: {}", e))?;
+        Ok(Self {
+            id: session.id.clone(),
+            opened: PathBuf::from(&session.path),
+            path: root.clone(),
+            root,
+        })
+    }
+
+    /// A path under this one, validated against the workspace
+    pub fn join(&self, target: impl AsRef<Path>) -> Result<Self, String> {
+        let path = validate_workspace_path(&self.root, &self.path.join(target))?;
+        Ok(Self {
+            id: self.id.clone(),
+            opened: self.opened.clone(),
+            root: self.root.clone(),
+            path,
+        })
+    }
+
+    /// A file named by the frontend (an agent, command or template name) in `dir`.
+    /// Only a plain file name is accepted: no separators, `..` or absolute paths.
+    pub fn join_file_name(&self, dir: impl AsRef<Path>, name: &str) -> Result<Self, String> {
+        let mut components = Path::new(name).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) if !name.contains(['/', '\\']) => {
+                self.join(dir.as_ref().join(name))
+            }
+            _ => Err(format!("Invalid file name: {}", name)),
+        }
+    }
+
+    /// ID of the workspace this path is in
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The workspace this path is in
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path relative to the workspace root (empty for the root itself)
+    pub fn relative(&self) -> &Path {
+        self.path.strip_prefix(&self.root).unwrap_or(Path::new(""))
+    }
+
+    /// Path relative to the workspace root with `/` separators, for note keys
+    pub fn relative_str(&self) -> String {
+        self.relative().to_string_lossy().replace('\\', "/")
+    }
+
+    /// The workspace root as it was opened, for paths and prompts shown to the user
+    pub fn opened_root(&self) -> &Path {
+        &self.opened
+    }
+
+    pub fn as_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// This path under the workspace root as opened, for returning to the frontend
+    pub fn display_path(&self) -> PathBuf {
+        self.display_path_of(&self.path)
+    }
+
+    /// A path under the canonical root (e.g. one built from this path), under the
+    /// workspace root as opened
+    pub fn display_path_of(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.root) {
+            Ok(relative) if relative.as_os_str().is_empty() => self.opened.clone(),
+            Ok(relative) => self.opened.join(relative),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+}
+
+impl Deref for WorkspacePath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for WorkspacePath {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,6 +239,18 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_new_file_in_new_folders() {
+        let dir = tempdir().unwrap();
+        let workspace = dir.path();
+        let result = validate_workspace_path(workspace, Path::new("2026/03/note.md"));
+        assert_eq!(
+            result.unwrap(),
+            workspace.canonicalize().unwrap().join("2026/03/note.md")
+        );
+        assert!(validate_workspace_path(workspace, Path::new("new/../../evil.md")).is_err());
+    }
+
     #[test]
     fn test_subdirectory_path_accepted() {
         let dir = tempdir().unwrap();
@@ -95,4 +262,126 @@ mod tests {
         let result = validate_workspace_path(workspace, &file);
         assert!(result.is_ok());
     }
+
+    fn open_state(workspace: &Path) -> SharedAppState {
+        let mut state = crate::AppState::default();
+        state.activate(WorkspaceSession::new(workspace.to_str().unwrap()).unwrap());
+        std::sync::Arc::new(tokio::sync::RwLock::new(state))
+    }
+
+    #[tokio::test]
+    async fn test_paths_in_any_open_workspace() {
+        let personal = tempdir().unwrap();
+        let team = tempdir().unwrap();
+        std::fs::write(team.path().join("plan.md"), "# Plan").unwrap();
+        let state = open_state(team.path());
+        let team_id = state.read().await.active_workspace.clone().unwrap();
+        state
+            .write()
+            .await
+            .activate(WorkspaceSession::new(personal.path().to_str().unwrap()).unwrap());
+
+        // Relative paths resolve against the active workspace, absolute ones
+        // against whichever open workspace holds them
+        let note = WorkspacePath::resolve(&state, "todo.md").await.unwrap();
+        assert_eq!(note.root(), personal.path().canonicalize().unwrap());
+        let plan = team.path().join("plan.md");
+        let plan = WorkspacePath::resolve(&state, plan.to_str().unwrap()).await.unwrap();
+        assert_eq!(plan.id(), team_id);
+        assert_eq!(plan.relative(), Path::new("plan.md"));
+
+        let by_id = WorkspacePath::check_workspace(&state, &team_id).await.unwrap();
+        assert_eq!(by_id.opened_root(), team.path());
+        let by_path = WorkspacePath::check_workspace(&state, team.path().to_str().unwrap());
+        assert_eq!(by_path.await.unwrap().id(), team_id);
+        let closed = tempdir().unwrap();
+        let closed = closed.path().to_str().unwrap();
+        assert!(WorkspacePath::check_workspace(&state, closed).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_workspace_path_requires_open_workspace() {
+        let state = SharedAppState::default();
+        assert!(WorkspacePath::workspace(&state).await.is_err());
+        assert!(WorkspacePath::resolve(&state, "note.md").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_workspace_path_rejects_traversal() {
+        let dir = tempdir().unwrap();
+        let workspace = dir.path().join("ws");
+        std::fs::create_dir_all(workspace.join("notes")).unwrap();
+        std::fs::write(dir.path().join("secret.md"), "outside").unwrap();
+        let state = open_state(&workspace);
+
+        let note = WorkspacePath::resolve(&state, "notes/new.md").await.unwrap();
+        assert_eq!(note.relative(), Path::new("notes/new.md"));
+
+        for escape in [
+            "../secret.md",
+            "notes/../../secret.md",
+            "/etc/passwd",
+            dir.path().join("secret.md").to_str().unwrap(),
+        ] {
+            assert!(
+                WorkspacePath::resolve(&state, escape).await.is_err(),
+                "{} escaped the workspace",
+                escape
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_workspace_path_rejects_symlink_escape() {
+        let dir = tempdir().unwrap();
+        let workspace = dir.path().join("ws");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(dir.path().join("secret.md"), "outside").unwrap();
+        std::os::unix::fs::symlink(dir.path(), workspace.join("link")).unwrap();
+        let state = open_state(&workspace);
+
+        assert!(WorkspacePath::resolve(&state, "link/secret.md").await.is_err());
+        assert!(WorkspacePath::resolve(&state, "link/new.md").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_file_names_from_frontend() {
+        let dir = tempdir().unwrap();
+        let state = open_state(dir.path());
+        let workspace = WorkspacePath::workspace(&state).await.unwrap();
+        std::fs::create_dir_all(dir.path().join(".chronicle/prompts")).unwrap();
+
+        let prompt = workspace
+            .join_file_name(".chronicle/prompts", "tagger.md")
+            .unwrap();
+        assert_eq!(prompt.relative(), Path::new(".chronicle/prompts/tagger.md"));
+
+        for name in ["../../../etc/passwd", "../pipeline.toml", "a/b.md", "..", "", "/etc/passwd"] {
+            assert!(
+                workspace.join_file_name(".chronicle/prompts", name).is_err(),
+                "{:?} was accepted",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claimed_workspace_must_be_open_one() {
+        let dir = tempdir().unwrap();
+        let other = tempdir().unwrap();
+        let state = open_state(dir.path());
+
+        assert!(
+            WorkspacePath::check_workspace(&state, dir.path().to_str().unwrap())
+                .await
+                .is_ok()
+        );
+        assert!(
+            WorkspacePath::check_workspace(&state, other.path().to_str().unwrap())
+                .await
+                .is_err()
+        );
+        assert!(WorkspacePath::check_workspace(&state, "/").await.is_err());
+    }
 }
@@ -1,19 +1,16 @@
-use crate::models::{FileNode, FileNodeType, RecentWorkspaces, Workspace};
+use crate::models::{AttachmentKind, FileNode, FileNodeType};
 use crate::storage::StorageError;
 use chrono::{DateTime, Utc};
 use directories::ProjectDirs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-const RECENT_WORKSPACES_FILE: &str = "recent_workspaces.json";
-const MAX_RECENT_WORKSPACES: usize = 10;
-
 /// Get the application data directory for Chronicle
 pub fn get_app_data_dir() -> Option<PathBuf> {
     ProjectDirs::from("com", "chronicle", "Chronicle").map(|dirs| dirs.data_dir().to_path_buf())
 }
 
-/// List all markdown files in a workspace directory
+/// List the notes, attachments and folders in a workspace directory
 pub fn list_files(workspace_path: &Path) -> Result<Vec<FileNode>, StorageError> {
     if !workspace_path.is_dir() {
         return Err(StorageError::NotFound(
@@ -40,24 +37,13 @@ pub fn list_files(workspace_path: &Path) -> Result<Vec<FileNode>, StorageError>
         if path.is_dir() {
             // Recursively get children for directories
             let children = list_files_recursive(path)?;
-            // Only include directories that have markdown files
-            if !children.is_empty() {
-                root_nodes.push(FileNode {
-                    name,
-                    path: path.display().to_string(),
-                    node_type: FileNodeType::Directory,
-                    children: Some(children),
-                    modified_at: get_modified_time(path),
-                });
+            // Skip directories holding only files that aren't shown, but keep
+            // empty ones: they were made to put notes in
+            if !children.is_empty() || is_empty_dir(path) {
+                root_nodes.push(directory_node(name, path, Some(children)));
             }
-        } else if is_markdown_file(path) {
-            root_nodes.push(FileNode {
-                name,
-                path: path.display().to_string(),
-                node_type: FileNodeType::File,
-                children: None,
-                modified_at: get_modified_time(path),
-            });
+        } else if let Some(node) = file_node(name, path) {
+            root_nodes.push(node);
         }
     }
 
@@ -86,23 +72,11 @@ fn list_files_recursive(dir_path: &Path) -> Result<Vec<FileNode>, StorageError>
 
         if path.is_dir() {
             let children = list_files_recursive(path)?;
-            if !children.is_empty() {
-                nodes.push(FileNode {
-                    name,
-                    path: path.display().to_string(),
-                    node_type: FileNodeType::Directory,
-                    children: Some(children),
-                    modified_at: get_modified_time(path),
-                });
+            if !children.is_empty() || is_empty_dir(path) {
+                nodes.push(directory_node(name, path, Some(children)));
             }
-        } else if is_markdown_file(path) {
-            nodes.push(FileNode {
-                name,
-                path: path.display().to_string(),
-                node_type: FileNodeType::File,
-                children: None,
-                modified_at: get_modified_time(path),
-            });
+        } else if let Some(node) = file_node(name, path) {
+            nodes.push(node);
         }
     }
 
@@ -110,7 +84,7 @@ fn list_files_recursive(dir_path: &Path) -> Result<Vec<FileNode>, StorageError>
     Ok(nodes)
 }
 
-fn is_hidden(entry: &walkdir::DirEntry) -> bool {
+pub(super) fn is_hidden(entry: &walkdir::DirEntry) -> bool {
     entry
         .file_name()
         .to_str()
@@ -118,75 +92,63 @@ fn is_hidden(entry: &walkdir::DirEntry) -> bool {
         .unwrap_or(false)
 }
 
-fn is_markdown_file(path: &Path) -> bool {
+pub(super) fn is_markdown_file(path: &Path) -> bool {
     path.extension()
         .map(|ext| ext.eq_ignore_ascii_case("md"))
         .unwrap_or(false)
 }
 
-fn get_modified_time(path: &Path) -> Option<DateTime<Utc>> {
-    path.metadata()
-        .ok()
-        .and_then(|m| m.modified().ok())
-        .map(|t| DateTime::<Utc>::from(t))
+pub(super) fn is_empty_dir(path: &Path) -> bool {
+    std::fs::read_dir(path)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(false)
 }
 
-/// Get list of recently opened workspaces
-pub fn get_recent_workspaces() -> Result<RecentWorkspaces, StorageError> {
-    let Some(data_dir) = get_app_data_dir() else {
-        return Ok(RecentWorkspaces::default());
-    };
-
-    let path = data_dir.join(RECENT_WORKSPACES_FILE);
-    if !path.exists() {
-        return Ok(RecentWorkspaces::default());
+/// Node for a directory; `children` is None when they haven't been listed yet
+pub(super) fn directory_node(
+    name: String,
+    path: &Path,
+    children: Option<Vec<FileNode>>,
+) -> FileNode {
+    FileNode {
+        name,
+        path: path.display().to_string(),
+        node_type: FileNodeType::Directory,
+        children,
+        modified_at: get_modified_time(path),
+        created_at: get_created_time(path),
+        attachment_kind: None,
     }
-
-    let content = crate::storage::read_file(&path)?;
-    let recent: RecentWorkspaces = serde_json::from_str(&content)?;
-    Ok(recent)
 }
 
-/// Save a workspace to the recent list
-pub fn save_recent_workspace(workspace: &Workspace) -> Result<(), StorageError> {
-    let Some(data_dir) = get_app_data_dir() else {
-        return Err(StorageError::InvalidPath(
-            "Could not determine app data directory".to_string(),
-        ));
+/// Node for a note or an attachment; other files aren't listed
+pub(super) fn file_node(name: String, path: &Path) -> Option<FileNode> {
+    let (node_type, attachment_kind) = if is_markdown_file(path) {
+        (FileNodeType::File, None)
+    } else {
+        (FileNodeType::Attachment, Some(AttachmentKind::of(path)?))
     };
+    Some(FileNode {
+        name,
+        path: path.display().to_string(),
+        node_type,
+        children: None,
+        modified_at: get_modified_time(path),
+        created_at: get_created_time(path),
+        attachment_kind,
+    })
+}
 
-    crate::storage::ensure_dir(&data_dir)?;
-
-    let mut recent = get_recent_workspaces().unwrap_or_default();
-
-    // Remove existing entry for this path if present
-    recent
-        .workspaces
-        .retain(|w| w.path != workspace.path);
-
-    // Add new entry at the beginning
-    recent.workspaces.insert(0, workspace.clone());
-
-    // Limit the number of recent workspaces
-    recent.workspaces.truncate(MAX_RECENT_WORKSPACES);
-    recent.version = 1;
-
-    let path = data_dir.join(RECENT_WORKSPACES_FILE);
-    let content = serde_json::to_string_pretty(&recent)?;
-    crate::storage::write_file(&path, &content)
+fn get_modified_time(path: &Path) -> Option<DateTime<Utc>> {
+    path.metadata()
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .map(|t| DateTime::<Utc>::from(t))
 }
 
-/// Count the number of files in a FileNode tree
-pub fn count_files(nodes: &[FileNode]) -> usize {
-    nodes.iter().fold(0, |acc, node| {
-        if node.node_type == FileNodeType::File {
-            acc + 1
-        } else {
-            acc + node
-                .children
-                .as_ref()
-                .map(|c| count_files(c))
-                .unwrap_or(0)
-        }
-    })
+fn get_created_time(path: &Path) -> Option<DateTime<Utc>> {
+    path.metadata()
+        .ok()
+        .and_then(|m| m.created().ok())
+        .map(DateTime::<Utc>::from)
 }
@@ -0,0 +1,191 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+/// Number of tasks kept in the list. Running tasks are never dropped, so the list
+/// can grow past this while many are running.
+const MAX_TASKS: usize = 100;
+
+/// What started a task
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskTrigger {
+    Manual,
+    Scheduled,
+    CatchUp,
+    AfterEdit,
+    SessionEnd,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// A background task shown in the task list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRecord {
+    pub id: u64,
+    pub name: String,
+    pub label: String,
+    pub workspace_path: String,
+    pub trigger: TaskTrigger,
+    pub status: TaskStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
+/// Managed state holding recent background tasks, newest first
+pub struct TaskList {
+    inner: Mutex<TaskListInner>,
+}
+
+struct TaskListInner {
+    next_id: u64,
+    tasks: VecDeque<TaskRecord>,
+}
+
+impl TaskList {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(TaskListInner {
+                next_id: 1,
+                tasks: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Acquire the lock with poison recovery
+    fn lock(&self) -> MutexGuard<'_, TaskListInner> {
+        match self.inner.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                tracing::warn!("Recovering from poisoned task list lock");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    /// Register a running task and return its record
+    pub fn start(
+        &self,
+        name: &str,
+        label: &str,
+        workspace_path: &str,
+        trigger: TaskTrigger,
+    ) -> TaskRecord {
+        let mut inner = self.lock();
+        let record = TaskRecord {
+            id: inner.next_id,
+            name: name.to_string(),
+            label: label.to_string(),
+            workspace_path: workspace_path.to_string(),
+            trigger,
+            status: TaskStatus::Running,
+            started_at: Utc::now(),
+            finished_at: None,
+            error: None,
+        };
+        inner.next_id += 1;
+        inner.tasks.push_front(record.clone());
+        inner.evict_finished();
+        record
+    }
+
+    /// Mark a task finished and return the updated record
+    pub fn finish(&self, id: u64, result: Result<(), String>) -> Option<TaskRecord> {
+        let mut inner = self.lock();
+        let task = inner.tasks.iter_mut().find(|t| t.id == id)?;
+        task.finished_at = Some(Utc::now());
+        match result {
+            Ok(()) => task.status = TaskStatus::Succeeded,
+            Err(e) => {
+                task.status = TaskStatus::Failed;
+                task.error = Some(e);
+            }
+        }
+        let task = task.clone();
+        inner.evict_finished();
+        Some(task)
+    }
+
+    /// All tasks, newest first
+    pub fn list(&self) -> Vec<TaskRecord> {
+        self.lock().tasks.iter().cloned().collect()
+    }
+}
+
+impl TaskListInner {
+    /// Drop the oldest finished tasks while the list is over `MAX_TASKS`
+    fn evict_finished(&mut self) {
+        while self.tasks.len() > MAX_TASKS {
+            let Some(oldest) = self
+                .tasks
+                .iter()
+                .rposition(|t| t.status != TaskStatus::Running)
+            else {
+                break;
+            };
+            self.tasks.remove(oldest);
+        }
+    }
+}
+
+impl Default for TaskList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_lifecycle() {
+        let list = TaskList::new();
+        let first = list.start("daily-digest", "daily digest", "/ws", TaskTrigger::Scheduled);
+        let second = list.start("agents", "background agents", "/ws", TaskTrigger::AfterEdit);
+        assert_ne!(first.id, second.id);
+
+        let tasks = list.list();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].id, second.id);
+        assert_eq!(tasks[0].status, TaskStatus::Running);
+
+        let done = list.finish(first.id, Err("boom".to_string())).unwrap();
+        assert_eq!(done.status, TaskStatus::Failed);
+        assert_eq!(done.error.as_deref(), Some("boom"));
+        assert!(done.finished_at.is_some());
+
+        assert!(list.finish(999, Ok(())).is_none());
+    }
+
+    #[test]
+    fn test_task_list_is_bounded() {
+        let list = TaskList::new();
+        let long_run = list.start("agents", "background agents", "/ws", TaskTrigger::Manual);
+        for _ in 0..(MAX_TASKS + 10) {
+            let task = list.start("t", "t", "/ws", TaskTrigger::Manual);
+            list.finish(task.id, Ok(()));
+        }
+        assert_eq!(list.list().len(), MAX_TASKS);
+
+        // The oldest task is still running, so it outlives newer finished ones
+        let finished = list.finish(long_run.id, Ok(())).unwrap();
+        assert_eq!(finished.status, TaskStatus::Succeeded);
+        assert_eq!(list.list().len(), MAX_TASKS);
+
+        // Running tasks are kept even past the cap
+        for _ in 0..(MAX_TASKS + 10) {
+            list.start("t", "t", "/ws", TaskTrigger::Manual);
+        }
+        assert_eq!(list.list().len(), MAX_TASKS + 10);
+    }
+}
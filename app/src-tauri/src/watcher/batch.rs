@@ -0,0 +1,145 @@
+use notify::EventKind;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// Quiet period after the last event before a batch is handled
+pub const BATCH_WINDOW: Duration = Duration::from_millis(300);
+
+/// Longest a batch waits while events keep arriving (e.g. during a sync)
+pub const BATCH_MAX_WAIT: Duration = Duration::from_secs(2);
+
+/// What happened to a path over a batch of raw events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl ChangeKind {
+    /// Kind of a raw event. Renames count as modifications; whether the path
+    /// still exists settles it when the batch finishes.
+    pub fn of(kind: &EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Create(_) => Some(ChangeKind::Created),
+            EventKind::Modify(_) => Some(ChangeKind::Modified),
+            EventKind::Remove(_) => Some(ChangeKind::Deleted),
+            _ => None,
+        }
+    }
+}
+
+/// Paths touched by raw events, each coalesced to a single change
+#[derive(Debug, Default)]
+pub struct Batch {
+    changes: BTreeMap<PathBuf, ChangeKind>,
+}
+
+impl Batch {
+    pub fn add(&mut self, path: PathBuf, kind: ChangeKind) {
+        use ChangeKind::*;
+        let merged = match (self.changes.get(&path), kind) {
+            (None, kind) => Some(kind),
+            (Some(Created), Modified) => Some(Created),
+            // A file that came and went changed nothing
+            (Some(Created), Deleted) => None,
+            (Some(Deleted), Created | Modified) => Some(Modified),
+            (Some(_), kind) => Some(kind),
+        };
+        match merged {
+            Some(kind) => self.changes.insert(path, kind),
+            None => self.changes.remove(&path),
+        };
+    }
+
+    /// The batch's changes, checked against the disk: a path that exists wasn't
+    /// deleted, and one that doesn't was
+    pub fn finish(self) -> Vec<(PathBuf, ChangeKind)> {
+        self.changes
+            .into_iter()
+            .map(|(path, kind)| {
+                let kind = match (kind, path.exists()) {
+                    (ChangeKind::Deleted, true) => ChangeKind::Modified,
+                    (_, false) => ChangeKind::Deleted,
+                    (kind, true) => kind,
+                };
+                (path, kind)
+            })
+            .collect()
+    }
+}
+
+/// Wait for the next batch: everything received until events stop for
+/// `BATCH_WINDOW`, or for at most `BATCH_MAX_WAIT`. Returns None once the sender
+/// is gone; events still pending then belong to a watcher that was stopped.
+pub fn next_batch(rx: &Receiver<(PathBuf, ChangeKind)>) -> Option<Batch> {
+    let (path, kind) = rx.recv().ok()?;
+    let started = Instant::now();
+    let mut batch = Batch::default();
+    batch.add(path, kind);
+
+    while started.elapsed() < BATCH_MAX_WAIT {
+        match rx.recv_timeout(BATCH_WINDOW) {
+            Ok((path, kind)) => batch.add(path, kind),
+            Err(RecvTimeoutError::Timeout) => break,
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
+    Some(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_batch_coalesces_per_path() {
+        let dir = tempdir().unwrap();
+        let kept = dir.path().join("tags.json");
+        let replaced = dir.path().join("actions.json");
+        let temp = dir.path().join(".tags.json.tmp");
+        std::fs::write(&kept, "{}").unwrap();
+        std::fs::write(&replaced, "[]").unwrap();
+
+        let mut batch = Batch::default();
+        batch.add(temp.clone(), ChangeKind::Created);
+        batch.add(temp.clone(), ChangeKind::Modified);
+        batch.add(temp, ChangeKind::Deleted);
+        batch.add(kept.clone(), ChangeKind::Created);
+        batch.add(kept.clone(), ChangeKind::Modified);
+        batch.add(replaced.clone(), ChangeKind::Deleted);
+        batch.add(replaced.clone(), ChangeKind::Created);
+        batch.add(dir.path().join("gone.json"), ChangeKind::Modified);
+
+        assert_eq!(
+            batch.finish(),
+            vec![
+                (replaced, ChangeKind::Modified),
+                (dir.path().join("gone.json"), ChangeKind::Deleted),
+                (kept, ChangeKind::Created),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_next_batch_waits_for_quiet() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        for i in 0..20 {
+            tx.send((
+                PathBuf::from(format!("/ws/{}.json", i)),
+                ChangeKind::Modified,
+            ))
+            .unwrap();
+        }
+        let batch = next_batch(&rx).unwrap();
+        assert_eq!(batch.changes.len(), 20);
+
+        drop(tx);
+        assert!(next_batch(&rx).is_none());
+    }
+}
@@ -0,0 +1,351 @@
+mod batch;
+mod notes;
+
+pub use batch::*;
+pub use notes::*;
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use crate::pipeline::{output_key, ProcessedManifest};
+use crate::storage::DirectoryCache;
+use crate::SharedAppState;
+
+/// How long after the app wrote a note its own write can still show up as a change
+const OWN_WRITE_GRACE: Duration = Duration::from_secs(3);
+
+/// Payload of the `notes:*` events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteEvent {
+    /// Open workspace the note belongs to
+    pub workspace_id: String,
+    pub kind: NoteChangeKind,
+    /// Absolute path, as the workspace was opened (the new path for renames)
+    pub path: String,
+    pub relative_path: String,
+    /// Previous absolute path, for renames
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+/// A file in .chronicle/ that changed; the `chronicle:*-updated` events carry
+/// the batch's changes as a list of these
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChronicleChange {
+    /// Open workspace the file belongs to
+    pub workspace_id: String,
+    /// Workspace-relative path
+    pub path: String,
+    pub kind: ChangeKind,
+    /// Workspace-relative note a processed output belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Event announcing a change to a file in .chronicle/, if anyone listens for it
+fn chronicle_event(relative: &Path) -> Option<&'static str> {
+    let mut parts = relative.strip_prefix(".chronicle").ok()?.components();
+    let first = parts.next()?.as_os_str().to_str()?;
+    let file = relative.file_name()?.to_str()?;
+    if file.starts_with('.') {
+        // Temporary files from atomic writes
+        return None;
+    }
+    match (first, parts.next()) {
+        ("tags.json", None) => Some("chronicle:tags-updated"),
+        ("actions.json", None) => Some("chronicle:actions-updated"),
+        ("links.json", None) => Some("chronicle:links-updated"),
+        ("config.toml", None) => Some("chronicle:config-updated"),
+        ("processed", Some(_)) => Some("chronicle:processed-updated"),
+        _ => None,
+    }
+}
+
+/// Managed state that keeps a filesystem watcher alive for each open workspace
+pub struct ChronicleWatcher {
+    inner: Mutex<HashMap<String, RecommendedWatcher>>,
+}
+
+impl ChronicleWatcher {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start watching a workspace: `.chronicle/` for index updates, and every note
+    /// for changes made outside the app. Replaces the workspace's previous watcher.
+    pub fn start(
+        &self,
+        workspace_id: &str,
+        workspace_path: &str,
+        app_handle: tauri::AppHandle,
+    ) -> Result<(), String> {
+        let root = PathBuf::from(workspace_path);
+        if !root.join(".chronicle").exists() {
+            return Err("Chronicle directory does not exist".to_string());
+        }
+        self.stop(workspace_id);
+
+        // Events are batched on their own thread, which exits once the watcher
+        // (and with it the sender) is dropped
+        let (tx, rx) = mpsc::channel::<(PathBuf, ChangeKind)>();
+        let tree = NoteTree::scan(&root);
+        let id = workspace_id.to_string();
+        std::thread::spawn(move || handle_batches(rx, id, tree, app_handle));
+
+        let watch_root = root.clone();
+        let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+            let Ok(event) = res else {
+                return;
+            };
+            let Some(kind) = ChangeKind::of(&event.kind) else {
+                return;
+            };
+            for path in event.paths {
+                let relative = path.strip_prefix(&watch_root).unwrap_or(&path);
+                if chronicle_event(relative).is_some() || !is_ignored_dir(relative) {
+                    let _ = tx.send((path, kind));
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch workspace: {}", e))?;
+
+        tracing::info!("Started filesystem watcher on {}", root.display());
+
+        let mut guard = self.inner.lock().map_err(|e| e.to_string())?;
+        guard.insert(workspace_id.to_string(), watcher);
+
+        Ok(())
+    }
+
+    /// Stop a workspace's watcher. Changes it hasn't reported yet are dropped.
+    pub fn stop(&self, workspace_id: &str) {
+        if let Ok(mut guard) = self.inner.lock() {
+            if guard.remove(workspace_id).is_some() {
+                tracing::info!("Stopped filesystem watcher");
+            }
+        }
+    }
+}
+
+/// Turn each batch of raw events into note changes and `.chronicle/` updates
+fn handle_batches(
+    rx: Receiver<(PathBuf, ChangeKind)>,
+    workspace_id: String,
+    mut tree: NoteTree,
+    app_handle: tauri::AppHandle,
+) {
+    while let Some(batch) = next_batch(&rx) {
+        let root = tree.root().to_path_buf();
+        let mut touched = Vec::new();
+        let mut updates: BTreeMap<&'static str, Vec<ChronicleChange>> = BTreeMap::new();
+        let cache = app_handle.try_state::<DirectoryCache>();
+        for (path, kind) in batch.finish() {
+            let relative = path.strip_prefix(&root).unwrap_or(&path);
+            if let Some(cache) = &cache {
+                cache.invalidate(&path);
+            }
+            match chronicle_event(relative) {
+                Some(event) => updates.entry(event).or_default().push(chronicle_change(
+                    &workspace_id,
+                    &root,
+                    &path,
+                    kind,
+                )),
+                None => touched.push(path),
+            }
+        }
+
+        // Listings depend on the folders the settings skip
+        if updates.contains_key("chronicle:config-updated") {
+            if let Some(cache) = &cache {
+                cache.clear_under(&root);
+            }
+        }
+
+        let changes = tree.apply(touched);
+        if !changes.is_empty() {
+            handle_note_changes(&app_handle, &workspace_id, &root, &changes);
+        }
+        for (event, changes) in updates {
+            let _ = app_handle.emit(event, &changes);
+        }
+    }
+}
+
+fn chronicle_change(
+    workspace_id: &str,
+    root: &Path,
+    path: &Path,
+    kind: ChangeKind,
+) -> ChronicleChange {
+    let note = output_key(root, path).map(|key| {
+        ProcessedManifest::load(root)
+            .note_for_output(&key)
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| format!("{}.md", key))
+    });
+    ChronicleChange {
+        workspace_id: workspace_id.to_string(),
+        path: relative_path(root, path),
+        kind,
+        note,
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Keep IDs, metadata and indexes in step with notes changed outside the app, tell
+/// the frontend, and flag the open note if it changed under the editor
+fn handle_note_changes(
+    app_handle: &tauri::AppHandle,
+    workspace_id: &str,
+    root: &Path,
+    changes: &[NoteChange],
+) {
+    let moves: Vec<(String, Option<String>)> = changes
+        .iter()
+        .filter_map(|c| match c.kind {
+            NoteChangeKind::Renamed => Some((c.from.clone()?, Some(c.path.clone()))),
+            NoteChangeKind::Deleted => Some((c.path.clone(), None)),
+            _ => None,
+        })
+        .collect();
+    if !moves.is_empty() {
+        match crate::notes::record_external_moves(root, &moves) {
+            Ok(touched) => record_own_writes(app_handle, workspace_id, root, &touched),
+            Err(e) => tracing::warn!("Failed to update indexes for moved notes: {}", e),
+        }
+    }
+    for change in changes {
+        if matches!(
+            change.kind,
+            NoteChangeKind::Created | NoteChangeKind::Renamed
+        ) {
+            if let Err(e) = crate::notes::ensure_note_id(root, &change.path) {
+                tracing::warn!("Failed to assign note ID to {}: {}", change.path, e);
+            }
+        }
+    }
+
+    let absolute = |relative: &str| root.join(relative).display().to_string();
+    for change in changes {
+        let event = NoteEvent {
+            workspace_id: workspace_id.to_string(),
+            kind: change.kind,
+            path: absolute(&change.path),
+            relative_path: change.path.clone(),
+            from: change.from.as_deref().map(absolute),
+        };
+        let _ = app_handle.emit(change.kind.event(), &event);
+    }
+
+    if let Some(path) = open_note_conflict(app_handle, workspace_id, root, changes) {
+        tracing::info!("Open note changed on disk");
+        let _ = app_handle.emit("notes:conflict", &path);
+    }
+}
+
+/// Record files the watcher wrote as the app's own, so an agent running meanwhile
+/// isn't blamed for them and has them rolled back
+fn record_own_writes(
+    app_handle: &tauri::AppHandle,
+    workspace_id: &str,
+    root: &Path,
+    paths: &[PathBuf],
+) {
+    let Some(state) = app_handle.try_state::<SharedAppState>() else {
+        return;
+    };
+    let mut app_state = state.blocking_write();
+    let Some(canonical) = app_state
+        .workspaces
+        .get(workspace_id)
+        .map(|w| w.root.clone())
+    else {
+        return;
+    };
+    for path in paths {
+        if let Ok(relative) = path.strip_prefix(root) {
+            app_state.record_user_write(canonical.join(relative));
+        }
+    }
+}
+
+/// The workspace's open note, if it was modified on disk and no longer matches what
+/// the editor last loaded or saved. The app's own recent writes don't count.
+fn open_note_conflict(
+    app_handle: &tauri::AppHandle,
+    workspace_id: &str,
+    root: &Path,
+    changes: &[NoteChange],
+) -> Option<String> {
+    let state = app_handle.try_state::<SharedAppState>()?;
+    let app_state = state.blocking_read();
+    let session = app_state.workspaces.get(workspace_id)?;
+    let open = session.current_file_path.as_deref()?;
+
+    let change = changes
+        .iter()
+        .find(|c| c.kind == NoteChangeKind::Modified && Path::new(open) == root.join(&c.path))?;
+    let path = root.join(&change.path);
+
+    let on_disk = std::fs::read_to_string(&path).ok()?;
+    if session.current_file_content.as_deref() == Some(on_disk.as_str()) {
+        return None;
+    }
+    let own_write = path
+        .canonicalize()
+        .ok()
+        .and_then(|p| app_state.user_writes.get(&p).copied())
+        .is_some_and(|at| (chrono::Utc::now() - at).to_std().unwrap_or_default() < OWN_WRITE_GRACE);
+    (!own_write).then(|| open.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chronicle_events_by_path() {
+        let event = |p: &str| chronicle_event(Path::new(p));
+        assert_eq!(
+            event(".chronicle/tags.json"),
+            Some("chronicle:tags-updated")
+        );
+        assert_eq!(
+            event(".chronicle/links.json"),
+            Some("chronicle:links-updated")
+        );
+        assert_eq!(
+            event(".chronicle/config.toml"),
+            Some("chronicle:config-updated")
+        );
+        assert_eq!(
+            event(".chronicle/processed/team/standup.json"),
+            Some("chronicle:processed-updated")
+        );
+        assert_eq!(event(".chronicle/processed/.standup.json.1f.tmp"), None);
+        assert_eq!(event(".chronicle/meta/standup.json"), None);
+        assert_eq!(event(".chronicle/entities/tags.json"), None);
+        assert_eq!(event("tags.json"), None);
+    }
+}
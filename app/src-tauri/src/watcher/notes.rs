@@ -0,0 +1,368 @@
+use git2::Repository;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// Directories that never hold notes, besides hidden ones
+const IGNORED_DIRS: &[&str] = &["node_modules"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteChangeKind {
+    Created,
+    Modified,
+    Deleted,
+    Renamed,
+}
+
+impl NoteChangeKind {
+    /// Event emitted to the frontend
+    pub fn event(self) -> &'static str {
+        match self {
+            NoteChangeKind::Created => "notes:created",
+            NoteChangeKind::Modified => "notes:modified",
+            NoteChangeKind::Deleted => "notes:deleted",
+            NoteChangeKind::Renamed => "notes:renamed",
+        }
+    }
+}
+
+/// A note that changed on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteChange {
+    pub kind: NoteChangeKind,
+    /// Workspace-relative path (the new path for renames)
+    pub path: String,
+    /// Previous workspace-relative path, for renames
+    pub from: Option<String>,
+}
+
+impl NoteChange {
+    fn new(kind: NoteChangeKind, path: &str) -> Self {
+        Self {
+            kind,
+            path: path.to_string(),
+            from: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+    /// Hash of the content, to recognize a note moved under another name
+    hash: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        if !meta.is_file() {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        std::fs::read(path).ok()?.hash(&mut hasher);
+        Some(Self {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+            hash: hasher.finish(),
+        })
+    }
+}
+
+/// Whether a workspace-relative path can be a note: markdown, outside hidden and
+/// ignored directories
+pub fn is_note_path(relative: &Path) -> bool {
+    let visible = relative.components().all(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        !name.starts_with('.') && !IGNORED_DIRS.contains(&name.as_ref())
+    });
+    visible
+        && relative
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+}
+
+/// Whether a workspace-relative path, or a directory on the way to it, is hidden
+/// or ignored, so nothing at or under it is worth looking at
+pub fn is_ignored_dir(relative: &Path) -> bool {
+    relative.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        name.starts_with('.') || IGNORED_DIRS.contains(&name.as_ref())
+    })
+}
+
+/// The notes last seen in a workspace. Raw filesystem events only say which paths
+/// were touched; diffing those paths against this tree turns them into note
+/// changes and hides events that changed nothing.
+pub struct NoteTree {
+    root: PathBuf,
+    notes: BTreeMap<String, FileStamp>,
+}
+
+impl NoteTree {
+    /// Record every note currently in the workspace
+    pub fn scan(root: &Path) -> Self {
+        let mut tree = Self {
+            root: root.to_path_buf(),
+            notes: BTreeMap::new(),
+        };
+        let repo = Repository::open(root).ok();
+        for relative in tree.notes_under(root, repo.as_ref()) {
+            if let Some(stamp) = FileStamp::of(&root.join(&relative)) {
+                tree.notes.insert(relative, stamp);
+            }
+        }
+        tree
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Bring the tree up to date for the touched paths (files or directories) and
+    /// return what changed, renames first. A note deleted and another created in
+    /// the same batch with the same name and size, or with the same content, is
+    /// reported as a rename.
+    pub fn apply<I>(&mut self, touched: I) -> Vec<NoteChange>
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        let repo = Repository::open(&self.root).ok();
+        let mut candidates = BTreeSet::new();
+        for path in touched {
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            if is_ignored_dir(relative) {
+                continue;
+            }
+            let relative_str = relative.to_string_lossy().replace('\\', "/");
+            if path.is_dir() {
+                candidates.extend(self.notes_under(&path, repo.as_ref()));
+            } else if is_note_path(relative) {
+                candidates.insert(relative_str.clone());
+            }
+            // A removed or renamed directory takes its notes with it
+            let prefix = if relative_str.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", relative_str)
+            };
+            candidates.extend(
+                self.notes
+                    .keys()
+                    .filter(|k| k.starts_with(&prefix))
+                    .cloned(),
+            );
+        }
+
+        let mut created = Vec::new();
+        let mut deleted = Vec::new();
+        let mut modified = Vec::new();
+        for relative in candidates {
+            let current = if is_git_ignored(repo.as_ref(), &relative) {
+                None
+            } else {
+                FileStamp::of(&self.root.join(&relative))
+            };
+            match (self.notes.get(&relative).copied(), current) {
+                (None, Some(stamp)) => created.push((relative, stamp)),
+                (Some(old), None) => deleted.push((relative, old)),
+                (Some(old), Some(stamp)) if old != stamp => modified.push((relative, stamp)),
+                _ => {}
+            }
+        }
+
+        let mut changes = Vec::new();
+        for (from, to) in pair_renames(&mut deleted, &mut created) {
+            self.notes.remove(&from);
+            self.notes.insert(to.0.clone(), to.1);
+            changes.push(NoteChange {
+                kind: NoteChangeKind::Renamed,
+                path: to.0,
+                from: Some(from),
+            });
+        }
+        for (relative, _) in deleted {
+            self.notes.remove(&relative);
+            changes.push(NoteChange::new(NoteChangeKind::Deleted, &relative));
+        }
+        for (relative, stamp) in created {
+            changes.push(NoteChange::new(NoteChangeKind::Created, &relative));
+            self.notes.insert(relative, stamp);
+        }
+        for (relative, stamp) in modified {
+            changes.push(NoteChange::new(NoteChangeKind::Modified, &relative));
+            self.notes.insert(relative, stamp);
+        }
+        changes
+    }
+
+    /// Notes under a directory, workspace-relative, skipping ignored ones
+    fn notes_under(&self, dir: &Path, repo: Option<&Repository>) -> Vec<String> {
+        WalkDir::new(dir)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| {
+                e.path()
+                    .strip_prefix(&self.root)
+                    .is_ok_and(|r| !is_ignored_dir(r))
+            })
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| {
+                let relative = e.path().strip_prefix(&self.root).ok()?;
+                is_note_path(relative).then(|| relative.to_string_lossy().replace('\\', "/"))
+            })
+            .filter(|relative| !is_git_ignored(repo, relative))
+            .collect()
+    }
+}
+
+/// Whether the workspace's .gitignore excludes a note
+fn is_git_ignored(repo: Option<&Repository>, relative: &str) -> bool {
+    repo.is_some_and(|r| r.is_path_ignored(relative).unwrap_or(false))
+}
+
+type Entry = (String, FileStamp);
+
+/// Take matching delete/create pairs out of the two lists: the same file name and
+/// size (moved to another folder, maybe edited on the way), or else the same
+/// content (renamed). Anything else stays a plain delete and create.
+fn pair_renames(deleted: &mut Vec<Entry>, created: &mut Vec<Entry>) -> Vec<(String, Entry)> {
+    let file_name = |p: &str| p.rsplit('/').next().unwrap_or(p).to_string();
+    let moved = |(from, old): &Entry, (to, new): &Entry| {
+        new.len == old.len && file_name(to) == file_name(from)
+    };
+    let renamed = |(_, old): &Entry, (_, new): &Entry| new.len > 0 && new.hash == old.hash;
+
+    let mut renames = Vec::new();
+    for same in [&moved as &dyn Fn(&Entry, &Entry) -> bool, &renamed] {
+        let mut i = 0;
+        while i < deleted.len() {
+            match created.iter().position(|c| same(&deleted[i], c)) {
+                Some(j) => {
+                    let (from, _) = deleted.remove(i);
+                    renames.push((from, created.remove(j)));
+                }
+                None => i += 1,
+            }
+        }
+    }
+    renames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, relative: &str, content: &str) -> PathBuf {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_note_tree_reports_changes() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let standup = write(root, "standup.md", "# Standup");
+        let review = write(root, "team/review.md", "# Review");
+        let mut tree = NoteTree::scan(root);
+
+        // Touching a file without changing it is not a change
+        assert!(tree.apply([standup.clone()]).is_empty());
+
+        let ideas = write(root, "ideas.md", "# Ideas");
+        std::fs::write(&standup, "# Standup\n\nNotes").unwrap();
+        std::fs::remove_file(&review).unwrap();
+        let changes = tree.apply([ideas, standup.clone(), review]);
+        let kinds: Vec<_> = changes.iter().map(|c| (c.kind, c.path.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (NoteChangeKind::Deleted, "team/review.md"),
+                (NoteChangeKind::Created, "ideas.md"),
+                (NoteChangeKind::Modified, "standup.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_note_tree_detects_renames() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let standup = write(root, "standup.md", "# Standup");
+        write(root, "team/a.md", "# A");
+        write(root, "team/b.md", "# Bee");
+        let mut tree = NoteTree::scan(root);
+
+        std::fs::rename(&standup, root.join("daily.md")).unwrap();
+        let changes = tree.apply([standup, root.join("daily.md")]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, NoteChangeKind::Renamed);
+        assert_eq!(changes[0].from.as_deref(), Some("standup.md"));
+        assert_eq!(changes[0].path, "daily.md");
+
+        // Renaming a folder renames every note in it
+        std::fs::rename(root.join("team"), root.join("crew")).unwrap();
+        let changes = tree.apply([root.join("team"), root.join("crew")]);
+        let renames: Vec<_> = changes
+            .iter()
+            .map(|c| (c.from.as_deref().unwrap(), c.path.as_str()))
+            .collect();
+        assert_eq!(
+            renames,
+            vec![("team/a.md", "crew/a.md"), ("team/b.md", "crew/b.md")]
+        );
+    }
+
+    #[test]
+    fn test_unrelated_notes_of_the_same_size_are_not_a_rename() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let old = write(root, "retro.md", "# Retro A");
+        let mut tree = NoteTree::scan(root);
+
+        // One note deleted while another of the same size is created
+        std::fs::remove_file(&old).unwrap();
+        let new = write(root, "plans.md", "# Plans B");
+        let changes = tree.apply([old, new]);
+        assert_eq!(
+            changes,
+            vec![
+                NoteChange::new(NoteChangeKind::Deleted, "retro.md"),
+                NoteChange::new(NoteChangeKind::Created, "plans.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_note_tree_skips_hidden_and_ignored() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        Repository::init(root).unwrap();
+        write(root, ".gitignore", "drafts/\n");
+        let mut tree = NoteTree::scan(root);
+
+        let touched = vec![
+            write(root, ".chronicle/context.md", "# Context"),
+            write(root, "node_modules/pkg/README.md", "# Pkg"),
+            write(root, "drafts/wip.md", "# WIP"),
+            write(root, "notes.txt", "plain"),
+            write(root, "kept.md", "# Kept"),
+        ];
+        let changes = tree.apply(touched);
+        assert_eq!(
+            changes,
+            vec![NoteChange::new(NoteChangeKind::Created, "kept.md")]
+        );
+    }
+}
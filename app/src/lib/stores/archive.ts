@@ -10,6 +10,8 @@ export interface ProcessedNoteInfo {
   actionCount: number;
   questionCount: number;
   processedAt: string | null;
+  stale: boolean;
+  linesChanged: number | null;
 }
 
 interface ArchiveState {
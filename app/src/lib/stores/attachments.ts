@@ -0,0 +1,64 @@
+import { writable, derived } from 'svelte/store';
+import { getInvoke } from '$lib/utils/tauri';
+
+export type AttachmentKind = 'image' | 'pdf' | 'audio' | 'video' | 'document';
+
+/** An attachment copied into the workspace's assets folder */
+export interface ImportedAttachment {
+  /** Workspace-relative path */
+  path: string;
+  kind: AttachmentKind;
+  /** Markdown that links it from the note */
+  markdown: string;
+}
+
+/** Whether a path is a note rather than an attachment */
+export function isNotePath(path: string): boolean {
+  return path.toLowerCase().endsWith('.md');
+}
+
+/** Copy a file on disk (e.g. dropped onto the editor) into the workspace */
+export async function importAttachmentFile(notePath: string, sourcePath: string) {
+  const invoke = await getInvoke();
+  return invoke<ImportedAttachment>('import_attachment', { notePath, sourcePath });
+}
+
+/** Store pasted or dropped data (e.g. a screenshot) in the workspace */
+export async function importAttachmentData(notePath: string, file: File) {
+  const invoke = await getInvoke();
+  const data = Array.from(new Uint8Array(await file.arrayBuffer()));
+  const fileName = file.name || `pasted.${file.type.split('/')[1] ?? 'png'}`;
+  return invoke<ImportedAttachment>('import_attachment_data', { notePath, fileName, data });
+}
+
+/** Open an attachment in the app the system uses for it */
+export async function openAttachment(path: string) {
+  const invoke = await getInvoke();
+  await invoke('open_attachment', { path });
+}
+
+function createAttachmentsStore() {
+  const { subscribe, set } = writable<{ orphans: string[] }>({ orphans: [] });
+
+  return {
+    subscribe,
+
+    /** Find attachments no note links to any more */
+    async loadOrphans(workspacePath: string) {
+      try {
+        const invoke = await getInvoke();
+        const orphans = await invoke<string[]>('list_orphaned_attachments', { workspacePath });
+        set({ orphans });
+      } catch (e) {
+        console.error('Failed to find unused attachments:', e);
+      }
+    },
+
+    clear() {
+      set({ orphans: [] });
+    },
+  };
+}
+
+export const attachmentsStore = createAttachmentsStore();
+export const orphanedAttachments = derived(attachmentsStore, ($s) => $s.orphans);
@@ -7,6 +7,10 @@ export interface CommandInfo {
   description: string;
   params: string[];
   content: string;
+  /** Tools the command runs with; commands that declare none are read-only */
+  tools: string[];
+  /** Workspace paths the command may write (folders end in "/") */
+  writes: string[];
 }
 
 interface CommandsState {
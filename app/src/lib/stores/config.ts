@@ -0,0 +1,82 @@
+import { writable, derived, get } from 'svelte/store';
+import { isTauri, getInvoke } from '$lib/utils/tauri';
+import { currentWorkspace, isActiveWorkspace, workspaceStore } from './workspace';
+import { toast } from './toast';
+
+/** Workspace settings; mirrors .chronicle/config.toml */
+export interface WorkspaceConfig {
+  version: number;
+  files: { max_file_size_mb: number; max_attachment_size_mb: number; skip_dirs: string[] };
+  search: { max_results: number };
+  agents: { process_max_turns: number; max_turns: number; allowed_tools: string[] };
+  digests: { weekly_days: number; monthly_days: number };
+  actions: { stale_after_days: number };
+}
+
+interface ConfigState {
+  config: WorkspaceConfig | null;
+  /** Why config.toml is invalid; the defaults apply until it is fixed */
+  error: string | null;
+}
+
+const defaultState: ConfigState = {
+  config: null,
+  error: null,
+};
+
+function createConfigStore() {
+  const { subscribe, set } = writable<ConfigState>(defaultState);
+
+  async function load(workspacePath: string) {
+    try {
+      const invoke = await getInvoke();
+      const state = await invoke<ConfigState>('get_config', { workspacePath });
+      const previousError = get({ subscribe }).error;
+      set(state);
+      if (state.error && state.error !== previousError) {
+        toast.warning(`Settings not applied: ${state.error}`, 8000);
+      }
+    } catch (e) {
+      console.error('Failed to load settings:', e);
+    }
+  }
+
+  return {
+    subscribe,
+
+    load,
+
+    /** Save settings; invalid ones are rejected with the reason */
+    update: async (config: WorkspaceConfig) => {
+      const ws = get(currentWorkspace);
+      if (!ws) return;
+      try {
+        const invoke = await getInvoke();
+        await invoke('update_config', { workspacePath: ws.path, config });
+        set({ config, error: null });
+      } catch (e) {
+        toast.error(String(e));
+        throw e;
+      }
+    },
+
+    clear: () => set(defaultState),
+  };
+}
+
+export const configStore = createConfigStore();
+export const workspaceConfig = derived(configStore, ($s) => $s.config);
+export const configError = derived(configStore, ($s) => $s.error);
+
+/** Initialize event listener for config.toml changes, applied without a restart */
+export async function initConfigListener(): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<{ workspaceId: string }[]>('chronicle:config-updated', (event) => {
+    const ws = get(currentWorkspace);
+    if (!ws || !event.payload.some((c) => isActiveWorkspace(c.workspaceId))) return;
+    configStore.load(ws.path);
+    // The folders the tree skips may have changed
+    workspaceStore.refreshFiles();
+  });
+}
@@ -1,6 +1,6 @@
 import { writable, derived, get } from 'svelte/store';
 import { isTauri, getInvoke } from '$lib/utils/tauri';
-import { currentWorkspace } from './workspace';
+import { currentWorkspace, isActiveWorkspace } from './workspace';
 import { currentNote } from './note';
 
 export interface LinksIndex {
@@ -63,7 +63,10 @@ export const relatedNotes = derived(
 export async function initLinksListener(): Promise<() => void> {
   if (!isTauri()) return () => {};
   const { listen } = await import('@tauri-apps/api/event');
-  return listen('chronicle:links-updated', () => {
-    linksStore.load();
+  return listen<{ workspaceId: string }[]>('chronicle:links-updated', (event) => {
+    // Other open workspaces have their own index
+    if (event.payload.some((c) => isActiveWorkspace(c.workspaceId))) {
+      linksStore.load();
+    }
   });
 }
@@ -127,6 +127,14 @@ function createNoteStore() {
         };
       }),
 
+    // Follow the open note to a new path (e.g. renamed on disk)
+    setPath: (path: string) =>
+      update((state) => {
+        if (!state.currentNote) return state;
+        syncAppState({ filePath: path, fileContent: state.lastSavedContent });
+        return { ...state, currentNote: { ...state.currentNote, path } };
+      }),
+
     // Close the current note
     closeNote: () => set(defaultState),
 
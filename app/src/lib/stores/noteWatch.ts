@@ -0,0 +1,92 @@
+import { writable, get } from 'svelte/store';
+import { getInvoke } from '$lib/utils/tauri';
+import { noteStore } from './note';
+import { workspaceStore, isActiveWorkspace } from './workspace';
+import { toast } from './toast';
+
+/** Payload of the backend's notes:created/modified/deleted/renamed events */
+export interface NoteEvent {
+  /** Open workspace the note belongs to */
+  workspaceId: string;
+  kind: 'created' | 'modified' | 'deleted' | 'renamed';
+  path: string;
+  relativePath: string;
+  /** Previous path, for renames */
+  from?: string;
+}
+
+/** One changed file in a chronicle:*-updated event (a batch of these per event) */
+export interface ChronicleChange {
+  /** Open workspace the file belongs to */
+  workspaceId: string;
+  path: string;
+  kind: 'created' | 'modified' | 'deleted';
+  /** Note a processed output belongs to */
+  note?: string;
+}
+
+/** Bumped whenever notes change on disk, so views that list notes (search) can refresh */
+export const notesRevision = writable(0);
+
+let refreshTimer: ReturnType<typeof setTimeout> | null = null;
+
+function fileName(path: string): string {
+  return path.substring(path.lastIndexOf('/') + 1);
+}
+
+/** A note changed on disk outside the editor */
+export function handleNoteEvent(event: NoteEvent) {
+  // Background workspaces are listed afresh when switched to
+  if (!isActiveWorkspace(event.workspaceId)) return;
+  notesRevision.update((n) => n + 1);
+
+  const state = get(noteStore);
+  const openPath = state.currentNote?.path;
+  if (event.kind === 'renamed' && event.from && event.from === openPath) {
+    noteStore.setPath(event.path);
+    toast.info(`${fileName(event.from)} was moved to ${event.relativePath}`);
+  } else if (event.kind === 'deleted' && event.path === openPath) {
+    toast.warning(`${fileName(event.path)} was deleted on disk. Save to keep it.`, 8000);
+  }
+
+  // The tree only changes shape when notes come and go; one refresh covers a batch
+  if (event.kind !== 'modified') {
+    if (refreshTimer) clearTimeout(refreshTimer);
+    refreshTimer = setTimeout(() => {
+      refreshTimer = null;
+      workspaceStore.refreshFiles();
+    }, 100);
+  }
+}
+
+/**
+ * The open note changed on disk and no longer matches what the editor loaded.
+ * Without local edits it is reloaded; with them the user picks which version wins.
+ */
+export async function handleNoteConflict(path: string) {
+  const state = get(noteStore);
+  if (state.currentNote?.path !== path) return;
+
+  try {
+    const invoke = await getInvoke();
+    const content = await invoke<string>('read_file', { path });
+    if (content === state.currentNote.content) return;
+
+    if (!state.isDirty) {
+      noteStore.openNote(path, content);
+      toast.info(`Reloaded ${fileName(path)}: it changed on disk`);
+      return;
+    }
+
+    const { confirm } = await import('@tauri-apps/plugin-dialog');
+    const reload = await confirm(
+      `"${fileName(path)}" changed on disk while you were editing it. Reload it and discard your changes, or keep your version?`,
+      { title: 'Note Changed on Disk', kind: 'warning', okLabel: 'Reload', cancelLabel: 'Keep Mine' }
+    );
+    if (reload && get(noteStore).currentNote?.path === path) {
+      noteStore.openNote(path, content);
+    }
+  } catch (e) {
+    console.error('Failed to handle note conflict:', e);
+  }
+}
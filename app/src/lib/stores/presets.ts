@@ -0,0 +1,77 @@
+import { writable, derived } from 'svelte/store';
+import { getInvoke } from '$lib/utils/tauri';
+import { workspaceStore } from './workspace';
+import { toast } from './toast';
+
+/** A workspace preset the creation wizard offers */
+export interface PresetInfo {
+  id: string;
+  name: string;
+  description: string;
+  /** Folders created in the new workspace */
+  folders: string[];
+  templates: string[];
+  prompts: string[];
+  commands: string[];
+  builtin: boolean;
+}
+
+function createPresetsStore() {
+  const { subscribe, set, update } = writable<PresetInfo[]>([]);
+
+  async function load() {
+    try {
+      const invoke = await getInvoke();
+      set(await invoke<PresetInfo[]>('list_workspace_presets'));
+    } catch (e) {
+      console.error('Failed to load workspace presets:', e);
+    }
+  }
+
+  return {
+    subscribe,
+
+    load,
+
+    /** Create a workspace folder in `parent` from a preset and open it */
+    createWorkspace: async (parent: string, name: string, presetId: string) => {
+      let path: string;
+      try {
+        const invoke = await getInvoke();
+        path = await invoke<string>('create_workspace', { parent, name, preset: presetId });
+      } catch (e) {
+        toast.error(String(e));
+        throw e;
+      }
+      return workspaceStore.openWorkspace(path);
+    },
+
+    /** Install a preset shared as a directory or .chronicle-preset archive */
+    install: async (path: string) => {
+      try {
+        const invoke = await getInvoke();
+        const preset = await invoke<PresetInfo>('install_workspace_preset', { path });
+        update((presets) => [...presets.filter((p) => p.id !== preset.id), preset]);
+        toast.success(`Installed the ${preset.name} preset`);
+        return preset;
+      } catch (e) {
+        toast.error(String(e));
+        throw e;
+      }
+    },
+
+    /** Pack a preset into a .chronicle-preset archive at `path` to share it */
+    export: async (id: string, path: string) => {
+      try {
+        const invoke = await getInvoke();
+        await invoke('export_workspace_preset', { id, path });
+      } catch (e) {
+        toast.error(String(e));
+        throw e;
+      }
+    },
+  };
+}
+
+export const presetsStore = createPresetsStore();
+export const builtinPresets = derived(presetsStore, ($p) => $p.filter((p) => p.builtin));
@@ -1,15 +1,41 @@
 import { writable, get } from 'svelte/store';
 import { getInvoke } from '$lib/utils/tauri';
-import { noteTitle } from './note';
+import { noteStore, noteTitle } from './note';
 import { currentWorkspace } from './workspace';
 import { toast } from './toast';
 
 export interface TrackerInfo {
+  pane_id: string;
   note_path: string;
+  /** Active editing time; idle and paused stretches don't count */
   duration_minutes: number;
   opened_at: string;
+  last_activity_at: string;
+  paused: boolean;
+  idle: boolean;
 }
 
+/** A session an earlier run of the app left unfinished */
+export interface OrphanedSession {
+  id: string;
+  notePath: string;
+  openedAt: string;
+  lastActivityAt: string;
+  durationMinutes: number;
+  hasUnsavedContent: boolean;
+}
+
+export interface RecoveredSession {
+  notePath: string;
+  durationMinutes: number;
+  restoredTo: string | null;
+  commitId: string | null;
+}
+
+/** Minimum gap between heartbeats sent to the backend */
+const HEARTBEAT_INTERVAL_MS = 30_000;
+let lastHeartbeatAt = 0;
+
 interface SessionStoreState {
   trackerInfo: TrackerInfo | null;
   isLoading: boolean;
@@ -84,6 +110,70 @@ function createSessionStore() {
       }
     },
 
+    // Report editor activity (throttled); time only accrues while there is activity.
+    // Unsaved content goes along so it can be recovered after a crash.
+    heartbeat: async () => {
+      const now = Date.now();
+      if (now - lastHeartbeatAt < HEARTBEAT_INTERVAL_MS) return;
+      lastHeartbeatAt = now;
+      try {
+        const invoke = await getInvoke();
+        const note = get(noteStore);
+        const unsavedContent = note.isDirty ? (note.currentNote?.content ?? null) : null;
+        await invoke('tracker_heartbeat', { unsavedContent });
+      } catch (e) {
+        console.error('[Session] Heartbeat failed:', e);
+      }
+    },
+
+    // Offer to recover sessions an earlier run left unfinished (quit or crash mid-edit)
+    recoverOrphanedSessions: async (workspacePath: string) => {
+      try {
+        const invoke = await getInvoke();
+        const orphans = await invoke<OrphanedSession[]>('list_orphaned_sessions', { workspacePath });
+        if (orphans.length === 0) return;
+
+        const { confirm } = await import('@tauri-apps/plugin-dialog');
+        for (const orphan of orphans) {
+          const name = orphan.notePath.substring(orphan.notePath.lastIndexOf('/') + 1);
+          const unsaved = orphan.hasUnsavedContent ? ' and unsaved changes' : '';
+          const recover = await confirm(
+            `Chronicle closed while "${name}" was open. Recover the session (${formatDuration(orphan.durationMinutes)})${unsaved}?`,
+            { title: 'Recover Session', kind: 'warning', okLabel: 'Recover', cancelLabel: 'Discard' }
+          );
+
+          if (recover) {
+            const recovered = await invoke<RecoveredSession>('recover_session', {
+              workspacePath,
+              sessionId: orphan.id,
+              restoreContent: true,
+            });
+            toast.success(
+              recovered.restoredTo
+                ? `Recovered unsaved changes to ${recovered.restoredTo.substring(recovered.restoredTo.lastIndexOf('/') + 1)}`
+                : `Session recovered (${formatDuration(recovered.durationMinutes)})`
+            );
+          } else {
+            await invoke('discard_orphaned_session', { workspacePath, sessionId: orphan.id });
+          }
+        }
+      } catch (e) {
+        console.error('[Session] Failed to recover sessions:', e);
+        toast.warning('Session recovery error');
+      }
+    },
+
+    // Pause or resume time tracking in every pane (e.g. window hidden)
+    setPaused: async (paused: boolean) => {
+      try {
+        const invoke = await getInvoke();
+        await invoke(paused ? 'pause_tracking' : 'resume_tracking');
+        if (!paused) lastHeartbeatAt = 0;
+      } catch (e) {
+        console.error('[Session] Failed to update pause state:', e);
+      }
+    },
+
     // Reset store
     reset: () => {
       set(defaultState);
@@ -1,6 +1,6 @@
 import { writable, derived, get } from 'svelte/store';
 import { isTauri, getInvoke } from '$lib/utils/tauri';
-import { currentWorkspace } from './workspace';
+import { currentWorkspace, isActiveWorkspace } from './workspace';
 import { parseTag, type TagCategory } from '$lib/utils/tagColors';
 
 export interface TagIndex {
@@ -105,7 +105,10 @@ export const tagFilteredPaths = derived(tagsStore, ($s) => {
 export async function initTagsListener(): Promise<() => void> {
   if (!isTauri()) return () => {};
   const { listen } = await import('@tauri-apps/api/event');
-  return listen('chronicle:tags-updated', () => {
-    tagsStore.load();
+  return listen<{ workspaceId: string }[]>('chronicle:tags-updated', (event) => {
+    // Other open workspaces have their own index
+    if (event.payload.some((c) => isActiveWorkspace(c.workspaceId))) {
+      tagsStore.load();
+    }
   });
 }
@@ -6,66 +6,298 @@ import { toast } from './toast';
 export interface FileNode {
   name: string;
   path: string;
-  type: 'file' | 'directory';
+  /** 'file' is a note; attachments are images, PDFs and other files notes link to */
+  type: 'file' | 'directory' | 'attachment';
+  /** Undefined for folders whose entries haven't been loaded yet */
   children?: FileNode[];
   modifiedAt?: string;
+  createdAt?: string;
+  attachmentKind?: 'image' | 'pdf' | 'audio' | 'video' | 'document';
 }
 
 export interface Workspace {
   path: string;
   name: string;
   lastOpened: string;
+  /** Pinned workspaces stay at the top of the recent list */
+  pinned: boolean;
+  /** Name the user gave it, shown instead of the folder name */
+  displayName?: string;
+  /** The folder no longer exists, e.g. it was moved or deleted */
+  missing: boolean;
+}
+
+/** Details of a recent workspace, loaded when its entry is hovered */
+export interface WorkspaceStats {
+  noteCount: number;
+  lastCommit: { id: string; summary: string; committedAt: string } | null;
+  remote: string | null;
 }
 
 export interface WorkspaceInfo {
+  /** ID of the open workspace, for commands that act on it */
+  id: string;
   path: string;
   name: string;
   isGitRepo: boolean;
-  fileCount: number;
+  /** Notes in the workspace; null until the background scan finishes */
+  fileCount: number | null;
+  /** Whether agents, commands and scheduled jobs may run here */
+  trusted: boolean;
+}
+
+/** A workspace open in the app; one tab of the workspace switcher */
+export interface OpenWorkspace {
+  id: string;
+  path: string;
+  name: string;
+  active: boolean;
+  trusted: boolean;
+}
+
+/** Order of the entries in each folder of the file tree */
+export type SortMode = 'mtime' | 'name' | 'created';
+
+/** Payload of the backend's workspace:scan-progress event */
+export interface ScanProgress {
+  workspaceId: string;
+  /** Note IDs and the watcher are set up before notes are counted */
+  stage: 'noteIds' | 'watcher' | 'notes';
+  scanned: number;
+  notes: number;
+  done: boolean;
 }
 
 interface WorkspaceState {
   currentWorkspace: WorkspaceInfo | null;
+  openWorkspaces: OpenWorkspace[];
   files: FileNode[];
+  sortMode: SortMode;
   recentWorkspaces: Workspace[];
   isLoading: boolean;
   error: string | null;
 }
 
+const SORT_KEY = 'chronicle:file-sort';
+
+/** Depth that lists a whole workspace */
+const FULL_TREE_DEPTH = 64;
+
+// Finished note counts by workspace ID; a small workspace can finish its scan
+// before open_workspace has returned
+const noteCounts = new Map<string, number>();
+
+function loadSortMode(): SortMode {
+  try {
+    const saved = localStorage.getItem(SORT_KEY);
+    if (saved === 'mtime' || saved === 'name' || saved === 'created') return saved;
+  } catch {
+    // Ignore storage errors, use the default
+  }
+  return 'mtime';
+}
+
+/** Folders in a tree whose entries have been loaded */
+function loadedDirectories(nodes: FileNode[], into: string[] = []): string[] {
+  for (const node of nodes) {
+    if (node.type === 'directory' && node.children) {
+      into.push(node.path);
+      loadedDirectories(node.children, into);
+    }
+  }
+  return into;
+}
+
+/** A tree with one folder's entries filled in */
+function withChildren(nodes: FileNode[], path: string, children: FileNode[]): FileNode[] {
+  return nodes.map((node) => {
+    if (node.type !== 'directory') return node;
+    if (node.path === path) return { ...node, children };
+    if (node.children && (path.startsWith(node.path + '/') || path.startsWith(node.path + '\\'))) {
+      return { ...node, children: withChildren(node.children, path, children) };
+    }
+    return node;
+  });
+}
+
 const defaultState: WorkspaceState = {
   currentWorkspace: null,
+  openWorkspaces: [],
   files: [],
+  sortMode: 'mtime',
   recentWorkspaces: [],
   isLoading: false,
   error: null,
 };
 
 function createWorkspaceStore() {
-  const { subscribe, set, update } = writable<WorkspaceState>(defaultState);
+  const { subscribe, set, update } = writable<WorkspaceState>({
+    ...defaultState,
+    sortMode: loadSortMode(),
+  });
+
+  async function listDirectory(path: string, depth: number): Promise<FileNode[]> {
+    const invoke = await getInvoke();
+    return invoke<FileNode[]>('list_directory', { path, depth, sort: get({ subscribe }).sortMode });
+  }
+
+  // Top-level folders start expanded, so the root is listed two levels deep.
+  // Deeper folders are listed as they are expanded; the ones already loaded in
+  // `previous` are listed again so a refresh doesn't collapse them.
+  async function loadTree(workspacePath: string, previous: FileNode[] = []): Promise<FileNode[]> {
+    let files = await listDirectory(workspacePath, 2);
+    const loaded = new Set(loadedDirectories(files));
+    for (const dir of loadedDirectories(previous)) {
+      if (loaded.has(dir)) continue;
+      try {
+        files = withChildren(files, dir, await listDirectory(dir, 1));
+        loaded.add(dir);
+      } catch {
+        // The folder is gone
+      }
+    }
+    return files;
+  }
+
+  async function setTrust(trusted: boolean) {
+    const state = get({ subscribe });
+    if (!state.currentWorkspace) return;
+    const invoke = await getInvoke();
+    await invoke('set_workspace_trust', {
+      workspacePath: state.currentWorkspace.path,
+      trusted,
+    });
+    update((s) => ({
+      ...s,
+      currentWorkspace: s.currentWorkspace ? { ...s.currentWorkspace, trusted } : null,
+      openWorkspaces: s.openWorkspaces.map((w) =>
+        w.id === s.currentWorkspace?.id ? { ...w, trusted } : w
+      ),
+    }));
+  }
+
+  // The open note belongs to the workspace being left: save it, end its session
+  // and close it so nothing of it shows in the next workspace
+  async function leaveCurrentNote() {
+    const { noteStore } = await import('./note');
+    const { autoSaveStore } = await import('./autosave');
+    const { sessionStore } = await import('./session');
+    if (get(noteStore).isDirty) {
+      await autoSaveStore.saveNow();
+    }
+    await sessionStore.stopTracking();
+    autoSaveStore.reset();
+    noteStore.closeNote();
+  }
+
+  async function loadOpenWorkspaces() {
+    const invoke = await getInvoke();
+    const openWorkspaces = await invoke<OpenWorkspace[]>('list_open_workspaces');
+    update((s) => ({ ...s, openWorkspaces }));
+  }
+
+  /** Run a command that changes the recent list and show the list it returns */
+  async function updateRecent(command: string, args: Record<string, unknown>) {
+    try {
+      const invoke = await getInvoke();
+      const recentWorkspaces = await invoke<Workspace[]>(command, args);
+      update((s) => ({ ...s, recentWorkspaces }));
+    } catch (e) {
+      console.error('Failed to update recent workspaces:', e);
+      toast.error(String(e));
+    }
+  }
+
+  async function openWorkspace(path: string) {
+    const previous = get({ subscribe }).currentWorkspace;
+    update((s) => ({ ...s, isLoading: true, error: null }));
+    try {
+      if (previous) {
+        await leaveCurrentNote();
+      }
+      const invoke = await getInvoke();
+      const info = await invoke<WorkspaceInfo>('open_workspace', { path });
+      const files = await loadTree(info.path);
+      update((s) => ({
+        ...s,
+        currentWorkspace: { ...info, fileCount: info.fileCount ?? noteCounts.get(info.id) ?? null },
+        files,
+        isLoading: false,
+      }));
+      await loadOpenWorkspaces();
+      // Sync workspace path to backend for MCP server
+      await syncAppState({ workspacePath: info.path });
+      // Before any note opens, so recovered content is what gets loaded
+      const { sessionStore } = await import('./session');
+      await sessionStore.recoverOrphanedSessions(info.path);
+      if (!info.trusted) {
+        await promptTrust(info);
+      }
+      return get({ subscribe }).currentWorkspace ?? info;
+    } catch (e) {
+      const error = e instanceof Error ? e.message : String(e);
+      toast.error('Failed to open workspace');
+      update((s) => ({ ...s, isLoading: false, error }));
+      throw e;
+    }
+  }
+
+  // Until the user trusts a workspace, agents, custom commands and scheduled jobs
+  // don't run in it. Declining keeps it restricted; it can be trusted later.
+  async function promptTrust(info: WorkspaceInfo) {
+    try {
+      const { confirm } = await import('@tauri-apps/plugin-dialog');
+      const trust = await confirm(
+        `Do you trust the notes in "${info.name}"? Trusting it lets Claude agents, custom commands and scheduled jobs run in this workspace.`,
+        { title: 'Trust Workspace', kind: 'warning', okLabel: 'Trust', cancelLabel: 'Not Now' }
+      );
+      if (trust) {
+        await setTrust(true);
+        toast.success(`Trusted ${info.name}`);
+      } else {
+        toast.info('Workspace is restricted: agents and commands are disabled');
+      }
+    } catch (e) {
+      console.error('Failed to set workspace trust:', e);
+      toast.error('Failed to trust workspace');
+    }
+  }
 
   return {
     subscribe,
 
-    openWorkspace: async (path: string) => {
-      update((s) => ({ ...s, isLoading: true, error: null }));
+    openWorkspace,
+
+    /** Make another open workspace the active one */
+    switchWorkspace: async (id: string) => {
+      const state = get({ subscribe });
+      const target = state.openWorkspaces.find((w) => w.id === id);
+      if (!target || target.id === state.currentWorkspace?.id) return;
+      return openWorkspace(target.path);
+    },
+
+    /** Close an open workspace; closing the active one switches to the next still open */
+    closeOpenWorkspace: async (id: string) => {
+      const state = get({ subscribe });
+      const isActive = state.currentWorkspace?.id === id;
       try {
+        if (isActive) {
+          await leaveCurrentNote();
+        }
         const invoke = await getInvoke();
-        const info = await invoke<WorkspaceInfo>('open_workspace', { path });
-        const files = await invoke<FileNode[]>('list_workspace_files', { workspacePath: path });
-        update((s) => ({
-          ...s,
-          currentWorkspace: info,
-          files,
-          isLoading: false,
-        }));
-        // Sync workspace path to backend for MCP server
-        await syncAppState({ workspacePath: path });
-        return info;
+        await invoke('close_workspace', { workspaceId: id });
+        await loadOpenWorkspaces();
+        if (!isActive) return;
+
+        const next = get({ subscribe }).openWorkspaces[0];
+        if (next) {
+          await openWorkspace(next.path);
+        } else {
+          update((s) => ({ ...s, currentWorkspace: null, files: [] }));
+        }
       } catch (e) {
-        const error = e instanceof Error ? e.message : String(e);
-        toast.error('Failed to open workspace');
-        update((s) => ({ ...s, isLoading: false, error }));
-        throw e;
+        console.error('Failed to close workspace:', e);
+        toast.error('Failed to close workspace');
       }
     },
 
@@ -75,10 +307,7 @@ function createWorkspaceStore() {
 
       update((s) => ({ ...s, isLoading: true }));
       try {
-        const invoke = await getInvoke();
-        const files = await invoke<FileNode[]>('list_workspace_files', {
-          workspacePath: state.currentWorkspace.path,
-        });
+        const files = await loadTree(state.currentWorkspace.path, state.files);
         update((s) => ({ ...s, files, isLoading: false }));
       } catch (e) {
         const error = e instanceof Error ? e.message : String(e);
@@ -86,6 +315,58 @@ function createWorkspaceStore() {
       }
     },
 
+    /** List a folder's entries the first time it is expanded */
+    loadDirectory: async (path: string) => {
+      try {
+        const children = await listDirectory(path, 1);
+        update((s) => ({ ...s, files: withChildren(s.files, path, children) }));
+      } catch (e) {
+        console.error('Failed to list folder:', e);
+        toast.error('Failed to list folder');
+      }
+    },
+
+    /** List every folder, for views that filter the whole tree (e.g. by tag) */
+    loadAllDirectories: async () => {
+      const state = get({ subscribe });
+      if (!state.currentWorkspace) return;
+      try {
+        const files = await listDirectory(state.currentWorkspace.path, FULL_TREE_DEPTH);
+        update((s) => ({ ...s, files }));
+      } catch (e) {
+        console.error('Failed to list files:', e);
+      }
+    },
+
+    /** Change how folders are sorted and list the tree again in that order */
+    setSortMode: async (sortMode: SortMode) => {
+      try {
+        localStorage.setItem(SORT_KEY, sortMode);
+      } catch {
+        // Ignore storage errors
+      }
+      const state = get({ subscribe });
+      update((s) => ({ ...s, sortMode }));
+      if (!state.currentWorkspace) return;
+      try {
+        const files = await loadTree(state.currentWorkspace.path, state.files);
+        update((s) => ({ ...s, files }));
+      } catch (e) {
+        console.error('Failed to sort files:', e);
+      }
+    },
+
+    /** The background note count of the active workspace finished */
+    setScanProgress: (progress: ScanProgress) => {
+      if (!progress.done) return;
+      noteCounts.set(progress.workspaceId, progress.notes);
+      update((s) =>
+        s.currentWorkspace?.id === progress.workspaceId
+          ? { ...s, currentWorkspace: { ...s.currentWorkspace, fileCount: progress.notes } }
+          : s
+      );
+    },
+
     loadRecentWorkspaces: async () => {
       try {
         const invoke = await getInvoke();
@@ -96,7 +377,37 @@ function createWorkspaceStore() {
       }
     },
 
-    closeWorkspace: () => set(defaultState),
+    pinRecent: (path: string, pinned: boolean) =>
+      updateRecent('set_recent_workspace_pinned', { path, pinned }),
+
+    /** Take a workspace off the recent list; its folder is left alone */
+    removeRecent: (path: string) => updateRecent('remove_recent_workspace', { path }),
+
+    /** Show a recent workspace under another name; an empty name resets it */
+    renameRecent: (path: string, name: string | null) =>
+      updateRecent('rename_recent_workspace', { path, name: name || null }),
+
+    /** Point a recent workspace at the folder it was moved to */
+    relocateRecent: (from: string, to: string) => updateRecent('relocate_workspace', { from, to }),
+
+    loadWorkspaceStats: async (path: string): Promise<WorkspaceStats | null> => {
+      try {
+        const invoke = await getInvoke();
+        return await invoke<WorkspaceStats>('get_workspace_stats', { path });
+      } catch (e) {
+        console.error('Failed to load workspace stats:', e);
+        return null;
+      }
+    },
+
+    setTrust,
+
+    promptTrust: async () => {
+      const state = get({ subscribe });
+      if (state.currentWorkspace) await promptTrust(state.currentWorkspace);
+    },
+
+    closeWorkspace: () => set({ ...defaultState, sortMode: get({ subscribe }).sortMode }),
 
     clearError: () => update((s) => ({ ...s, error: null })),
   };
@@ -111,3 +422,10 @@ export const hasWorkspace = derived(workspaceStore, ($ws) => $ws.currentWorkspac
 export const workspaceLoading = derived(workspaceStore, ($ws) => $ws.isLoading);
 export const workspaceError = derived(workspaceStore, ($ws) => $ws.error);
 export const recentWorkspaces = derived(workspaceStore, ($ws) => $ws.recentWorkspaces);
+export const openWorkspaces = derived(workspaceStore, ($ws) => $ws.openWorkspaces);
+export const fileSortMode = derived(workspaceStore, ($ws) => $ws.sortMode);
+
+/** Whether a backend event concerns the workspace shown in the window */
+export function isActiveWorkspace(workspaceId: string | undefined): boolean {
+  return workspaceId !== undefined && get(workspaceStore).currentWorkspace?.id === workspaceId;
+}